@@ -0,0 +1,150 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::schema::{serialize_documents_parallel, Document, FieldValue, Schema, STORED, TEXT};
+use tantivy::BinarySerializable;
+
+// The former implementation of `Document::eq`, sorting all of the field values.
+fn sorted_field_values_eq(left: &Document, right: &Document) -> bool {
+    let mut left_field_values: Vec<&FieldValue> = left.field_values().iter().collect();
+    let mut right_field_values: Vec<&FieldValue> = right.field_values().iter().collect();
+    left_field_values.sort();
+    right_field_values.sort();
+    left_field_values == right_field_values
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let payload = schema_builder.add_bytes_field("payload", STORED);
+    let mut doc = Document::default();
+    doc.add_text(title, "The Old Man and the Sea");
+    for _ in 0..10 {
+        doc.add_bytes(payload, vec![7u8; 100_000]);
+    }
+    let mut buffer = Vec::new();
+    doc.serialize(&mut buffer).unwrap();
+
+    c.bench_function("document-deserialize", |b| {
+        b.iter(|| Document::deserialize(&mut &buffer[..]).unwrap())
+    });
+    c.bench_function("document-skip-deserialize", |b| {
+        b.iter(|| Document::skip_deserialize(&mut &buffer[..]).unwrap())
+    });
+
+    let mut schema_builder = Schema::builder();
+    let fields: Vec<_> = (0..50)
+        .map(|i| schema_builder.add_u64_field(&format!("field{}", i), STORED))
+        .collect();
+    let mut doc = Document::default();
+    for _ in 0..4 {
+        for &field in fields.iter().rev() {
+            doc.add_u64(field, 1u64);
+        }
+    }
+    c.bench_function("document-get-sorted-field-values", |b| {
+        b.iter(|| doc.get_sorted_field_values().len())
+    });
+    doc.sort_field_values();
+    c.bench_function("document-get-sorted-field-values-presorted", |b| {
+        b.iter(|| doc.get_sorted_field_values().len())
+    });
+
+    let mut schema_builder = Schema::builder();
+    let tag = schema_builder.add_text_field("tag", TEXT);
+    let tags: Vec<String> = (0..10_000).map(|i| format!("tag{}", i)).collect();
+    c.bench_function("document-add-text-10k-tags", |b| {
+        b.iter(|| {
+            let mut doc = Document::default();
+            for tag_str in &tags {
+                doc.add_text(tag, tag_str);
+            }
+            doc
+        })
+    });
+    c.bench_function("document-add-texts-10k-tags", |b| {
+        b.iter(|| {
+            let mut doc = Document::default();
+            doc.add_texts(tag, &tags);
+            doc
+        })
+    });
+
+    let mut schema_builder = Schema::builder();
+    let count = schema_builder.add_u64_field("count", STORED);
+    c.bench_function("document-add-u64-100k", |b| {
+        b.iter(|| {
+            let mut doc = Document::default();
+            for val in 0u64..100_000u64 {
+                doc.add_u64(count, val);
+            }
+            doc
+        })
+    });
+    c.bench_function("document-add-u64s-100k", |b| {
+        b.iter(|| {
+            let mut doc = Document::default();
+            doc.add_u64s(count, 0u64..100_000u64);
+            doc
+        })
+    });
+
+    let mut schema_builder = Schema::builder();
+    let fields: Vec<_> = (0..100)
+        .map(|i| schema_builder.add_text_field(&format!("field{}", i), STORED))
+        .collect();
+    let mut doc = Document::default();
+    for (i, &field) in fields.iter().enumerate() {
+        doc.add_text(field, format!("value{}", i));
+    }
+    let same_order_doc = doc.clone();
+    let reversed_doc: Document = doc.field_values().iter().rev().cloned().collect();
+    c.bench_function("document-eq-100-fields-sorted-baseline", |b| {
+        b.iter(|| sorted_field_values_eq(&doc, &same_order_doc))
+    });
+    c.bench_function("document-eq-100-fields", |b| {
+        b.iter(|| doc == same_order_doc)
+    });
+    c.bench_function("document-eq-100-fields-reversed-sorted-baseline", |b| {
+        b.iter(|| sorted_field_values_eq(&doc, &reversed_doc))
+    });
+    c.bench_function("document-eq-100-fields-reversed", |b| {
+        b.iter(|| doc == reversed_doc)
+    });
+}
+
+pub fn serialize_documents_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let count = schema_builder.add_u64_field("count", STORED);
+    let docs: Vec<Document> = (0..1_000_000u64)
+        .map(|i| {
+            let mut doc = Document::default();
+            doc.add_text(title, format!("The Old Man and the Sea, copy {}", i));
+            doc.add_u64(count, i);
+            doc
+        })
+        .collect();
+    c.bench_function("document-serialize-1m", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            for doc in &docs {
+                doc.serialize(&mut buffer).unwrap();
+            }
+            buffer
+        })
+    });
+    c.bench_function("document-serialize-parallel-1m", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            serialize_documents_parallel(&docs, &mut buffer).unwrap();
+            buffer
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_group! {
+    name = serialize_documents_benches;
+    config = Criterion::default().sample_size(10);
+    targets = serialize_documents_benchmark
+}
+criterion_main!(benches, serialize_documents_benches);
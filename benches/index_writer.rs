@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use crossbeam::thread;
+use tantivy::schema::{Document, Field, Schema, STORED, TEXT};
+use tantivy::{doc, Index, IndexWriter};
+
+const NUM_PRODUCERS: usize = 4;
+const NUM_DOCS_PER_PRODUCER: usize = 5_000;
+
+fn documents(title: Field, producer: usize) -> Vec<Document> {
+    (0..NUM_DOCS_PER_PRODUCER)
+        .map(|i| doc!(title=>format!("producer {} doc {}", producer, i)))
+        .collect()
+}
+
+// Adds the documents of `NUM_PRODUCERS` producer threads, sharing a single
+// index writer, with `add_document` or `add_documents`.
+fn index_documents(bulk: bool) {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_with_num_threads(2, 100_000_000).unwrap();
+    thread::scope(|scope| {
+        for producer in 0..NUM_PRODUCERS {
+            let index_writer = &index_writer;
+            scope.spawn(move |_| {
+                let docs = documents(title, producer);
+                if bulk {
+                    index_writer.add_documents(docs).unwrap();
+                } else {
+                    for doc in docs {
+                        index_writer.add_document(doc);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+    index_writer.commit().unwrap();
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    c.bench_function("index-writer-add-document", |b| {
+        b.iter(|| index_documents(false))
+    });
+    c.bench_function("index-writer-add-documents", |b| {
+        b.iter(|| index_documents(true))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = criterion_benchmark
+}
+criterion_main!(benches);
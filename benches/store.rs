@@ -0,0 +1,106 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tantivy::schema::{Schema, STORED, STRING};
+use tantivy::{doc, Index};
+
+const NUM_DOCS: usize = 100;
+const PAYLOAD_LEN: usize = 64 * 1024;
+const NUM_STATUS_DOCS: usize = 100_000;
+const STATUSES: [&str; 5] = [
+    "awaiting-payment",
+    "payment-received",
+    "shipped-to-customer",
+    "delivered-to-customer",
+    "returned-by-customer",
+];
+
+// Builds a thumbnail-like payload: smooth gradients with some noise.
+fn payload(seed: usize) -> Vec<u8> {
+    (0..PAYLOAD_LEN)
+        .map(|i| ((i / 256 + seed) % 251) as u8 ^ ((i * 7919 + seed) % 13) as u8)
+        .collect()
+}
+
+// Indexes `NUM_DOCS` documents carrying a large bytes payload,
+// and returns the number of bytes of the resulting doc store.
+fn index_payloads(payloads: &[Vec<u8>], bytes_compression_threshold: Option<usize>) -> usize {
+    let mut schema_builder = Schema::builder();
+    let payload_field = schema_builder.add_bytes_field("payload", STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+    index_writer
+        .set_bytes_compression_threshold(bytes_compression_threshold)
+        .unwrap();
+    for payload in payloads {
+        index_writer.add_document(doc!(payload_field=>payload.clone()));
+    }
+    index_writer.commit().unwrap();
+    let searcher = index.reader().unwrap().searcher();
+    searcher
+        .space_usage()
+        .unwrap()
+        .segments()
+        .iter()
+        .map(|segment| segment.store().total())
+        .sum()
+}
+
+// Indexes `NUM_STATUS_DOCS` documents with a low-cardinality stored field,
+// and returns the number of bytes of the resulting doc store.
+fn index_statuses(interned: bool) -> usize {
+    let mut schema_builder = Schema::builder();
+    let status_field = schema_builder.add_text_field("status", STRING | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+    if interned {
+        index_writer.set_interned_fields(vec![status_field]);
+    }
+    for i in 0..NUM_STATUS_DOCS {
+        // a pseudo-random sequence, so that the blocks of the store
+        // do not simply repeat the same pattern.
+        let status = STATUSES[(i * 7_919 + i / 3) % STATUSES.len()];
+        index_writer.add_document(doc!(status_field=>status));
+    }
+    index_writer.commit().unwrap();
+    let searcher = index.reader().unwrap().searcher();
+    searcher
+        .space_usage()
+        .unwrap()
+        .segments()
+        .iter()
+        .map(|segment| segment.store().total())
+        .sum()
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let payloads: Vec<Vec<u8>> = (0..NUM_DOCS).map(payload).collect();
+    println!(
+        "store size for {} payloads of {} bytes: {} bytes uncompressed, {} bytes compressed",
+        NUM_DOCS,
+        PAYLOAD_LEN,
+        index_payloads(&payloads, None),
+        index_payloads(&payloads, Some(1_024))
+    );
+    println!(
+        "store size for {} documents with a status: {} bytes, {} bytes with interned strings",
+        NUM_STATUS_DOCS,
+        index_statuses(false),
+        index_statuses(true)
+    );
+    c.bench_function("store-bytes-uncompressed", |b| {
+        b.iter(|| index_payloads(&payloads, None))
+    });
+    c.bench_function("store-bytes-compressed", |b| {
+        b.iter(|| index_payloads(&payloads, Some(1_024)))
+    });
+    c.bench_function("store-statuses", |b| b.iter(|| index_statuses(false)));
+    c.bench_function("store-statuses-interned", |b| {
+        b.iter(|| index_statuses(true))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = criterion_benchmark
+}
+criterion_main!(benches);
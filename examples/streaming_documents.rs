@@ -0,0 +1,51 @@
+// # Streaming documents example
+//
+// This example shows how to use a `DocumentWriter` to serialize
+// documents one field value at a time, without ever building
+// the whole list of field values in memory.
+//
+// In this example we will:
+// - serialize a batch of documents into a buffer (think of a file
+// produced by a bulk import),
+// - stream these documents back, one at a time, into the `IndexWriter`.
+
+use tantivy::collector::Count;
+use tantivy::query::AllQuery;
+use tantivy::schema::*;
+use tantivy::{BinarySerializable, Index};
+
+fn main() -> tantivy::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let tag = schema_builder.add_text_field("tag", STRING | STORED);
+    let schema = schema_builder.build();
+
+    // Each document has a title and 100 tags.
+    // The `DocumentWriter` needs to know upfront how many field values
+    // will be written.
+    let mut buffer: Vec<u8> = Vec::new();
+    for doc_id in 0..1_000 {
+        let mut doc_writer = DocumentWriter::new(&mut buffer, 101)?;
+        doc_writer.add_text(title, &format!("Document #{}", doc_id))?;
+        for tag_id in 0..100 {
+            doc_writer.add_text(tag, &format!("tag{}", (doc_id + tag_id) % 250))?;
+        }
+        doc_writer.finish()?;
+    }
+
+    let index = Index::create_in_ram(schema);
+    let mut index_writer = index.writer(50_000_000)?;
+
+    // The `IndexWriter` consumes `Document`s: we only materialize
+    // one document at a time.
+    let mut cursor = &buffer[..];
+    while !cursor.is_empty() {
+        let doc = Document::deserialize(&mut cursor)?;
+        index_writer.add_document(doc);
+    }
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.search(&AllQuery, &Count)?, 1_000);
+    Ok(())
+}
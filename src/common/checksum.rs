@@ -0,0 +1,93 @@
+use crc32fast::Hasher;
+use std::io;
+use std::io::{Read, Write};
+
+/// Writer computing the crc32 checksum of the bytes written through it.
+pub(crate) struct ChecksumWriter<W> {
+    underlying: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub fn wrap(underlying: W) -> ChecksumWriter<W> {
+        ChecksumWriter {
+            underlying,
+            hasher: Hasher::new(),
+        }
+    }
+
+    /// Returns the underlying write object, and the checksum
+    /// of the bytes written so far.
+    pub fn finish(self) -> (W, u32) {
+        (self.underlying, self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written_size = self.underlying.write(buf)?;
+        self.hasher.update(&buf[..written_size]);
+        Ok(written_size)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.underlying.write_all(buf)?;
+        self.hasher.update(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+/// Reader computing the crc32 checksum of the bytes read through it.
+pub(crate) struct ChecksumReader<R> {
+    underlying: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub fn wrap(underlying: R) -> ChecksumReader<R> {
+        ChecksumReader {
+            underlying,
+            hasher: Hasher::new(),
+        }
+    }
+
+    /// Returns the underlying read object, and the checksum
+    /// of the bytes read so far.
+    pub fn finish(self) -> (R, u32) {
+        (self.underlying, self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_size = self.underlying.read(buf)?;
+        self.hasher.update(&buf[..read_size]);
+        Ok(read_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{ChecksumReader, ChecksumWriter};
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_checksum_reader_writer() {
+        let mut buffer = Vec::new();
+        let mut writer = ChecksumWriter::wrap(&mut buffer);
+        writer.write_all(b"hello").unwrap();
+        writer.write_all(b" world").unwrap();
+        let (_, written_checksum) = writer.finish();
+        assert_eq!(written_checksum, crc32fast::hash(b"hello world"));
+        let mut reader = ChecksumReader::wrap(&buffer[..]);
+        let mut read_buffer = Vec::new();
+        reader.read_to_end(&mut read_buffer).unwrap();
+        let (_, read_checksum) = reader.finish();
+        assert_eq!(read_checksum, written_checksum);
+    }
+}
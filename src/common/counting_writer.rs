@@ -1,7 +1,7 @@
 use crate::directory::AntiCallToken;
 use crate::directory::TerminatingWrite;
 use std::io;
-use std::io::Write;
+use std::io::{Read, Write};
 
 pub struct CountingWriter<W> {
     underlying: W,
@@ -51,6 +51,33 @@ impl<W: TerminatingWrite> TerminatingWrite for CountingWriter<W> {
     }
 }
 
+/// Reader counting the bytes read through it.
+pub(crate) struct CountingReader<R> {
+    underlying: R,
+    read_bytes: u64,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub fn wrap(underlying: R) -> CountingReader<R> {
+        CountingReader {
+            underlying,
+            read_bytes: 0,
+        }
+    }
+
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_size = self.underlying.read(buf)?;
+        self.read_bytes += read_size as u64;
+        Ok(read_size)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
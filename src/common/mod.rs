@@ -1,5 +1,6 @@
 pub mod bitpacker;
 mod bitset;
+mod checksum;
 mod composite_file;
 mod counting_writer;
 mod serialize;
@@ -7,7 +8,9 @@ mod vint;
 
 pub use self::bitset::BitSet;
 pub(crate) use self::bitset::TinySet;
+pub(crate) use self::checksum::{ChecksumReader, ChecksumWriter};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
+pub(crate) use self::counting_writer::CountingReader;
 pub use self::counting_writer::CountingWriter;
 pub use self::serialize::{BinarySerializable, FixedSize};
 pub use self::vint::{
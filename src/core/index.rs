@@ -20,6 +20,7 @@ use crate::reader::IndexReaderBuilder;
 use crate::schema::Field;
 use crate::schema::FieldType;
 use crate::schema::Schema;
+use crate::store::{DefaultCodec, DocumentCodec, DEFAULT_CODEC_NAME};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::IndexWriter;
 use std::collections::HashSet;
@@ -53,6 +54,7 @@ pub struct Index {
     schema: Schema,
     executor: Arc<Executor>,
     tokenizers: TokenizerManager,
+    doc_store_codec: Arc<dyn DocumentCodec>,
     inventory: SegmentMetaInventory,
 }
 
@@ -170,11 +172,51 @@ impl Index {
             directory,
             schema,
             tokenizers: TokenizerManager::default(),
+            doc_store_codec: Arc::new(DefaultCodec::default()),
             executor: Arc::new(Executor::single_thread()),
             inventory,
         })
     }
 
+    /// Accessor to the codec encoding the documents in the doc store.
+    pub fn doc_store_codec(&self) -> Arc<dyn DocumentCodec> {
+        self.doc_store_codec.clone()
+    }
+
+    /// Replaces the codec encoding the documents in the doc store,
+    /// a [`DefaultCodec`](../store/struct.DefaultCodec.html) by default.
+    ///
+    /// Just like the tokenizers, the codec is not persisted with the index:
+    /// it must be set every time the index is opened, before creating
+    /// any index writer or reader, and must remain the same for the whole
+    /// life of the index. Its name is recorded in the meta of the index on commit:
+    /// creating an index writer or reader fails if the index has segments
+    /// written with a codec of another name.
+    pub fn set_doc_store_codec(&mut self, doc_store_codec: Arc<dyn DocumentCodec>) {
+        self.doc_store_codec = doc_store_codec;
+    }
+
+    /// Checks that the segments of the index were written with
+    /// a codec of the same name as the doc store codec of the index.
+    pub(crate) fn check_doc_store_codec(&self, metas: &IndexMeta) -> crate::Result<()> {
+        if metas.segments.is_empty() {
+            return Ok(());
+        }
+        let index_codec_name = metas
+            .doc_store_codec
+            .as_deref()
+            .unwrap_or(DEFAULT_CODEC_NAME);
+        if index_codec_name != self.doc_store_codec.name() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The index was written with the doc store codec {:?}, but is opened with the \
+                 codec {:?}. See Index::set_doc_store_codec.",
+                index_codec_name,
+                self.doc_store_codec.name()
+            )));
+        }
+        Ok(())
+    }
+
     /// Accessor for the tokenizer manager.
     pub fn tokenizers(&self) -> &TokenizerManager {
         &self.tokenizers
@@ -280,6 +322,7 @@ impl Index {
         num_threads: usize,
         overall_heap_size_in_bytes: usize,
     ) -> crate::Result<IndexWriter> {
+        self.check_doc_store_codec(&self.load_metas()?)?;
         let directory_lock = self
             .directory
             .acquire_lock(&INDEX_WRITER_LOCK)
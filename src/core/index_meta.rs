@@ -217,6 +217,13 @@ pub struct IndexMeta {
     /// to help identify this commit.
     /// This payload is entirely unused by tantivy.
     pub payload: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Name of the codec the documents of the segments are stored with,
+    /// see [`DocumentCodec::name`](../store/trait.DocumentCodec.html#tymethod.name).
+    ///
+    /// It is not set for the indexes created before the codec was recorded,
+    /// which use the default codec.
+    pub doc_store_codec: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -226,6 +233,8 @@ struct UntrackedIndexMeta {
     pub opstamp: Opstamp,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
+    #[serde(default)]
+    pub doc_store_codec: Option<String>,
 }
 
 impl UntrackedIndexMeta {
@@ -239,6 +248,7 @@ impl UntrackedIndexMeta {
             schema: self.schema,
             opstamp: self.opstamp,
             payload: self.payload,
+            doc_store_codec: self.doc_store_codec,
         }
     }
 }
@@ -255,6 +265,7 @@ impl IndexMeta {
             schema,
             opstamp: 0u64,
             payload: None,
+            doc_store_codec: None,
         }
     }
 
@@ -297,6 +308,7 @@ mod tests {
             schema,
             opstamp: 0u64,
             payload: None,
+            doc_store_codec: None,
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
@@ -12,7 +12,7 @@ use crate::schema::FieldType;
 use crate::schema::Schema;
 use crate::schema::{Field, IndexRecordOption};
 use crate::space_usage::SegmentSpaceUsage;
-use crate::store::StoreReader;
+use crate::store::{DocumentCodec, StoreReader};
 use crate::termdict::TermDictionary;
 use crate::DocId;
 use crate::{common::CompositeFile, error::DataCorruption};
@@ -51,6 +51,7 @@ pub struct SegmentReader {
     fieldnorm_readers: FieldNormReaders,
 
     store_file: FileSlice,
+    store_codec: Arc<dyn DocumentCodec>,
     delete_bitset_opt: Option<DeleteBitSet>,
     schema: Schema,
 }
@@ -148,7 +149,7 @@ impl SegmentReader {
 
     /// Accessor to the segment's `StoreReader`.
     pub fn get_store_reader(&self) -> io::Result<StoreReader> {
-        StoreReader::open(self.store_file.clone())
+        StoreReader::open_with_codec(self.store_file.clone(), self.store_codec.clone())
     }
 
     /// Open a new segment for reading.
@@ -207,6 +208,7 @@ impl SegmentReader {
             fieldnorm_readers,
             segment_id: segment.id(),
             store_file,
+            store_codec: segment.index().doc_store_codec(),
             delete_bitset_opt,
             positions_composite,
             positions_idx_composite,
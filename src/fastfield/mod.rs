@@ -186,11 +186,17 @@ impl FastValue for crate::DateTime {
     }
 }
 
+/// Returns the u64 representation of the value of a numeric fast field.
+///
+/// Booleans are accepted by u64 fields, as `0` and `1`, and f32 values
+/// by f64 fields, widened to f64.
 fn value_to_u64(value: &Value) -> u64 {
     match *value {
         Value::U64(ref val) => *val,
+        Value::Bool(val) => u64::from(val),
         Value::I64(ref val) => common::i64_to_u64(*val),
         Value::F64(ref val) => common::f64_to_u64(*val),
+        Value::F32(val) => common::f64_to_u64(f64::from(val)),
         Value::Date(ref datetime) => common::i64_to_u64(datetime.timestamp()),
         _ => panic!("Expected a u64/i64/f64 field, got {:?} ", value),
     }
@@ -217,11 +217,11 @@ impl IntFastFieldWriter {
     /// in `common::i64_to_u64` and `common::f64_to_u64`.
     ///
     /// If the value is missing, then the default value is used
-    /// instead.
+    /// instead. `Null` values are considered missing.
     /// If the document has more than one value for the given field,
     /// only the first one is taken in account.
     fn extract_val(&self, doc: &Document) -> u64 {
-        match doc.get_first(self.field) {
+        match doc.get_all(self.field).find(|value| !value.is_null()) {
             Some(v) => super::value_to_u64(v),
             None => self.val_if_missing,
         }
@@ -1,8 +1,9 @@
 use super::operation::{AddOperation, UserOperation};
 use super::segment_updater::SegmentUpdater;
-use super::PreparedCommit;
+use super::{PreparedCommit, RollbackResult};
 use crate::common::BitSet;
 use crate::core::Index;
+use crate::core::Searcher;
 use crate::core::Segment;
 use crate::core::SegmentComponent;
 use crate::core::SegmentId;
@@ -15,23 +16,29 @@ use crate::error::TantivyError;
 use crate::fastfield::write_delete_bitset;
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
-use crate::indexer::operation::DeleteOperation;
+use crate::indexer::operation::{DeleteOperation, DeleteTarget};
 use crate::indexer::stamper::Stamper;
 use crate::indexer::MergePolicy;
 use crate::indexer::SegmentEntry;
 use crate::indexer::SegmentWriter;
+use crate::query::{Query, RangeQuery, Weight};
 use crate::schema::Document;
 use crate::schema::IndexRecordOption;
 use crate::schema::Term;
+use crate::schema::{DatePrecision, Field, Schema, Type, Value};
+use crate::DateTime;
+use crate::DocId;
 use crate::Opstamp;
 use crossbeam::channel;
 use futures::executor::block_on;
 use futures::future::Future;
 use smallvec::smallvec;
 use smallvec::SmallVec;
+use std::collections::HashMap;
 use std::mem;
-use std::ops::Range;
-use std::sync::Arc;
+use std::ops::{Bound, Range};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::thread::JoinHandle;
 
@@ -47,6 +54,10 @@ pub const HEAP_SIZE_MAX: usize = u32::max_value() as usize - MARGIN_IN_BYTES;
 // reaches `PIPELINE_MAX_SIZE_IN_DOCS`
 const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
 
+// `run_chunked` consumes its operations by chunks of `RUN_CHUNK_SIZE`,
+// and `add_documents` sends its documents by groups of the same size.
+const RUN_CHUNK_SIZE: usize = 1_000;
+
 // Group of operations.
 // Most of the time, users will send operation one-by-one, but it can be useful to
 // send them as a small block to ensure that
@@ -57,6 +68,20 @@ type OperationGroup = SmallVec<[AddOperation; 4]>;
 type OperationSender = channel::Sender<OperationGroup>;
 type OperationReceiver = channel::Receiver<OperationGroup>;
 
+/// Settings of an `IndexWriter`, set through its setters.
+///
+/// They are shared with the segment updater, which applies them to the
+/// segments resulting from a merge, and are carried over to the new
+/// `IndexWriter` created by a rollback.
+#[derive(Clone, Default)]
+pub(crate) struct IndexWriterSettings {
+    pub bytes_compression_threshold: Option<usize>,
+    pub interned_fields: Vec<Field>,
+    pub date_precisions: Vec<(Field, DatePrecision)>,
+    // limit on the payload of a document, and whether its texts are counted.
+    pub max_document_bytes: Option<(usize, bool)>,
+}
+
 /// `IndexWriter` is the user entry-point to add document to an index.
 ///
 /// It manages a small number of indexing thread, as well as a shared
@@ -79,6 +104,8 @@ pub struct IndexWriter {
 
     segment_updater: SegmentUpdater,
 
+    settings: Arc<RwLock<IndexWriterSettings>>,
+
     worker_id: usize,
 
     num_threads: usize,
@@ -87,10 +114,16 @@ pub struct IndexWriter {
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+    // opstamp of the first operation that is not part of a commit yet.
+    first_uncommitted_opstamp: Opstamp,
+
+    num_uncommitted_adds: AtomicUsize,
+    num_uncommitted_deletes: AtomicUsize,
 }
 
 fn compute_deleted_bitset(
     delete_bitset: &mut BitSet,
+    index: &Index,
     segment_reader: &SegmentReader,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
@@ -108,17 +141,44 @@ fn compute_deleted_bitset(
         // Limit doc helps identify the first document
         // that may be affected by the delete operation.
         let limit_doc = doc_opstamps.compute_doc_limit(delete_op.opstamp);
-        let inverted_index = segment_reader.inverted_index(delete_op.term.field())?;
-        if let Some(mut docset) =
-            inverted_index.read_postings(&delete_op.term, IndexRecordOption::Basic)?
-        {
-            let mut deleted_doc = docset.doc();
-            while deleted_doc != TERMINATED {
-                if deleted_doc < limit_doc {
-                    delete_bitset.insert(deleted_doc);
-                    might_have_changed = true;
+        match delete_op.target {
+            DeleteTarget::ByTerm(ref term) => {
+                let inverted_index = segment_reader.inverted_index(term.field())?;
+                if let Some(mut docset) =
+                    inverted_index.read_postings(term, IndexRecordOption::Basic)?
+                {
+                    might_have_changed |= delete_docs(&mut docset, limit_doc, delete_bitset);
+                }
+            }
+            DeleteTarget::ByTerms { field, ref terms } => {
+                let inverted_index = segment_reader.inverted_index(field)?;
+                for term in terms {
+                    if let Some(mut docset) =
+                        inverted_index.read_postings(term, IndexRecordOption::Basic)?
+                    {
+                        might_have_changed |= delete_docs(&mut docset, limit_doc, delete_bitset);
+                    }
                 }
-                deleted_doc = docset.advance();
+            }
+            DeleteTarget::ByRange {
+                field,
+                ref lower,
+                ref upper,
+            } => {
+                let range_query =
+                    range_query_for_values(segment_reader.schema(), field, lower, upper)?;
+                let mut docset = range_query.range_weight().scorer(segment_reader, 1.0)?;
+                might_have_changed |= delete_docs(&mut docset, limit_doc, delete_bitset);
+            }
+            DeleteTarget::ByQuery(ref query) => {
+                let searcher = Searcher::new(
+                    segment_reader.schema().clone(),
+                    index.clone(),
+                    vec![segment_reader.clone()],
+                )?;
+                let weight = query.weight(&searcher, false)?;
+                let mut docset = weight.scorer(segment_reader, 1.0)?;
+                might_have_changed |= delete_docs(docset.as_mut(), limit_doc, delete_bitset);
             }
         }
         delete_cursor.advance();
@@ -126,6 +186,97 @@ fn compute_deleted_bitset(
     Ok(might_have_changed)
 }
 
+// Marks all of the documents of the docset that are strictly lower than
+// `limit_doc` as deleted.
+//
+// Returns true if at least one document was marked.
+fn delete_docs<D: DocSet + ?Sized>(
+    docset: &mut D,
+    limit_doc: DocId,
+    delete_bitset: &mut BitSet,
+) -> bool {
+    let mut might_have_changed = false;
+    let mut deleted_doc = docset.doc();
+    while deleted_doc != TERMINATED {
+        if deleted_doc < limit_doc {
+            delete_bitset.insert(deleted_doc);
+            might_have_changed = true;
+        }
+        deleted_doc = docset.advance();
+    }
+    might_have_changed
+}
+
+// Checks the payload of a document against the limit set with
+// `IndexWriter::set_max_document_bytes`, if any.
+fn check_document_bytes(
+    max_document_bytes: Option<(usize, bool)>,
+    document: &Document,
+) -> crate::Result<()> {
+    if let Some((max_document_bytes, include_text)) = max_document_bytes {
+        let document_bytes = document.total_bytes_payload(include_text);
+        if document_bytes > max_document_bytes {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The document holds {} bytes of payload, above the limit of {} bytes",
+                document_bytes, max_document_bytes
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Builds the `RangeQuery` matching the documents targeted by a range delete.
+//
+// Returns an error if the field is not indexed or if one of the bounds
+// does not match the type of the field.
+fn range_query_for_values(
+    schema: &Schema,
+    field: Field,
+    lower: &Bound<Value>,
+    upper: &Bound<Value>,
+) -> crate::Result<RangeQuery> {
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_indexed() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not indexed.",
+            field_entry.name()
+        )));
+    }
+    let value_type = field_entry.field_type().value_type();
+    let to_term = |value: &Value| -> crate::Result<Term> {
+        let term_opt = match (value_type, value) {
+            (Type::Str, Value::Str(_))
+            | (Type::U64, Value::U64(_))
+            | (Type::I64, Value::I64(_))
+            | (Type::F64, Value::F64(_))
+            | (Type::Date, Value::Date(_))
+            | (Type::Bytes, Value::Bytes(_)) => Term::from_value(field, value),
+            _ => None,
+        };
+        term_opt.ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Expected a {:?} bound for field {:?}, got {:?}",
+                value_type,
+                field_entry.name(),
+                value
+            ))
+        })
+    };
+    let to_term_bound = |bound: &Bound<Value>| -> crate::Result<Bound<Term>> {
+        Ok(match bound {
+            Bound::Included(value) => Bound::Included(to_term(value)?),
+            Bound::Excluded(value) => Bound::Excluded(to_term(value)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    };
+    Ok(RangeQuery::new_term_bounds(
+        field,
+        value_type,
+        &to_term_bound(lower)?,
+        &to_term_bound(upper)?,
+    ))
+}
+
 /// Advance delete for the given segment up to the target opstamp.
 ///
 /// Note that there are no guarantee that the resulting `segment_entry` delete_opstamp
@@ -159,6 +310,7 @@ pub(crate) fn advance_deletes(
 
     compute_deleted_bitset(
         &mut delete_bitset,
+        segment.index(),
         &segment_reader,
         segment_entry.delete_cursor(),
         &DocToOpstampMapping::None,
@@ -199,6 +351,10 @@ fn index_documents(
     let schema = segment.schema();
 
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone(), &schema)?;
+    segment_writer
+        .set_bytes_compression_threshold(segment_updater.get_bytes_compression_threshold())?;
+    segment_writer.set_interned_fields(segment_updater.get_interned_fields());
+    segment_writer.set_date_precisions(segment_updater.get_date_precisions());
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc, &schema)?;
@@ -263,6 +419,7 @@ fn apply_deletes(
     let mut deleted_bitset = BitSet::with_max_value(max_doc);
     let may_have_deletes = compute_deleted_bitset(
         &mut deleted_bitset,
+        segment.index(),
         &segment_reader,
         &mut delete_cursor,
         &doc_to_opstamps,
@@ -295,6 +452,22 @@ impl IndexWriter {
         num_threads: usize,
         heap_size_in_bytes_per_thread: usize,
         directory_lock: DirectoryLock,
+    ) -> crate::Result<IndexWriter> {
+        IndexWriter::with_settings(
+            index,
+            num_threads,
+            heap_size_in_bytes_per_thread,
+            directory_lock,
+            IndexWriterSettings::default(),
+        )
+    }
+
+    fn with_settings(
+        index: &Index,
+        num_threads: usize,
+        heap_size_in_bytes_per_thread: usize,
+        directory_lock: DirectoryLock,
+        settings: IndexWriterSettings,
     ) -> crate::Result<IndexWriter> {
         if heap_size_in_bytes_per_thread < HEAP_SIZE_MIN {
             let err_msg = format!(
@@ -316,8 +489,14 @@ impl IndexWriter {
 
         let stamper = Stamper::new(current_opstamp);
 
-        let segment_updater =
-            SegmentUpdater::create(index.clone(), stamper.clone(), &delete_queue.cursor())?;
+        let settings = Arc::new(RwLock::new(settings));
+
+        let segment_updater = SegmentUpdater::create(
+            index.clone(),
+            stamper.clone(),
+            &delete_queue.cursor(),
+            settings.clone(),
+        )?;
 
         let mut index_writer = IndexWriter {
             _directory_lock: Some(directory_lock),
@@ -329,6 +508,7 @@ impl IndexWriter {
             operation_sender: document_sender,
 
             segment_updater,
+            settings,
 
             workers_join_handle: vec![],
             num_threads,
@@ -336,8 +516,12 @@ impl IndexWriter {
             delete_queue,
 
             committed_opstamp: current_opstamp,
+            first_uncommitted_opstamp: current_opstamp,
             stamper,
 
+            num_uncommitted_adds: AtomicUsize::new(0),
+            num_uncommitted_deletes: AtomicUsize::new(0),
+
             worker_id: 0,
         };
         index_writer.start_workers()?;
@@ -458,6 +642,111 @@ impl IndexWriter {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Accessor to the bytes compression threshold.
+    pub fn get_bytes_compression_threshold(&self) -> Option<usize> {
+        self.settings.read().unwrap().bytes_compression_threshold
+    }
+
+    /// Sets the size above which the `Value::Bytes` of the stored documents
+    /// are individually compressed in the doc store.
+    ///
+    /// This is useful for large binary values, as the doc store otherwise only
+    /// compresses whole blocks of documents. Smaller bytes values are stored as is,
+    /// and compressed values are decompressed transparently when reading documents.
+    /// By default (`None`), bytes values are never compressed individually.
+    ///
+    /// The setting applies to the segments created after the call,
+    /// including the segments resulting from a merge. It is kept on rollback.
+    ///
+    /// The threshold is a property of the doc store codec of the index:
+    /// an error is returned if this codec does not support compressing
+    /// bytes values, see [`Index::set_doc_store_codec`](../struct.Index.html#method.set_doc_store_codec).
+    pub fn set_bytes_compression_threshold(
+        &self,
+        bytes_compression_threshold: Option<usize>,
+    ) -> crate::Result<()> {
+        if bytes_compression_threshold.is_some()
+            && self
+                .index
+                .doc_store_codec()
+                .with_bytes_compression(bytes_compression_threshold)
+                .is_none()
+        {
+            return Err(TantivyError::InvalidArgument(
+                "The doc store codec of the index does not support compressing bytes values"
+                    .to_string(),
+            ));
+        }
+        self.settings.write().unwrap().bytes_compression_threshold = bytes_compression_threshold;
+        Ok(())
+    }
+
+    /// Accessor to the fields whose strings are interned in the doc store.
+    pub fn get_interned_fields(&self) -> Vec<Field> {
+        self.settings.read().unwrap().interned_fields.clone()
+    }
+
+    /// Sets the fields whose strings are interned in the doc store.
+    ///
+    /// Each distinct string of these fields is stored once per segment,
+    /// in a dictionary, and the stored documents only refer to it by its id.
+    /// This reduces the size of the doc store dramatically for low-cardinality
+    /// fields, such as a country code or a status. Documents are still added
+    /// with `add_text`, and the interned strings are resolved transparently
+    /// when reading documents.
+    ///
+    /// The setting applies to the segments created after the call,
+    /// including the segments resulting from a merge. It is kept on rollback.
+    pub fn set_interned_fields(&self, interned_fields: Vec<Field>) {
+        self.settings.write().unwrap().interned_fields = interned_fields;
+    }
+
+    /// Accessor to the maximum payload of a document, along with
+    /// whether its texts are counted in the payload.
+    pub fn get_max_document_bytes(&self) -> Option<(usize, bool)> {
+        self.settings.read().unwrap().max_document_bytes
+    }
+
+    /// Sets the maximum total length, in bytes, of the `Bytes` values
+    /// of a document, as computed by
+    /// [`Document::total_bytes_payload(include_text)`](../schema/struct.Document.html#method.total_bytes_payload).
+    /// If `include_text` is true, the length of the texts is counted as well.
+    ///
+    /// The operations adding documents, such as `try_add_document`, `add_documents`
+    /// or `run`, reject the documents above the limit with a
+    /// `TantivyError::InvalidArgument`, before giving any opstamp to their
+    /// operations. `add_document` and `upsert_document` panic instead.
+    /// By default (`None`), documents are not limited. The limit is kept on rollback.
+    pub fn set_max_document_bytes(&self, max_document_bytes: Option<usize>, include_text: bool) {
+        self.settings.write().unwrap().max_document_bytes =
+            max_document_bytes.map(|max_document_bytes| (max_document_bytes, include_text));
+    }
+
+    fn check_document_bytes(&self, document: &Document) -> crate::Result<()> {
+        check_document_bytes(self.get_max_document_bytes(), document)
+    }
+
+    /// Accessor to the precision the stored dates of each field are truncated to.
+    pub fn get_date_precisions(&self) -> Vec<(Field, DatePrecision)> {
+        self.settings.read().unwrap().date_precisions.clone()
+    }
+
+    /// Sets the precision the dates of each of the given fields are
+    /// truncated to in the doc store.
+    ///
+    /// Dates truncated to the second, the millisecond or the microsecond
+    /// take 4 bytes less in the doc store than dates with a nanosecond part.
+    /// The truncated part is lost: the documents read from the doc store
+    /// hold the truncated dates. Indexing and fast fields are not affected,
+    /// as they only keep the precision of the second anyway.
+    ///
+    /// The setting applies to the documents added after the call. The dates
+    /// already stored are left as is, including when their segment is merged.
+    /// It is kept on rollback.
+    pub fn set_date_precisions(&self, date_precisions: Vec<(Field, DatePrecision)>) {
+        self.settings.write().unwrap().date_precisions = date_precisions;
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -551,9 +840,12 @@ impl IndexWriter {
     /// After calling rollback, the index is in the same
     /// state as it was after the last commit.
     ///
-    /// The opstamp at the last commit is returned.
-    pub fn rollback(&mut self) -> crate::Result<Opstamp> {
+    /// The opstamp at the last commit is returned, along with
+    /// the number of add and delete operations that were discarded.
+    pub fn rollback(&mut self) -> crate::Result<RollbackResult> {
         info!("Rolling back to opstamp {}", self.committed_opstamp);
+        let num_reverted_adds = self.num_uncommitted_adds();
+        let num_reverted_deletes = self.num_uncommitted_deletes();
         // marks the segment updater as killed. From now on, all
         // segment updates will be ignored.
         self.segment_updater.kill();
@@ -565,11 +857,13 @@ impl IndexWriter {
             .take()
             .expect("The IndexWriter does not have any lock. This is a bug, please report.");
 
-        let new_index_writer: IndexWriter = IndexWriter::new(
+        let settings = self.settings.read().unwrap().clone();
+        let new_index_writer: IndexWriter = IndexWriter::with_settings(
             &self.index,
             self.num_threads,
             self.heap_size_in_bytes_per_thread,
             directory_lock,
+            settings,
         )?;
 
         // the current `self` is dropped right away because of this call.
@@ -585,7 +879,11 @@ impl IndexWriter {
         // was dropped with the index_writer.
         for _ in document_receiver {}
 
-        Ok(self.committed_opstamp)
+        Ok(RollbackResult {
+            opstamp: self.committed_opstamp,
+            num_reverted_adds,
+            num_reverted_deletes,
+        })
     }
 
     /// Prepares a commit.
@@ -637,11 +935,22 @@ impl IndexWriter {
         }
 
         let commit_opstamp = self.stamper.stamp();
-        let prepared_commit = PreparedCommit::new(self, commit_opstamp);
+        let first_opstamp = self.first_uncommitted_opstamp;
+        let prepared_commit = PreparedCommit::new(self, first_opstamp..commit_opstamp);
         info!("Prepared commit {}", commit_opstamp);
         Ok(prepared_commit)
     }
 
+    /// Prepares a commit carrying the given payload.
+    ///
+    /// This is equivalent to calling `prepare_commit()` followed by
+    /// [`PreparedCommit::set_payload()`](PreparedCommit.html).
+    pub fn prepare_commit_with_payload(&mut self, payload: &str) -> crate::Result<PreparedCommit> {
+        let mut prepared_commit = self.prepare_commit()?;
+        prepared_commit.set_payload(payload);
+        Ok(prepared_commit)
+    }
+
     /// Commits all of the pending changes
     ///
     /// A call to commit blocks.
@@ -674,11 +983,196 @@ impl IndexWriter {
     /// only after calling `commit()`.
     pub fn delete_term(&self, term: Term) -> Opstamp {
         let opstamp = self.stamper.stamp();
-        let delete_operation = DeleteOperation { opstamp, term };
-        self.delete_queue.push(delete_operation);
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::ByTerm(term),
+        };
+        self.push_delete_operation(delete_operation);
+        opstamp
+    }
+
+    /// Deletes all of the documents containing any of the given terms,
+    /// as one delete operation per term.
+    ///
+    /// The delete operations receive consecutive opstamps, in the order of
+    /// `terms`, and the opstamp of each of them is returned: this is
+    /// equivalent to calling `delete_term` for each of the terms, without
+    /// any other operation being interleaved.
+    ///
+    /// See [`delete_terms`](#method.delete_terms) to delete the terms of a field
+    /// at once, as a single operation.
+    pub fn delete_term_batch(&self, terms: Vec<Term>) -> Vec<Opstamp> {
+        let opstamps = self.stamper.stamps(terms.len() as u64);
+        for (term, opstamp) in terms.into_iter().zip(opstamps.clone()) {
+            self.push_delete_operation(DeleteOperation {
+                opstamp,
+                target: DeleteTarget::ByTerm(term),
+            });
+        }
+        opstamps.collect()
+    }
+
+    /// Delete all documents containing at least one of the given terms
+    /// of `field`, as a single operation.
+    ///
+    /// All of the terms are deleted at the same opstamp: this is equivalent to,
+    /// but cheaper than, calling `delete_term` for each of the terms while no
+    /// other operation is running. An error is returned if one of the terms
+    /// is not a term of `field`.
+    ///
+    /// Just like `delete_term`, the delete operation only affects documents that
+    /// were added in previous commits, and documents that were added previously
+    /// in the same commit. The deletion itself will be visible only after
+    /// calling `commit()`.
+    pub fn delete_terms(&self, field: Field, terms: Vec<Term>) -> crate::Result<Opstamp> {
+        if let Some(term) = terms.iter().find(|term| term.field() != field) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Expected terms of field {:?}, got a term of field {:?}",
+                field,
+                term.field()
+            )));
+        }
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::ByTerms { field, terms },
+        };
+        self.push_delete_operation(delete_operation);
+        Ok(opstamp)
+    }
+
+    /// Delete all documents matching a given query.
+    ///
+    /// The query is evaluated, with scoring disabled, against each of the
+    /// segments when the delete is applied. This is significantly more expensive
+    /// than deleting by term: prefer `delete_term` whenever possible.
+    ///
+    /// Just like `delete_term`, the delete operation only affects documents that
+    /// were added in previous commits, and documents that were added previously
+    /// in the same commit. The deletion itself will be visible only after
+    /// calling `commit()`. Errors raised by the query are only reported then.
+    pub fn delete_query(&self, query: Box<dyn Query>) -> Opstamp {
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::ByQuery(query),
+        };
+        self.push_delete_operation(delete_operation);
         opstamp
     }
 
+    /// Delete all documents having at least one value of `field`
+    /// within the given range.
+    ///
+    /// The field needs to be indexed, and the bounds need to be of
+    /// the same type as the field. An error is returned otherwise.
+    ///
+    /// Just like `delete_term`, the delete operation only affects documents that
+    /// were added in previous commits, and documents that were added previously
+    /// in the same commit. The deletion itself will be visible only after
+    /// calling `commit()`.
+    pub fn delete_range(
+        &self,
+        field: Field,
+        lower: Bound<Value>,
+        upper: Bound<Value>,
+    ) -> crate::Result<Opstamp> {
+        range_query_for_values(&self.index.schema(), field, &lower, &upper)?;
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: DeleteTarget::ByRange {
+                field,
+                lower,
+                upper,
+            },
+        };
+        self.push_delete_operation(delete_operation);
+        Ok(opstamp)
+    }
+
+    fn delete_half_open_range(
+        &self,
+        field: Field,
+        lower_inclusive: Value,
+        upper_exclusive: Value,
+    ) -> crate::Result<Opstamp> {
+        self.delete_range(
+            field,
+            Bound::Included(lower_inclusive),
+            Bound::Excluded(upper_exclusive),
+        )
+    }
+
+    /// Delete all documents having at least one value of the u64 `field`
+    /// within `[lower_inclusive, upper_exclusive)`.
+    ///
+    /// See [`delete_range`](#method.delete_range).
+    pub fn delete_range_u64(
+        &self,
+        field: Field,
+        lower_inclusive: u64,
+        upper_exclusive: u64,
+    ) -> crate::Result<Opstamp> {
+        self.delete_half_open_range(
+            field,
+            Value::U64(lower_inclusive),
+            Value::U64(upper_exclusive),
+        )
+    }
+
+    /// Delete all documents having at least one value of the i64 `field`
+    /// within `[lower_inclusive, upper_exclusive)`.
+    ///
+    /// See [`delete_range`](#method.delete_range).
+    pub fn delete_range_i64(
+        &self,
+        field: Field,
+        lower_inclusive: i64,
+        upper_exclusive: i64,
+    ) -> crate::Result<Opstamp> {
+        self.delete_half_open_range(
+            field,
+            Value::I64(lower_inclusive),
+            Value::I64(upper_exclusive),
+        )
+    }
+
+    /// Delete all documents having at least one value of the f64 `field`
+    /// within `[lower_inclusive, upper_exclusive)`.
+    ///
+    /// See [`delete_range`](#method.delete_range).
+    pub fn delete_range_f64(
+        &self,
+        field: Field,
+        lower_inclusive: f64,
+        upper_exclusive: f64,
+    ) -> crate::Result<Opstamp> {
+        self.delete_half_open_range(
+            field,
+            Value::F64(lower_inclusive),
+            Value::F64(upper_exclusive),
+        )
+    }
+
+    /// Delete all documents having at least one value of the date `field`
+    /// within `[lower_inclusive, upper_exclusive)`.
+    ///
+    /// Just like for indexing, dates are truncated to the second.
+    /// See [`delete_range`](#method.delete_range).
+    pub fn delete_range_date(
+        &self,
+        field: Field,
+        lower_inclusive: &DateTime,
+        upper_exclusive: &DateTime,
+    ) -> crate::Result<Opstamp> {
+        self.delete_half_open_range(
+            field,
+            Value::Date(*lower_inclusive),
+            Value::Date(*upper_exclusive),
+        )
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -697,14 +1191,179 @@ impl IndexWriter {
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// # Panics
+    /// If the document is above the limit set with
+    /// [`set_max_document_bytes`](#method.set_max_document_bytes), or if all of
+    /// the indexing workers are gone: use [`try_add_document`](#method.try_add_document)
+    /// to get an error instead.
     pub fn add_document(&self, document: Document) -> Opstamp {
+        self.try_add_document(document)
+            .unwrap_or_else(|e| panic!("Failed to index document. {:?}", e))
+    }
+
+    /// Adds a document, provided its `Bytes` payload is within the limit set
+    /// with [`set_max_document_bytes`](#method.set_max_document_bytes).
+    ///
+    /// Oversized documents are rejected with a `TantivyError::InvalidArgument`,
+    /// and do not consume any opstamp.
+    pub fn try_add_document(&self, document: Document) -> crate::Result<Opstamp> {
+        self.check_document_bytes(&document)?;
         let opstamp = self.stamper.stamp();
-        let add_operation = AddOperation { opstamp, document };
-        let send_result = self.operation_sender.send(smallvec![add_operation]);
-        if let Err(e) = send_result {
-            panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
+        let add_operation = AddOperation {
+            opstamp,
+            document,
+            delete_term: None,
+        };
+        self.try_send_add_operations(smallvec![add_operation])?;
+        Ok(opstamp)
+    }
+
+    /// Adds a batch of documents.
+    ///
+    /// The documents receive consecutive opstamps, in the order of the
+    /// iterator, and the opstamp of the last document is returned.
+    /// An empty batch still receives a valid opstamp.
+    ///
+    /// This is cheaper than calling `add_document` for each document:
+    /// the opstamps are reserved all at once, and the documents are sent
+    /// to the indexing workers by groups of 1,000 rather than one by one.
+    ///
+    /// If the indexing pipeline is full, this call may block.
+    pub fn add_documents<D, I>(&self, documents: I) -> crate::Result<Opstamp>
+    where
+        D: Into<Document>,
+        I: IntoIterator<Item = D>,
+    {
+        let documents: Vec<Document> = documents.into_iter().map(Into::into).collect();
+        for document in &documents {
+            self.check_document_bytes(document)?;
         }
-        opstamp
+        if documents.is_empty() {
+            return Ok(self.stamper.stamp());
+        }
+        let Range { start, end } = self.stamper.stamps(documents.len() as u64);
+        let mut add_operations =
+            documents
+                .into_iter()
+                .zip(start..end)
+                .map(|(document, opstamp)| AddOperation {
+                    opstamp,
+                    document,
+                    delete_term: None,
+                });
+        loop {
+            let add_operation_group: OperationGroup =
+                add_operations.by_ref().take(RUN_CHUNK_SIZE).collect();
+            if add_operation_group.is_empty() {
+                break;
+            }
+            self.try_send_add_operations(add_operation_group)?;
+        }
+        Ok(end - 1)
+    }
+
+    /// Deletes all of the documents containing `delete_term`, and adds
+    /// a document.
+    ///
+    /// The delete and the add share the same opstamp: the delete
+    /// affects the documents that were added previously, but not
+    /// the document added by this call.
+    ///
+    /// If the indexing pipeline is full, this call may block.
+    ///
+    /// # Panics
+    /// If the document is above the limit set with
+    /// [`set_max_document_bytes`](#method.set_max_document_bytes), or if all of
+    /// the indexing workers are gone: use [`try_upsert_document`](#method.try_upsert_document)
+    /// to get an error instead.
+    pub fn upsert_document(&self, delete_term: Term, document: Document) -> Opstamp {
+        self.try_upsert_document(delete_term, document)
+            .unwrap_or_else(|e| panic!("Failed to index document. {:?}", e))
+    }
+
+    /// Same as [`upsert_document`](#method.upsert_document), but returns
+    /// an error instead of panicking.
+    ///
+    /// A document above the limit set with [`set_max_document_bytes`](#method.set_max_document_bytes)
+    /// is rejected with a `TantivyError::InvalidArgument`: neither the delete nor
+    /// the add is run, and no opstamp is consumed.
+    pub fn try_upsert_document(
+        &self,
+        delete_term: Term,
+        document: Document,
+    ) -> crate::Result<Opstamp> {
+        self.check_document_bytes(&document)?;
+        let opstamp = self.stamper.stamp();
+        let add_operation = AddOperation {
+            opstamp,
+            document,
+            delete_term: Some(delete_term),
+        };
+        self.try_send_add_operations(smallvec![add_operation])?;
+        Ok(opstamp)
+    }
+
+    /// Pushes the delete operations attached to the add operations
+    /// to the delete queue, and sends the add operations to the indexing workers.
+    ///
+    /// Returns an error if all of the indexing workers are gone.
+    fn try_send_add_operations(&self, mut add_operations: OperationGroup) -> crate::Result<()> {
+        for add_operation in &mut add_operations {
+            if let Some(delete_term) = add_operation.delete_term.take() {
+                self.push_delete_operation(DeleteOperation {
+                    opstamp: add_operation.opstamp,
+                    target: DeleteTarget::ByTerm(delete_term),
+                });
+            }
+        }
+        self.num_uncommitted_adds
+            .fetch_add(add_operations.len(), AtomicOrdering::Relaxed);
+        self.operation_sender.send(add_operations).map_err(|_| {
+            TantivyError::ErrorInThread(
+                "Sending to indexing channel failed. This probably means all of the indexing \
+                 threads have panicked."
+                    .to_string(),
+            )
+        })
+    }
+
+    fn push_delete_operation(&self, delete_operation: DeleteOperation) {
+        self.delete_queue.push(delete_operation);
+        self.num_uncommitted_deletes
+            .fetch_add(1, AtomicOrdering::Relaxed);
+    }
+
+    /// Returns the number of add and delete operations that were
+    /// run since the last commit or rollback.
+    ///
+    /// This is the sum of [`num_uncommitted_adds`](#method.num_uncommitted_adds)
+    /// and [`num_uncommitted_deletes`](#method.num_uncommitted_deletes).
+    pub fn num_uncommitted_operations(&self) -> usize {
+        self.num_uncommitted_adds() + self.num_uncommitted_deletes()
+    }
+
+    /// Returns the number of documents added since the last commit or rollback.
+    ///
+    /// The add operations dropped by `run_deduplicated` are not counted.
+    pub fn num_uncommitted_adds(&self) -> usize {
+        self.num_uncommitted_adds.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Returns the number of delete operations run since the last commit or rollback.
+    ///
+    /// `upsert_document` counts as both an add and a delete operation.
+    pub fn num_uncommitted_deletes(&self) -> usize {
+        self.num_uncommitted_deletes.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Resets the number of uncommitted operations, once they are committed
+    /// by the commit of the given opstamp.
+    pub(crate) fn reset_uncommitted_operations(&mut self, commit_opstamp: Opstamp) {
+        self.first_uncommitted_opstamp = commit_opstamp + 1;
+        self.num_uncommitted_adds.store(0, AtomicOrdering::Relaxed);
+        self.num_uncommitted_deletes
+            .store(0, AtomicOrdering::Relaxed);
     }
 
     /// Gets a range of stamps from the stamper and "pops" the last stamp
@@ -732,41 +1391,183 @@ impl IndexWriter {
     ///
     /// Each operation of the given `user_operations` will receive an in-order,
     /// contiguous u64 opstamp. The entire batch itself is also given an
-    /// opstamp that is 1 greater than the last given operation. This
-    /// `batch_opstamp` is the return value of `run`. An empty group of
-    /// `user_operations`, an empty `Vec<UserOperation>`, still receives
-    /// a valid opstamp even though no changes were _actually_ made to the index.
+    /// opstamp that is 1 greater than the last given operation.
+    ///
+    /// `run` returns the range of the opstamps given to the operations: its
+    /// `start` is the opstamp of the first operation, and its `end` is the
+    /// `batch_opstamp`. The length of the range is the number of operations.
+    /// An empty group of `user_operations`, an empty `Vec<UserOperation>`,
+    /// still receives a valid `batch_opstamp` even though no changes were
+    /// _actually_ made to the index: the returned range is then empty.
     ///
     /// Like adds and deletes (see `IndexWriter.add_document` and
     /// `IndexWriter.delete_term`), the changes made by calling `run` will be
     /// visible to readers only after calling `commit()`.
-    pub fn run(&self, user_operations: Vec<UserOperation>) -> Opstamp {
+    ///
+    /// If one of the documents is above the limit set with
+    /// [`set_max_document_bytes`](#method.set_max_document_bytes), the whole
+    /// group is rejected with a `TantivyError::InvalidArgument`, before any
+    /// opstamp is given to its operations.
+    pub fn run(&self, user_operations: Vec<UserOperation>) -> crate::Result<Range<Opstamp>> {
+        self.check_user_operations(&user_operations)?;
+        self.run_operations(user_operations.into_iter().map(Some))
+    }
+
+    /// Runs a group of document operations, just like [`run`](#method.run),
+    /// deduplicating the add operations by the value of `key_field`.
+    ///
+    /// When several add operations of the group have the same first value for
+    /// `key_field`, only the last of them is indexed. Add operations without
+    /// any value for `key_field` are never deduplicated.
+    ///
+    /// Deduplicated operations still receive their opstamp, so that all of the
+    /// other operations receive the same opstamps as with `run`. In particular,
+    /// a delete operation placed between two adds of the same key is honored:
+    /// it never affects the add operations that come after it.
+    ///
+    /// Deduplicating requires building a hash map over the keys of the group,
+    /// which costs memory proportional to the number of add operations.
+    ///
+    /// Like with `run`, the group is rejected if one of its documents, including
+    /// the deduplicated ones, is above the limit set with
+    /// [`set_max_document_bytes`](#method.set_max_document_bytes).
+    pub fn run_deduplicated(
+        &self,
+        key_field: Field,
+        user_operations: Vec<UserOperation>,
+    ) -> crate::Result<Range<Opstamp>> {
+        self.check_user_operations(&user_operations)?;
+        let mut is_last_add: Vec<bool> = vec![true; user_operations.len()];
+        let mut last_add_ords: HashMap<&Value, usize> = HashMap::new();
+        for (ord, user_op) in user_operations.iter().enumerate() {
+            if let UserOperation::Add(document) = user_op {
+                if let Some(key) = document.get_first(key_field) {
+                    if let Some(previous_ord) = last_add_ords.insert(key, ord) {
+                        is_last_add[previous_ord] = false;
+                    }
+                }
+            }
+        }
+        self.run_operations(
+            user_operations
+                .into_iter()
+                .zip(is_last_add)
+                .map(|(user_op, is_last_add)| if is_last_add { Some(user_op) } else { None }),
+        )
+    }
+
+    /// Checks the documents of a group of operations against the limit
+    /// set with `set_max_document_bytes`.
+    fn check_user_operations(&self, user_operations: &[UserOperation]) -> crate::Result<()> {
+        let max_document_bytes = self.get_max_document_bytes();
+        if max_document_bytes.is_none() {
+            return Ok(());
+        }
+        for user_op in user_operations {
+            if let UserOperation::Add(document) = user_op {
+                check_document_bytes(max_document_bytes, document)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a group of operations. `None` operations are skipped, but
+    /// still receive an opstamp.
+    fn run_operations<I>(&self, user_operations: I) -> crate::Result<Range<Opstamp>>
+    where
+        I: ExactSizeIterator<Item = Option<UserOperation>>,
+    {
         let count = user_operations.len() as u64;
         if count == 0 {
-            return self.stamper.stamp();
+            let batch_opstamp = self.stamper.stamp();
+            return Ok(batch_opstamp..batch_opstamp);
         }
         let (batch_opstamp, stamps) = self.get_batch_opstamps(count);
+        let first_opstamp = stamps.start;
+        self.send_operations(user_operations, stamps)?;
+        Ok(first_opstamp..batch_opstamp)
+    }
 
+    /// Pushes the delete operations of a group to the delete queue, and sends
+    /// its add operations to the indexing workers, with the given opstamps.
+    /// `None` operations are skipped.
+    fn send_operations<I>(&self, user_operations: I, stamps: Range<Opstamp>) -> crate::Result<()>
+    where
+        I: Iterator<Item = Option<UserOperation>>,
+    {
         let mut adds = OperationGroup::default();
 
-        for (user_op, opstamp) in user_operations.into_iter().zip(stamps) {
+        for (user_op, opstamp) in user_operations.zip(stamps) {
             match user_op {
-                UserOperation::Delete(term) => {
-                    let delete_operation = DeleteOperation { opstamp, term };
-                    self.delete_queue.push(delete_operation);
+                None => {}
+                Some(UserOperation::Delete(term)) => {
+                    let delete_operation = DeleteOperation {
+                        opstamp,
+                        target: DeleteTarget::ByTerm(term),
+                    };
+                    self.push_delete_operation(delete_operation);
+                }
+                Some(UserOperation::DeleteByQuery(query)) => {
+                    let delete_operation = DeleteOperation {
+                        opstamp,
+                        target: DeleteTarget::ByQuery(query),
+                    };
+                    self.push_delete_operation(delete_operation);
                 }
-                UserOperation::Add(document) => {
-                    let add_operation = AddOperation { opstamp, document };
+                Some(UserOperation::Add(document)) => {
+                    let add_operation = AddOperation {
+                        opstamp,
+                        document,
+                        delete_term: None,
+                    };
                     adds.push(add_operation);
                 }
             }
         }
-        let send_result = self.operation_sender.send(adds);
-        if let Err(e) = send_result {
-            panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
-        };
+        self.try_send_add_operations(adds)
+    }
 
-        batch_opstamp
+    /// Runs the document operations of an iterator by chunks, without
+    /// materializing all of them at once.
+    ///
+    /// The operations are consumed by chunks of 1000 operations. Like with
+    /// [`run`](#method.run), the operations receive in-order, contiguous opstamps,
+    /// and the iterator itself is given the opstamp following the opstamp of its
+    /// last operation: the stamper of the index writer is held until the iterator
+    /// is exhausted, so that the operations run concurrently on the index writer
+    /// wait for `run_chunked` to return. The iterator must hence not run operations
+    /// on the index writer itself. The add operations of a chunk are flushed into
+    /// the same segment, but the chunks may end up in different segments.
+    ///
+    /// Like `run`, `run_chunked` returns the range of the opstamps given to the
+    /// operations: its `start` is the opstamp of the first operation, and its `end`
+    /// is the batch opstamp. An empty iterator still receives a valid batch opstamp,
+    /// and the returned range is then empty.
+    ///
+    /// Like with `run`, a chunk holding a document above the limit set with
+    /// [`set_max_document_bytes`](#method.set_max_document_bytes) is rejected
+    /// with a `TantivyError::InvalidArgument`, before any opstamp is given to its
+    /// operations. The chunks run before it are kept, and the rest of the
+    /// iterator is not consumed.
+    pub fn run_chunked<I: IntoIterator<Item = UserOperation>>(
+        &self,
+        user_operations: I,
+    ) -> crate::Result<Range<Opstamp>> {
+        let mut user_operations = user_operations.into_iter();
+        let stamper = self.stamper.lock();
+        let mut first_opstamp_opt: Option<Opstamp> = None;
+        loop {
+            let chunk: Vec<UserOperation> = user_operations.by_ref().take(RUN_CHUNK_SIZE).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            self.check_user_operations(&chunk)?;
+            let stamps = stamper.stamps(chunk.len() as u64);
+            first_opstamp_opt.get_or_insert(stamps.start);
+            self.send_operations(chunk.into_iter().map(Some), stamps)?;
+        }
+        let batch_opstamp = stamper.stamp();
+        Ok(first_opstamp_opt.unwrap_or(batch_opstamp)..batch_opstamp)
     }
 }
 
@@ -783,16 +1584,23 @@ impl Drop for IndexWriter {
 #[cfg(test)]
 mod tests {
 
-    use super::super::operation::UserOperation;
-    use crate::collector::TopDocs;
+    use super::super::operation::{DeleteOperation, DeleteTarget, UserOperation};
+    use crate::collector::{Count, TopDocs};
+    use crate::common::BinarySerializable;
     use crate::directory::error::LockError;
+    use crate::directory::RAMDirectory;
     use crate::error::*;
     use crate::indexer::NoMergePolicy;
-    use crate::query::TermQuery;
-    use crate::schema::{self, IndexRecordOption, STRING};
+    use crate::query::{BooleanQuery, RangeQuery, TermQuery};
+    use crate::schema::{self, DatePrecision, Document, IndexRecordOption, Value, STRING};
+    use crate::DocAddress;
     use crate::Index;
+    use crate::Opstamp;
     use crate::ReloadPolicy;
     use crate::Term;
+    use chrono::{TimeZone, Utc};
+    use futures::executor::block_on;
+    use std::ops::{Bound, Range};
 
     #[test]
     fn test_operations_group() {
@@ -805,8 +1613,187 @@ mod tests {
             UserOperation::Add(doc!(text_field=>"a")),
             UserOperation::Add(doc!(text_field=>"b")),
         ];
-        let batch_opstamp1 = index_writer.run(operations);
-        assert_eq!(batch_opstamp1, 2u64);
+        let opstamps = index_writer.run(operations).unwrap();
+        assert_eq!(opstamps, 0u64..2u64);
+    }
+
+    #[test]
+    fn test_upsert_document() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let id_term = |id: u64| Term::from_field_u64(id_field, id);
+        let text_count = |text: &str| -> crate::Result<usize> {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            reader.searcher().search(&query, &Count)
+        };
+        index_writer.add_document(doc!(id_field=>1u64, text_field=>"a"));
+        index_writer.add_document(doc!(id_field=>2u64, text_field=>"a"));
+        index_writer.commit()?;
+        // The delete of the upsert only affects documents added previously,
+        // including documents of the same commit.
+        index_writer.add_document(doc!(id_field=>1u64, text_field=>"b"));
+        let upsert_opstamp =
+            index_writer.upsert_document(id_term(1u64), doc!(id_field=>1u64, text_field=>"c"));
+        let add_opstamp = index_writer.add_document(doc!(id_field=>1u64, text_field=>"d"));
+        assert_eq!(add_opstamp, upsert_opstamp + 1);
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 3);
+        assert_eq!(text_count("a")?, 1);
+        assert_eq!(text_count("b")?, 0);
+        assert_eq!(text_count("c")?, 1);
+        assert_eq!(text_count("d")?, 1);
+        index_writer.upsert_document(id_term(1u64), doc!(id_field=>1u64, text_field=>"e"));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(text_count("e")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_deduplicated() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let id_term = |id: &str| Term::from_field_text(id_field, id);
+        let text_count = |text: &str| -> crate::Result<usize> {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            reader.searcher().search(&query, &Count)
+        };
+        let operations = vec![
+            // add, add: only the last add is kept.
+            UserOperation::Add(doc!(id_field=>"1", text_field=>"a")),
+            UserOperation::Add(doc!(id_field=>"1", text_field=>"b")),
+            // add, delete, add: the delete does not affect the last add.
+            UserOperation::Add(doc!(id_field=>"2", text_field=>"c")),
+            UserOperation::Delete(id_term("2")),
+            UserOperation::Add(doc!(id_field=>"2", text_field=>"d")),
+            // add, add, delete: the delete affects the last add.
+            UserOperation::Add(doc!(id_field=>"3", text_field=>"e")),
+            UserOperation::Add(doc!(id_field=>"3", text_field=>"f")),
+            UserOperation::Delete(id_term("3")),
+            // documents without a key are never deduplicated.
+            UserOperation::Add(doc!(text_field=>"g")),
+            UserOperation::Add(doc!(text_field=>"g")),
+        ];
+        // Deduplicated operations still get their opstamps.
+        assert_eq!(
+            index_writer.run_deduplicated(id_field, operations)?.end,
+            10u64
+        );
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 4);
+        for (text, expected_count) in &[
+            ("a", 0),
+            ("b", 1),
+            ("c", 0),
+            ("d", 1),
+            ("e", 0),
+            ("f", 0),
+            ("g", 2),
+        ] {
+            assert_eq!(text_count(text)?, *expected_count, "{}", text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_chunked() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let operations = vec!["a", "b", "c", "d"]
+            .into_iter()
+            .filter(|text| *text != "c")
+            .map(|text| UserOperation::Add(doc!(text_field=>text)));
+        // 3 operations and the batch itself.
+        assert_eq!(index_writer.run_chunked(operations)?, 0u64..3u64);
+        assert_eq!(index_writer.run_chunked(Vec::new())?, 4u64..4u64);
+        let operations =
+            (0..2_500).map(|i| UserOperation::Add(doc!(text_field=>format!("doc{}", i))));
+        // 2500 operations, and a single batch opstamp.
+        assert_eq!(index_writer.run_chunked(operations)?, 5u64..5u64 + 2_500u64);
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2_503u64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_chunked_concurrent_opstamps() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests()?;
+        let (opstamps, concurrent_opstamps) = crossbeam::scope(|scope| {
+            let concurrent_adds = scope.spawn(|_| {
+                (0..2_000)
+                    .map(|_| index_writer.add_document(doc!(text_field=>"concurrent")))
+                    .collect::<Vec<Opstamp>>()
+            });
+            let operations =
+                (0..5_000).map(|i| UserOperation::Add(doc!(text_field=>format!("doc{}", i))));
+            let opstamps = index_writer.run_chunked(operations);
+            (opstamps, concurrent_adds.join().unwrap())
+        })
+        .unwrap();
+        let opstamps = opstamps?;
+        // the range holds the opstamps of the 5000 operations only.
+        assert_eq!(opstamps.end - opstamps.start, 5_000u64);
+        assert!(concurrent_opstamps
+            .iter()
+            .all(|opstamp| *opstamp < opstamps.start || *opstamp > opstamps.end));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_documents() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        assert_eq!(index_writer.add_document(doc!(id_field=>0u64)), 0u64);
+        // 2,499 documents with consecutive opstamps.
+        let docs = (1u64..2_500u64).map(|id| doc!(id_field=>id));
+        assert_eq!(index_writer.add_documents(docs)?, 2_499u64);
+        assert_eq!(index_writer.num_uncommitted_adds(), 2_500);
+        assert_eq!(
+            index_writer.add_documents(Vec::<Document>::new())?,
+            2_500u64
+        );
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 2_500u64);
+        assert_eq!(searcher.segment_readers().len(), 1);
+        for doc_id in (0u32..2_500u32).step_by(97) {
+            let doc = searcher.doc(DocAddress(0, doc_id))?;
+            assert_eq!(doc.get_first(id_field), Some(&Value::U64(doc_id as u64)));
+        }
+        let query = TermQuery::new(
+            Term::from_field_u64(id_field, 1_234u64),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
     }
 
     #[test]
@@ -874,7 +1861,7 @@ mod tests {
             UserOperation::Delete(b_term),
         ];
 
-        index_writer.run(operations);
+        index_writer.run(operations).unwrap();
         index_writer.commit().expect("failed to commit");
         reader.reload().expect("failed to load searchers");
 
@@ -899,16 +1886,891 @@ mod tests {
     }
 
     #[test]
-    fn test_empty_operations_group() {
-        let schema_builder = schema::Schema::builder();
+    fn test_delete_range() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut index_writer = index.writer_for_tests().unwrap();
+        for id in 0u64..30u64 {
+            index_writer.add_document(doc!(id_field=>id));
+        }
+        index_writer.commit().unwrap();
+        // [10, 20)
+        index_writer
+            .delete_range(
+                id_field,
+                Bound::Included(Value::U64(10)),
+                Bound::Excluded(Value::U64(20)),
+            )
+            .unwrap();
+        // (25, +inf)
+        index_writer
+            .delete_range(id_field, Bound::Excluded(Value::U64(25)), Bound::Unbounded)
+            .unwrap();
+        // added after the delete operations, hence not deleted.
+        index_writer.add_document(doc!(id_field=>15u64));
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        let count = |range: Range<u64>| {
+            searcher
+                .search(&RangeQuery::new_u64(id_field, range), &Count)
+                .unwrap()
+        };
+        assert_eq!(count(0..10), 10);
+        assert_eq!(count(10..20), 1);
+        assert_eq!(count(20..26), 6);
+        assert_eq!(count(26..100), 0);
+    }
+
+    #[test]
+    fn test_delete_typed_ranges() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let offset_field = schema_builder.add_i64_field("offset", schema::INDEXED);
+        let score_field = schema_builder.add_f64_field("score", schema::INDEXED);
+        let date_field = schema_builder.add_date_field("date", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut index_writer = index.writer_for_tests().unwrap();
+        for id in 0u64..40u64 {
+            let mut doc = Document::default();
+            doc.add_u64(id_field, id)
+                .add_i64(offset_field, id as i64 - 20)
+                .add_f64(score_field, id as f64 / 2.0)
+                .add_date_from_timestamp_secs(date_field, id as i64 * 60);
+            index_writer.add_document(doc);
+        }
+        index_writer.commit().unwrap();
+        // ids [5, 10)
+        index_writer.delete_range_u64(id_field, 5, 10).unwrap();
+        // ids [15, 18), offsets [-5, -2)
+        index_writer.delete_range_i64(offset_field, -5, -2).unwrap();
+        // ids [24, 27), scores [12.0, 13.5)
+        index_writer
+            .delete_range_f64(score_field, 12.0f64, 13.5f64)
+            .unwrap();
+        // ids [30, 35), dates [30min, 35min)
+        index_writer
+            .delete_range_date(
+                date_field,
+                &Utc.timestamp(30 * 60, 0),
+                &Utc.timestamp(35 * 60, 0),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        let searcher = reader.searcher();
+        let count = |range: Range<u64>| {
+            searcher
+                .search(&RangeQuery::new_u64(id_field, range), &Count)
+                .unwrap()
+        };
+        assert_eq!(count(0..5), 5);
+        assert_eq!(count(5..10), 0);
+        assert_eq!(count(10..15), 5);
+        assert_eq!(count(15..18), 0);
+        assert_eq!(count(18..24), 6);
+        assert_eq!(count(24..27), 0);
+        assert_eq!(count(27..30), 3);
+        assert_eq!(count(30..35), 0);
+        assert_eq!(count(35..40), 5);
+        // the bounds need to be of the same type as the field.
+        assert!(index_writer.delete_range_u64(offset_field, 0, 1).is_err());
+        assert!(index_writer.delete_range_i64(id_field, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_delete_empty_range() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let mut index_writer = index.writer_for_tests().unwrap();
+        for id in 0u64..10u64 {
+            index_writer.add_document(doc!(id_field=>id));
+        }
+        index_writer
+            .delete_range(
+                id_field,
+                Bound::Excluded(Value::U64(5)),
+                Bound::Excluded(Value::U64(5)),
+            )
+            .unwrap();
+        index_writer
+            .delete_range(
+                id_field,
+                Bound::Included(Value::U64(8)),
+                Bound::Included(Value::U64(2)),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 10);
+    }
+
+    #[test]
+    fn test_delete_range_invalid() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let stored_field = schema_builder.add_u64_field("stored", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests().unwrap();
+        assert!(matches!(
+            index_writer.delete_range(id_field, Bound::Included(Value::I64(1)), Bound::Unbounded),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            index_writer.delete_range(
+                stored_field,
+                Bound::Included(Value::U64(1)),
+                Bound::Unbounded
+            ),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    fn test_delete_query() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        for id in 0u64..30u64 {
+            let text = if id % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(id_field=>id, text_field=>text));
+        }
+        index_writer.commit()?;
+        // the even ids within [10, 20)
+        let query = BooleanQuery::intersection(vec![
+            Box::new(RangeQuery::new_u64(id_field, 10..20)),
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, "even"),
+                IndexRecordOption::Basic,
+            )),
+        ]);
+        index_writer.delete_query(Box::new(query));
+        // added after the delete operation, hence not deleted.
+        index_writer.add_document(doc!(id_field=>12u64, text_field=>"even"));
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let count = |range: Range<u64>| {
+            searcher
+                .search(&RangeQuery::new_u64(id_field, range), &Count)
+                .unwrap()
+        };
+        assert_eq!(count(0..10), 10);
+        assert_eq!(count(10..20), 6);
+        assert_eq!(count(12..13), 1);
+        assert_eq!(count(20..30), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_terms() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        for id in 0u64..2_000u64 {
+            index_writer.add_document(doc!(id_field=>id));
+        }
+        index_writer.commit()?;
+        // added in the same commit, before the delete operation, hence deleted.
+        index_writer.add_document(doc!(id_field=>10u64));
+        let terms: Vec<Term> = (0u64..1_000u64)
+            .map(|id| Term::from_field_u64(id_field, id))
+            .collect();
+        let delete_opstamp = index_writer.delete_terms(id_field, terms)?;
+        // added after the delete operation, hence not deleted.
+        let add_opstamp = index_writer.add_document(doc!(id_field=>500u64));
+        assert_eq!(add_opstamp, delete_opstamp + 1);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let count = |range: Range<u64>| {
+            searcher
+                .search(&RangeQuery::new_u64(id_field, range), &Count)
+                .unwrap()
+        };
+        assert_eq!(count(0..1_000), 1);
+        assert_eq!(count(500..501), 1);
+        assert_eq!(count(1_000..2_000), 1_000);
+        let mixed_terms = vec![
+            Term::from_field_u64(id_field, 1_500u64),
+            Term::from_field_text(text_field, "hello"),
+        ];
+        assert!(matches!(
+            index_writer.delete_terms(id_field, mixed_terms),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_term_batch() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        for text in &["a", "b", "c", "d"] {
+            index_writer.add_document(doc!(text_field=>*text));
+        }
+        let commit_opstamp = index_writer.commit()?;
+        let terms: Vec<Term> = vec!["a", "c", "d"]
+            .into_iter()
+            .map(|text| Term::from_field_text(text_field, text))
+            .collect();
+        let opstamps = index_writer.delete_term_batch(terms);
+        assert_eq!(
+            opstamps,
+            vec![commit_opstamp + 1, commit_opstamp + 2, commit_opstamp + 3]
+        );
+        assert_eq!(index_writer.num_uncommitted_deletes(), 3);
+        assert!(index_writer.delete_term_batch(Vec::new()).is_empty());
+        // added after the delete operations, hence not deleted.
+        let add_opstamp = index_writer.add_document(doc!(text_field=>"a"));
+        assert_eq!(add_opstamp, commit_opstamp + 4);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let count = |text: &str| {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("a"), 1);
+        assert_eq!(count("b"), 1);
+        assert_eq!(count("c"), 0);
+        assert_eq!(count("d"), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_num_uncommitted_operations() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        assert_eq!(index_writer.num_uncommitted_operations(), 0);
+        for id in 0u64..10u64 {
+            index_writer.add_document(doc!(id_field=>id));
+        }
+        index_writer.delete_term(Term::from_field_u64(id_field, 3u64));
+        index_writer.upsert_document(Term::from_field_u64(id_field, 4u64), doc!(id_field=>4u64));
+        index_writer.run(vec![
+            UserOperation::Add(doc!(id_field=>11u64)),
+            UserOperation::Delete(Term::from_field_u64(id_field, 5u64)),
+        ])?;
+        assert_eq!(index_writer.num_uncommitted_adds(), 12);
+        assert_eq!(index_writer.num_uncommitted_deletes(), 3);
+        assert_eq!(index_writer.num_uncommitted_operations(), 15);
+        index_writer.commit()?;
+        assert_eq!(index_writer.num_uncommitted_operations(), 0);
+        index_writer.add_document(doc!(id_field=>12u64));
+        assert_eq!(index_writer.num_uncommitted_operations(), 1);
+        index_writer.rollback()?;
+        assert_eq!(index_writer.num_uncommitted_operations(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_operations_eq() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let term = Term::from_field_u64(id_field, 1u64);
+        assert_eq!(
+            UserOperation::Delete(term.clone()),
+            UserOperation::Delete(term.clone())
+        );
+        assert_ne!(
+            UserOperation::Delete(term.clone()),
+            UserOperation::Add(doc!(id_field=>1u64))
+        );
+        let delete_operation = DeleteOperation {
+            opstamp: 1u64,
+            target: DeleteTarget::ByQuery(Box::new(RangeQuery::new_u64(id_field, 0..10))),
+        };
+        // queries are only equal to themselves.
+        let same_delete_operation = &delete_operation;
+        assert_eq!(*same_delete_operation, delete_operation);
+        let cloned_delete_operation = delete_operation.clone();
+        assert_eq!(cloned_delete_operation.opstamp, 1u64);
+        assert!(matches!(
+            cloned_delete_operation.target,
+            DeleteTarget::ByQuery(_)
+        ));
+        assert_ne!(cloned_delete_operation, delete_operation);
+        let delete_by_query =
+            UserOperation::DeleteByQuery(Box::new(RangeQuery::new_u64(id_field, 0..10)));
+        let same_delete_by_query = &delete_by_query;
+        assert_eq!(*same_delete_by_query, delete_by_query);
+        assert_ne!(
+            delete_by_query,
+            UserOperation::DeleteByQuery(Box::new(RangeQuery::new_u64(id_field, 0..10)))
+        );
+    }
+
+    #[test]
+    fn test_delete_by_query_operation() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let operations = vec![
+            UserOperation::Add(doc!(id_field=>1u64)),
+            UserOperation::Add(doc!(id_field=>2u64)),
+            UserOperation::DeleteByQuery(Box::new(RangeQuery::new_u64(id_field, 0..10))),
+            UserOperation::Add(doc!(id_field=>3u64)),
+        ];
+        index_writer.run(operations)?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(
+            searcher.search(&RangeQuery::new_u64(id_field, 3..4), &Count)?,
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_compression_threshold() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        assert_eq!(index_writer.get_bytes_compression_threshold(), None);
+        index_writer.set_bytes_compression_threshold(Some(1_000))?;
+        assert_eq!(index_writer.get_bytes_compression_threshold(), Some(1_000));
+        let small_payload = vec![1u8; 10];
+        let large_payload = vec![2u8; 100_000];
+        index_writer.add_document(doc!(payload_field=>small_payload.clone()));
+        index_writer.add_document(doc!(payload_field=>large_payload.clone()));
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let payloads: Vec<Vec<u8>> = (0..2)
+            .map(|doc_id| {
+                let doc = searcher.doc(DocAddress(0, doc_id)).unwrap();
+                doc.get_first(payload_field)
+                    .and_then(Value::as_bytes)
+                    .unwrap()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(payloads, vec![small_payload, large_payload]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_compression_threshold_kept_on_rollback() -> crate::Result<()> {
+        use crate::store::DocumentCodec;
+        use std::io;
+        use std::sync::{Arc, Mutex};
+
+        // records the thresholds the store writers are created with.
+        #[derive(Clone, Default)]
+        struct RecordingCodec {
+            thresholds: Arc<Mutex<Vec<Option<usize>>>>,
+        }
+
+        impl DocumentCodec for RecordingCodec {
+            fn name(&self) -> &str {
+                "recording"
+            }
+
+            fn serialize(&self, doc: &Document, mut writer: &mut dyn io::Write) -> io::Result<()> {
+                doc.serialize(&mut writer)
+            }
+
+            fn deserialize(&self, mut reader: &mut dyn io::Read) -> io::Result<Document> {
+                Document::deserialize(&mut reader)
+            }
+
+            fn with_bytes_compression(
+                &self,
+                bytes_compression_threshold: Option<usize>,
+            ) -> Option<Arc<dyn DocumentCodec>> {
+                self.thresholds
+                    .lock()
+                    .unwrap()
+                    .push(bytes_compression_threshold);
+                Some(Arc::new(self.clone()))
+            }
+        }
+
+        let mut schema_builder = schema::Schema::builder();
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let codec = RecordingCodec::default();
+        index.set_doc_store_codec(Arc::new(codec.clone()));
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_bytes_compression_threshold(Some(1_000))?;
+        index_writer.add_document(doc!(payload_field=>vec![1u8; 10]));
+        index_writer.rollback()?;
+        assert_eq!(index_writer.get_bytes_compression_threshold(), Some(1_000));
+        codec.thresholds.lock().unwrap().clear();
+        index_writer.add_document(doc!(payload_field=>vec![1u8; 10]));
+        index_writer.commit()?;
+        let thresholds = codec.thresholds.lock().unwrap().clone();
+        assert!(!thresholds.is_empty());
+        assert!(thresholds.iter().all(|&threshold| threshold == Some(1_000)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_doc_store_codec() -> crate::Result<()> {
+        use crate::store::DocumentCodec;
+        use std::io;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct CountingCodec {
+            num_deserialized: AtomicUsize,
+        }
+
+        impl DocumentCodec for CountingCodec {
+            fn name(&self) -> &str {
+                "counting"
+            }
+
+            fn serialize(&self, doc: &Document, mut writer: &mut dyn io::Write) -> io::Result<()> {
+                doc.serialize(&mut writer)
+            }
+
+            fn deserialize(&self, mut reader: &mut dyn io::Read) -> io::Result<Document> {
+                self.num_deserialized.fetch_add(1, Ordering::SeqCst);
+                Document::deserialize(&mut reader)
+            }
+        }
+
+        let mut schema_builder = schema::Schema::builder();
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), schema_builder.build())?;
+        let codec = Arc::new(CountingCodec::default());
+        index.set_doc_store_codec(codec.clone());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.set_bytes_compression_threshold(Some(1_000)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        index_writer.set_bytes_compression_threshold(None)?;
+        assert_eq!(index_writer.get_bytes_compression_threshold(), None);
+        index_writer.add_document(doc!(payload_field=>vec![1u8; 10]));
+        index_writer.commit()?;
+        reader.reload()?;
+        let doc = reader.searcher().doc(DocAddress(0, 0))?;
+        assert_eq!(
+            doc.get_first(payload_field).and_then(Value::as_bytes),
+            Some(&[1u8; 10][..])
+        );
+        assert_eq!(codec.num_deserialized.load(Ordering::SeqCst), 1);
+        drop(index_writer);
+        // the name of the codec is recorded in the meta of the index.
+        assert_eq!(
+            index.load_metas()?.doc_store_codec,
+            Some("counting".to_string())
+        );
+        let mut reopened_index = Index::open(directory)?;
+        assert!(matches!(
+            reopened_index.reader(),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            reopened_index.writer_for_tests(),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        reopened_index.set_doc_store_codec(codec);
+        assert_eq!(reopened_index.reader()?.searcher().num_docs(), 1);
+        reopened_index.writer_for_tests()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_interned_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let status_field = schema_builder.add_text_field("status", STRING | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert!(index_writer.get_interned_fields().is_empty());
+        index_writer.set_interned_fields(vec![status_field]);
+        assert_eq!(index_writer.get_interned_fields(), vec![status_field]);
+        let statuses = ["active", "pending", "closed"];
+        for _ in 0..2 {
+            for i in 0..30 {
+                index_writer.add_document(doc!(status_field=>statuses[i % statuses.len()]));
+            }
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        block_on(index_writer.merge(&segment_ids))?;
+        index_writer.wait_merging_threads()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        for doc_id in 0..60u32 {
+            let doc = searcher.doc(DocAddress(0, doc_id))?;
+            assert_eq!(
+                doc.get_first_str(status_field),
+                Some(statuses[doc_id as usize % statuses.len()])
+            );
+        }
+        let pending_query = TermQuery::new(
+            Term::from_field_text(status_field, "pending"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&pending_query, &Count)?, 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interned_fields_kept_on_rollback() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let status_field = schema_builder.add_text_field("status", STRING | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_interned_fields(vec![status_field]);
+        index_writer.add_document(doc!(status_field=>"active"));
+        index_writer.rollback()?;
+        assert_eq!(index_writer.get_interned_fields(), vec![status_field]);
+        index_writer.add_document(doc!(status_field=>"pending"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let store_reader = searcher.segment_reader(0).get_store_reader()?;
+        assert!(store_reader.has_string_dictionary());
+        assert_eq!(
+            store_reader.get(0)?.get_first_str(status_field),
+            Some("pending")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_document_bytes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let text_field = schema_builder.add_text_field("text", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        assert_eq!(index_writer.get_max_document_bytes(), None);
+        index_writer.set_max_document_bytes(Some(100), false);
+        assert_eq!(index_writer.get_max_document_bytes(), Some((100, false)));
+        let small_opstamp = index_writer.try_add_document(doc!(payload_field=>vec![0u8; 100]))?;
+        let large_doc = doc!(payload_field=>vec![0u8; 60], payload_field=>vec![1u8; 41]);
+        assert!(matches!(
+            index_writer.try_add_document(large_doc.clone()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.add_documents(vec![doc!(payload_field=>vec![2u8; 10]), large_doc.clone()]),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer
+                .try_upsert_document(Term::from_field_u64(id_field, 1u64), large_doc.clone()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let operations = || {
+            vec![
+                UserOperation::Add(doc!(payload_field=>vec![3u8; 10])),
+                UserOperation::Add(large_doc.clone()),
+            ]
+        };
+        assert!(matches!(
+            index_writer.run(operations()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.run_deduplicated(id_field, operations()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.run_chunked(operations()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        // rejected documents do not consume any opstamp.
+        let opstamp = index_writer.try_add_document(doc!(payload_field=>vec![4u8; 10]))?;
+        assert_eq!(opstamp, small_opstamp + 1);
+        // texts are only counted if requested.
+        let text_doc = doc!(text_field=>"a".repeat(200));
+        index_writer.try_add_document(text_doc.clone())?;
+        index_writer.set_max_document_bytes(Some(100), true);
+        assert!(index_writer.try_add_document(text_doc).is_err());
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 3);
+        index_writer.set_max_document_bytes(None, false);
+        index_writer.add_document(large_doc);
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 4);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to index document")]
+    fn test_add_document_above_max_document_bytes_panics() {
+        let mut schema_builder = schema::Schema::builder();
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests().unwrap();
+        index_writer.set_max_document_bytes(Some(100), false);
+        index_writer.add_document(doc!(payload_field=>vec![0u8; 101]));
+    }
+
+    #[test]
+    fn test_max_document_bytes_kept_on_rollback() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let payload_field = schema_builder.add_bytes_field("payload", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_max_document_bytes(Some(100), false);
+        index_writer.rollback()?;
+        assert_eq!(index_writer.get_max_document_bytes(), Some((100, false)));
+        assert!(matches!(
+            index_writer.try_add_document(doc!(payload_field=>vec![0u8; 101])),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_precisions() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let day_field = schema_builder.add_date_field("day", schema::STORED);
+        let timestamp_field = schema_builder.add_date_field("timestamp", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(index_writer.get_date_precisions().is_empty());
+        index_writer.set_date_precisions(vec![(day_field, DatePrecision::Seconds)]);
+        assert_eq!(
+            index_writer.get_date_precisions(),
+            vec![(day_field, DatePrecision::Seconds)]
+        );
+        let date = Utc.timestamp(1_600_000_000, 123_456_789);
+        let mut doc = Document::default();
+        doc.add_date(day_field, &date)
+            .add_date(timestamp_field, &date);
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        reader.reload()?;
+        let doc = reader.searcher().doc(DocAddress(0, 0))?;
+        assert_eq!(
+            doc.get_first(day_field),
+            Some(&Value::Date(Utc.timestamp(1_600_000_000, 0)))
+        );
+        assert_eq!(doc.get_first(timestamp_field), Some(&Value::Date(date)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_precisions_kept_on_rollback() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let day_field = schema_builder.add_date_field("day", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_date_precisions(vec![(day_field, DatePrecision::Seconds)]);
+        let date = Utc.timestamp(1_600_000_000, 123_456_789);
+        index_writer.add_document(doc!(day_field=>date));
+        index_writer.rollback()?;
+        assert_eq!(
+            index_writer.get_date_precisions(),
+            vec![(day_field, DatePrecision::Seconds)]
+        );
+        index_writer.add_document(doc!(day_field=>date));
+        index_writer.commit()?;
+        reader.reload()?;
+        let doc = reader.searcher().doc(DocAddress(0, 0))?;
+        assert_eq!(
+            doc.get_first(day_field),
+            Some(&Value::Date(Utc.timestamp(1_600_000_000, 0)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bool_values_in_u64_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let flag_field = schema_builder.add_u64_field("flag", schema::FAST | schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_bool(flag_field, true);
+        index_writer.add_document(doc);
+        let mut doc = Document::default();
+        doc.add_bool(flag_field, false);
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let fast_field_reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .u64(flag_field)
+            .unwrap();
+        assert_eq!(fast_field_reader.get(0), 1u64);
+        assert_eq!(fast_field_reader.get(1), 0u64);
+        let query = TermQuery::new(
+            Term::from_field_u64(flag_field, 1u64),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_f32_values_in_f64_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let score_field = schema_builder.add_f64_field("score", schema::FAST | schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_f32(score_field, 0.1f32);
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let score_reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .f64(score_field)
+            .unwrap();
+        // f32 values are widened to f64.
+        assert_eq!(score_reader.get(0), f64::from(0.1f32));
+        let query = TermQuery::new(
+            Term::from_field_f64(score_field, f64::from(0.1f32)),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_values_in_numeric_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let count_field = schema_builder.add_i64_field("count", schema::FAST | schema::INDEXED);
+        let tags_field = schema_builder.add_u64_field(
+            "tags",
+            schema::IntOptions::default()
+                .set_indexed()
+                .set_fast(schema::Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_null(count_field)
+            .add_null(tags_field)
+            .add_u64(tags_field, 3u64);
+        index_writer.add_document(doc);
+        let mut doc = Document::default();
+        doc.add_i64(count_field, -2i64).add_null(tags_field);
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let segment_reader = searcher.segment_reader(0);
+        let count_reader = segment_reader.fast_fields().i64(count_field).unwrap();
+        // a null value is handled as a missing value.
+        assert_eq!(count_reader.get(0), 0i64);
+        assert_eq!(count_reader.get(1), -2i64);
+        let tags_reader = segment_reader.fast_fields().u64s(tags_field).unwrap();
+        let mut tags = Vec::new();
+        tags_reader.get_vals(0, &mut tags);
+        assert_eq!(tags, vec![3u64]);
+        tags_reader.get_vals(1, &mut tags);
+        assert!(tags.is_empty());
+        let query = TermQuery::new(
+            Term::from_field_i64(count_field, -2i64),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_operations_group() {
+        let schema_builder = schema::Schema::builder();
         let index = Index::create_in_ram(schema_builder.build());
         let index_writer = index.writer(3_000_000).unwrap();
         let operations1 = vec![];
-        let batch_opstamp1 = index_writer.run(operations1);
-        assert_eq!(batch_opstamp1, 0u64);
+        let opstamps1 = index_writer.run(operations1).unwrap();
+        assert_eq!(opstamps1, 0u64..0u64);
         let operations2 = vec![];
-        let batch_opstamp2 = index_writer.run(operations2);
-        assert_eq!(batch_opstamp2, 1u64);
+        let opstamps2 = index_writer.run(operations2).unwrap();
+        assert_eq!(opstamps2, 1u64..1u64);
+    }
+
+    #[test]
+    fn test_run_returns_opstamp_range() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer = index.writer_for_tests().unwrap();
+        let first_opstamp = index_writer.add_document(doc!(text_field=>"a"));
+        let operations = vec![
+            UserOperation::Add(doc!(text_field=>"b")),
+            UserOperation::Delete(Term::from_field_text(text_field, "a")),
+            UserOperation::Add(doc!(text_field=>"c")),
+        ];
+        let num_operations = operations.len();
+        let opstamps = index_writer.run(operations).unwrap();
+        assert_eq!(opstamps.start, first_opstamp + 1);
+        assert_eq!(opstamps.end - opstamps.start, num_operations as u64);
+        assert_eq!(opstamps.clone().count(), num_operations);
+        // the opstamp following the batch opstamp is given to the next operation.
+        let next_opstamp = index_writer.add_document(doc!(text_field=>"d"));
+        assert_eq!(next_opstamp, opstamps.end + 1);
     }
 
     #[test]
@@ -1176,7 +3038,7 @@ mod tests {
         // rollback to last commit, when index was empty
         let rollback = index_writer.rollback();
         assert!(rollback.is_ok());
-        let rollback_tstamp = rollback.unwrap();
+        let rollback_tstamp = rollback.unwrap().opstamp;
         assert_eq!(rollback_tstamp, second_commit_tstamp);
 
         // working with an empty index == no documents
@@ -1,35 +1,170 @@
+use crate::query::{Query, QueryClone};
 use crate::schema::Document;
 use crate::schema::Term;
+use crate::schema::{Field, Value};
 use crate::Opstamp;
+use std::ops::Bound;
+
+/// Describes the documents affected by a `DeleteOperation`.
+///
+/// Queries have no notion of equality: two `ByQuery` targets
+/// are equal if they hold the very same query.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug)]
+pub enum DeleteTarget {
+    /// Deletes all documents containing the given term.
+    ByTerm(Term),
+    /// Deletes all documents containing at least one of the given terms.
+    ///
+    /// All of the terms are required to be terms of `field`.
+    ByTerms {
+        /// Field of the terms.
+        field: Field,
+        /// Terms of the documents to delete.
+        terms: Vec<Term>,
+    },
+    /// Deletes all documents having at least one value of `field`
+    /// within the given range.
+    ///
+    /// The field is required to be indexed, and the bounds are required
+    /// to be of the same type as the field.
+    ByRange {
+        /// Field on which the range applies.
+        field: Field,
+        /// Lower bound of the range.
+        lower: Bound<Value>,
+        /// Upper bound of the range.
+        upper: Bound<Value>,
+    },
+    /// Deletes all documents matching the given query.
+    ///
+    /// The query is evaluated against each segment, with scoring disabled.
+    /// This is significantly more expensive than deleting by term.
+    ByQuery(Box<dyn Query>),
+}
+
+// Compares the addresses of the queries, regardless of their vtable.
+fn is_same_query(left: &dyn Query, right: &dyn Query) -> bool {
+    std::ptr::eq(
+        left as *const dyn Query as *const u8,
+        right as *const dyn Query as *const u8,
+    )
+}
+
+impl Clone for DeleteTarget {
+    fn clone(&self) -> Self {
+        match self {
+            DeleteTarget::ByTerm(term) => DeleteTarget::ByTerm(term.clone()),
+            DeleteTarget::ByTerms { field, terms } => DeleteTarget::ByTerms {
+                field: *field,
+                terms: terms.clone(),
+            },
+            DeleteTarget::ByRange {
+                field,
+                lower,
+                upper,
+            } => DeleteTarget::ByRange {
+                field: *field,
+                lower: lower.clone(),
+                upper: upper.clone(),
+            },
+            DeleteTarget::ByQuery(query) => DeleteTarget::ByQuery(query.box_clone()),
+        }
+    }
+}
+
+impl PartialEq for DeleteTarget {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DeleteTarget::ByTerm(left), DeleteTarget::ByTerm(right)) => left == right,
+            (
+                DeleteTarget::ByTerms {
+                    field: left_field,
+                    terms: left_terms,
+                },
+                DeleteTarget::ByTerms {
+                    field: right_field,
+                    terms: right_terms,
+                },
+            ) => left_field == right_field && left_terms == right_terms,
+            (
+                DeleteTarget::ByRange {
+                    field: left_field,
+                    lower: left_lower,
+                    upper: left_upper,
+                },
+                DeleteTarget::ByRange {
+                    field: right_field,
+                    lower: right_lower,
+                    upper: right_upper,
+                },
+            ) => {
+                left_field == right_field && left_lower == right_lower && left_upper == right_upper
+            }
+            (DeleteTarget::ByQuery(left), DeleteTarget::ByQuery(right)) => {
+                is_same_query(left.as_ref(), right.as_ref())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DeleteTarget {}
 
 /// Timestamped Delete operation.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct DeleteOperation {
     pub opstamp: Opstamp,
-    pub term: Term,
+    pub target: DeleteTarget,
 }
 
 impl Default for DeleteOperation {
     fn default() -> Self {
         DeleteOperation {
             opstamp: 0u64,
-            term: Term::new(),
+            target: DeleteTarget::ByTerm(Term::new()),
         }
     }
 }
 
 /// Timestamped Add operation.
+///
+/// If `delete_term` is set, the documents containing this term are deleted
+/// at the same opstamp, before the document is added.
 #[derive(Eq, PartialEq, Debug)]
 pub struct AddOperation {
     pub opstamp: Opstamp,
     pub document: Document,
+    pub delete_term: Option<Term>,
 }
 
 /// UserOperation is an enum type that encapsulates other operation types.
-#[derive(Eq, PartialEq, Debug)]
+///
+/// Like for `DeleteTarget`, two `DeleteByQuery` operations are equal
+/// if they hold the very same query.
+#[derive(Debug)]
 pub enum UserOperation {
     /// Add operation
     Add(Document),
     /// Delete operation
     Delete(Term),
+    /// Delete all of the documents matching a query.
+    ///
+    /// See [`IndexWriter::delete_query`](../struct.IndexWriter.html#method.delete_query).
+    DeleteByQuery(Box<dyn Query>),
 }
+
+impl PartialEq for UserOperation {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (UserOperation::Add(left), UserOperation::Add(right)) => left == right,
+            (UserOperation::Delete(left), UserOperation::Delete(right)) => left == right,
+            (UserOperation::DeleteByQuery(left), UserOperation::DeleteByQuery(right)) => {
+                is_same_query(left.as_ref(), right.as_ref())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for UserOperation {}
@@ -1,20 +1,37 @@
 use super::IndexWriter;
 use crate::Opstamp;
 use futures::executor::block_on;
+use std::ops::Range;
+
+/// Outcome of a rollback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollbackResult {
+    /// Opstamp of the last commit, which the index was rolled back to.
+    pub opstamp: Opstamp,
+    /// Number of add operations that were discarded.
+    pub num_reverted_adds: usize,
+    /// Number of delete operations that were discarded.
+    pub num_reverted_deletes: usize,
+}
 
 /// A prepared commit
 pub struct PreparedCommit<'a> {
     index_writer: &'a mut IndexWriter,
     payload: Option<String>,
     opstamp: Opstamp,
+    first_opstamp: Opstamp,
 }
 
 impl<'a> PreparedCommit<'a> {
-    pub(crate) fn new(index_writer: &'a mut IndexWriter, opstamp: Opstamp) -> PreparedCommit<'_> {
+    pub(crate) fn new(
+        index_writer: &'a mut IndexWriter,
+        opstamp_range: Range<Opstamp>,
+    ) -> PreparedCommit<'_> {
         PreparedCommit {
             index_writer,
             payload: None,
-            opstamp,
+            opstamp: opstamp_range.end,
+            first_opstamp: opstamp_range.start,
         }
     }
 
@@ -22,14 +39,40 @@ impl<'a> PreparedCommit<'a> {
         self.opstamp
     }
 
+    /// Returns the range of the opstamps of the operations made durable
+    /// by the commit, i.e. the operations run since the previous commit.
+    ///
+    /// The range ends with the opstamp of the commit itself, which is
+    /// excluded. It is empty if no operation was run since the previous commit.
+    pub fn opstamp_range(&self) -> Range<Opstamp> {
+        self.first_opstamp..self.opstamp
+    }
+
     pub fn set_payload(&mut self, payload: &str) {
         self.payload = Some(payload.to_string())
     }
 
-    pub fn abort(self) -> crate::Result<Opstamp> {
+    pub fn payload(&self) -> Option<&str> {
+        self.payload.as_deref()
+    }
+
+    pub fn clear_payload(&mut self) {
+        self.payload = None
+    }
+
+    /// Cancels the prepared commit, rolling back to the last commit.
+    ///
+    /// The result reports the number of add and delete operations discarded.
+    pub fn abort(self) -> crate::Result<RollbackResult> {
         self.index_writer.rollback()
     }
 
+    /// Commits the prepared commit, blocking the current thread
+    /// until the commit is done.
+    ///
+    /// This method must not be called from within an async runtime,
+    /// as blocking on the commit may deadlock. Use
+    /// [`commit_async`](#method.commit_async) there instead.
     pub fn commit(self) -> crate::Result<Opstamp> {
         info!("committing {}", self.opstamp);
         let _ = block_on(
@@ -37,6 +80,159 @@ impl<'a> PreparedCommit<'a> {
                 .segment_updater()
                 .schedule_commit(self.opstamp, self.payload),
         );
+        self.index_writer.reset_uncommitted_operations(self.opstamp);
         Ok(self.opstamp)
     }
+
+    /// Commits the prepared commit, without blocking the current thread.
+    pub async fn commit_async(self) -> crate::Result<Opstamp> {
+        info!("committing {}", self.opstamp);
+        self.index_writer
+            .segment_updater()
+            .schedule_commit(self.opstamp, self.payload)
+            .await?;
+        self.index_writer.reset_uncommitted_operations(self.opstamp);
+        Ok(self.opstamp)
+    }
+
+    pub fn commit_with_payload(self) -> crate::Result<(Opstamp, Option<String>)> {
+        let payload = self.payload.clone();
+        let opstamp = self.commit()?;
+        Ok((opstamp, payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RollbackResult;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_prepared_commit_payload() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        assert_eq!(prepared_commit.payload(), None);
+        prepared_commit.set_payload("first");
+        assert_eq!(prepared_commit.payload(), Some("first"));
+        prepared_commit.clear_payload();
+        assert_eq!(prepared_commit.payload(), None);
+        prepared_commit.set_payload("second");
+        assert_eq!(prepared_commit.payload(), Some("second"));
+        prepared_commit.commit()?;
+        assert_eq!(index.load_metas()?.payload, Some("second".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepared_commit_with_payload() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a"));
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        prepared_commit.set_payload("payload");
+        let expected_opstamp = prepared_commit.opstamp();
+        assert_eq!(
+            prepared_commit.commit_with_payload()?,
+            (expected_opstamp, Some("payload".to_string()))
+        );
+        let prepared_commit = index_writer.prepare_commit()?;
+        let (_, payload) = prepared_commit.commit_with_payload()?;
+        assert_eq!(payload, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepare_commit_with_payload() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a"));
+        let prepared_commit = index_writer.prepare_commit_with_payload("payload")?;
+        assert_eq!(prepared_commit.payload(), Some("payload"));
+        prepared_commit.commit()?;
+        assert_eq!(index.load_metas()?.payload, Some("payload".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepared_commit_opstamp_range() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let first_opstamp = index_writer.add_document(doc!(text=>"a"));
+        index_writer.delete_term(Term::from_field_text(text, "b"));
+        let last_opstamp = index_writer.add_documents(vec![doc!(text=>"b"), doc!(text=>"c")])?;
+        let prepared_commit = index_writer.prepare_commit()?;
+        let opstamp_range = prepared_commit.opstamp_range();
+        assert_eq!(opstamp_range, first_opstamp..last_opstamp + 1);
+        assert_eq!(opstamp_range.end, prepared_commit.opstamp());
+        let commit_opstamp = prepared_commit.commit()?;
+        // no operation since the previous commit.
+        let prepared_commit = index_writer.prepare_commit()?;
+        assert!(prepared_commit.opstamp_range().is_empty());
+        assert_eq!(prepared_commit.opstamp_range().start, commit_opstamp + 1);
+        prepared_commit.commit()?;
+        let opstamp = index_writer.add_document(doc!(text=>"d"));
+        let prepared_commit = index_writer.prepare_commit()?;
+        assert_eq!(prepared_commit.opstamp_range(), opstamp..opstamp + 1);
+        // aborting rolls back to the last commit.
+        prepared_commit.abort()?;
+        let opstamp = index_writer.add_document(doc!(text=>"e"));
+        let prepared_commit = index_writer.prepare_commit()?;
+        assert_eq!(prepared_commit.opstamp_range().end, opstamp + 1);
+        assert!(prepared_commit.opstamp_range().contains(&opstamp));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepared_commit_abort() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a"));
+        let commit_opstamp = index_writer.commit()?;
+        index_writer.add_document(doc!(text=>"b"));
+        index_writer.add_document(doc!(text=>"c"));
+        index_writer.delete_term(Term::from_field_text(text, "a"));
+        let prepared_commit = index_writer.prepare_commit()?;
+        assert_eq!(
+            prepared_commit.abort()?,
+            RollbackResult {
+                opstamp: commit_opstamp,
+                num_reverted_adds: 2,
+                num_reverted_deletes: 1,
+            }
+        );
+        let prepared_commit = index_writer.prepare_commit()?;
+        assert_eq!(prepared_commit.abort()?.num_reverted_adds, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepared_commit_async() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a"));
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        prepared_commit.set_payload("async");
+        let expected_opstamp = prepared_commit.opstamp();
+        let opstamp = futures::executor::block_on(prepared_commit.commit_async())?;
+        assert_eq!(opstamp, expected_opstamp);
+        assert_eq!(index.load_metas()?.payload, Some("async".to_string()));
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        Ok(())
+    }
 }
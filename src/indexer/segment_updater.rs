@@ -8,7 +8,7 @@ use crate::core::SerializableSegment;
 use crate::core::META_FILEPATH;
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::indexer::delete_queue::DeleteCursor;
-use crate::indexer::index_writer::advance_deletes;
+use crate::indexer::index_writer::{advance_deletes, IndexWriterSettings};
 use crate::indexer::merge_operation::MergeOperationInventory;
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
@@ -17,7 +17,7 @@ use crate::indexer::SegmentEntry;
 use crate::indexer::SegmentSerializer;
 use crate::indexer::{DefaultMergePolicy, MergePolicy};
 use crate::indexer::{MergeCandidate, MergeOperation};
-use crate::schema::Schema;
+use crate::schema::{DatePrecision, Field, Schema};
 use crate::Opstamp;
 use futures::channel::oneshot;
 use futures::executor::{ThreadPool, ThreadPoolBuilder};
@@ -50,6 +50,7 @@ pub fn save_new_metas(schema: Schema, directory: &dyn Directory) -> crate::Resul
             schema,
             opstamp: 0u64,
             payload: None,
+            doc_store_codec: None,
         },
         directory,
     )
@@ -110,6 +111,8 @@ fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    bytes_compression_threshold: Option<usize>,
+    interned_fields: Vec<Field>,
 ) -> crate::Result<SegmentEntry> {
     // first we need to apply deletes to our segment.
     let merged_segment = index.new_segment();
@@ -131,7 +134,13 @@ fn merge(
     let merger: IndexMerger = IndexMerger::open(index.schema(), &segments[..])?;
 
     // ... we just serialize this index merger in our new segment to merge the two segments.
-    let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+    let mut segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+    segment_serializer
+        .get_store_writer()
+        .set_bytes_compression_threshold(bytes_compression_threshold)?;
+    segment_serializer
+        .get_store_writer()
+        .set_interned_fields(interned_fields);
 
     let num_docs = merger.write(segment_serializer)?;
 
@@ -155,6 +164,7 @@ pub(crate) struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    settings: Arc<RwLock<IndexWriterSettings>>,
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
@@ -165,6 +175,7 @@ impl SegmentUpdater {
         index: Index,
         stamper: Stamper,
         delete_cursor: &DeleteCursor,
+        settings: Arc<RwLock<IndexWriterSettings>>,
     ) -> crate::Result<SegmentUpdater> {
         let segments = index.searchable_segment_metas()?;
         let segment_manager = SegmentManager::from_segments(segments, delete_cursor);
@@ -194,6 +205,7 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            settings,
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
@@ -209,6 +221,18 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_bytes_compression_threshold(&self) -> Option<usize> {
+        self.settings.read().unwrap().bytes_compression_threshold
+    }
+
+    pub fn get_interned_fields(&self) -> Vec<Field> {
+        self.settings.read().unwrap().interned_fields.clone()
+    }
+
+    pub fn get_date_precisions(&self) -> Vec<(Field, DatePrecision)> {
+        self.settings.read().unwrap().date_precisions.clone()
+    }
+
     fn schedule_future<T: 'static + Send, F: Future<Output = crate::Result<T>> + 'static + Send>(
         &self,
         f: F,
@@ -297,6 +321,7 @@ impl SegmentUpdater {
                 schema: index.schema(),
                 opstamp,
                 payload: commit_message,
+                doc_store_codec: Some(index.doc_store_codec().name().to_string()),
             };
             // TODO add context to the error.
             save_metas(&index_meta, directory.box_clone().borrow_mut())?;
@@ -401,6 +426,8 @@ impl SegmentUpdater {
                 &segment_updater.index,
                 segment_entries,
                 merge_operation.target_opstamp(),
+                segment_updater.get_bytes_compression_threshold(),
+                segment_updater.get_interned_fields(),
             ) {
                 Ok(after_merge_segment_entry) => {
                     let segment_meta = segment_updater
@@ -435,7 +462,10 @@ impl SegmentUpdater {
         // We therefore consider merges using these two sets of segments independently.
         let merge_policy = self.get_merge_policy();
 
-        let current_opstamp = self.stamper.stamp();
+        // The stamper may be locked by `IndexWriter::run_chunked`, which can wait
+        // for this segment updater: the merges hence do not take an opstamp of
+        // their own, and consume the deletes up to the last opstamp given instead.
+        let current_opstamp = self.stamper.last_opstamp();
         let mut merge_candidates: Vec<MergeOperation> = merge_policy
             .compute_merge_candidates(&uncommitted_segments)
             .into_iter()
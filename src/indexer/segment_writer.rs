@@ -6,6 +6,7 @@ use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::postings::compute_table_size;
 use crate::postings::MultiFieldPostingsWriter;
+use crate::schema::DatePrecision;
 use crate::schema::FieldType;
 use crate::schema::Schema;
 use crate::schema::Term;
@@ -17,6 +18,23 @@ use crate::tokenizer::{TokenStreamChain, Tokenizer};
 use crate::Opstamp;
 use crate::{DocId, SegmentComponent};
 
+/// Returns true if `value` can be indexed and written in the fast field
+/// of a numeric field of type `field_type`.
+///
+/// Booleans are accepted by u64 fields, as `0` and `1`, and f32 values
+/// by f64 fields, widened to f64.
+fn is_numeric_value_compatible(field_type: &FieldType, value: &Value) -> bool {
+    matches!(
+        (field_type, value),
+        (FieldType::U64(_), Value::U64(_))
+            | (FieldType::U64(_), Value::Bool(_))
+            | (FieldType::I64(_), Value::I64(_))
+            | (FieldType::F64(_), Value::F64(_))
+            | (FieldType::F64(_), Value::F32(_))
+            | (FieldType::Date(_), Value::Date(_))
+    )
+}
+
 /// Computes the initial size of the hash table.
 ///
 /// Returns a number of bit `b`, such that the recommended initial table size is 2^b.
@@ -94,6 +112,38 @@ impl SegmentWriter {
         })
     }
 
+    /// Sets the size above which the `Value::Bytes` of the stored documents
+    /// are individually compressed.
+    ///
+    /// See [`StoreWriter::set_bytes_compression_threshold`](../store/struct.StoreWriter.html#method.set_bytes_compression_threshold).
+    pub fn set_bytes_compression_threshold(
+        &mut self,
+        bytes_compression_threshold: Option<usize>,
+    ) -> crate::Result<()> {
+        self.segment_serializer
+            .get_store_writer()
+            .set_bytes_compression_threshold(bytes_compression_threshold)?;
+        Ok(())
+    }
+
+    /// Sets the fields whose strings are interned in the doc store.
+    ///
+    /// See [`StoreWriter::set_interned_fields`](../store/struct.StoreWriter.html#method.set_interned_fields).
+    pub fn set_interned_fields(&mut self, interned_fields: Vec<Field>) {
+        self.segment_serializer
+            .get_store_writer()
+            .set_interned_fields(interned_fields);
+    }
+
+    /// Sets the precision the stored dates of each field are truncated to.
+    ///
+    /// See [`StoreWriter::set_date_precisions`](../store/struct.StoreWriter.html#method.set_date_precisions).
+    pub fn set_date_precisions(&mut self, date_precisions: Vec<(Field, DatePrecision)>) {
+        self.segment_serializer
+            .get_store_writer()
+            .set_date_precisions(date_precisions);
+    }
+
     /// Lay on disk the current content of the `SegmentWriter`
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
@@ -123,11 +173,31 @@ impl SegmentWriter {
     ) -> crate::Result<()> {
         let doc_id = self.max_doc;
         let mut doc = add_operation.document;
+
+        // The values of the numeric fast fields are checked before any of them
+        // is written: the fast field writers expect values of the field type.
+        // `Null` values are skipped by the fast field writers.
+        for field_value in doc.field_values() {
+            let field_entry = schema.get_field_entry(field_value.field());
+            if field_entry.is_fast()
+                && !field_value.value().is_null()
+                && !is_numeric_value_compatible(field_entry.field_type(), field_value.value())
+            {
+                return Err(crate::TantivyError::SchemaError(format!(
+                    "Expected a {:?} for field {:?}",
+                    field_entry.field_type().value_type(),
+                    field_entry.name()
+                )));
+            }
+        }
         self.doc_opstamps.push(add_operation.opstamp);
 
         self.fast_field_writers.add_document(&doc);
 
-        for (field, field_values) in doc.get_sorted_field_values() {
+        for (field, mut field_values) in doc.get_sorted_field_values() {
+            // `Null` values record fields explicitly present without a value:
+            // there is nothing to index.
+            field_values.retain(|field_value| !field_value.value().is_null());
             let field_entry = schema.get_field_entry(field);
             let make_schema_error = || {
                 crate::TantivyError::SchemaError(format!(
@@ -217,10 +287,10 @@ impl SegmentWriter {
                     if int_option.is_indexed() {
                         for field_value in field_values {
                             term_buffer.set_field(field_value.field());
-                            let u64_val = field_value
-                                .value()
-                                .u64_value()
-                                .ok_or_else(make_schema_error)?;
+                            let u64_val = match *field_value.value() {
+                                Value::Bool(val) => u64::from(val),
+                                ref value => value.u64_value().ok_or_else(make_schema_error)?,
+                            };
                             term_buffer.set_u64(u64_val);
                             multifield_postings.subscribe(doc_id, &term_buffer);
                         }
@@ -256,10 +326,10 @@ impl SegmentWriter {
                     if int_option.is_indexed() {
                         for field_value in field_values {
                             term_buffer.set_field(field_value.field());
-                            let f64_val = field_value
-                                .value()
-                                .f64_value()
-                                .ok_or_else(make_schema_error)?;
+                            let f64_val = match *field_value.value() {
+                                Value::F32(val) => f64::from(val),
+                                ref value => value.f64_value().ok_or_else(make_schema_error)?,
+                            };
                             term_buffer.set_f64(f64_val);
                             multifield_postings.subscribe(doc_id, &term_buffer);
                         }
@@ -1,7 +1,7 @@
 use crate::Opstamp;
 use std::ops::Range;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
 #[cfg(not(target_arch = "arm"))]
 mod atomic_impl {
@@ -21,6 +21,10 @@ mod atomic_impl {
             self.0.fetch_add(val as u64, order) as u64
         }
 
+        pub fn load(&self, order: Ordering) -> u64 {
+            self.0.load(order)
+        }
+
         pub fn revert(&self, val: u64, order: Ordering) -> u64 {
             self.0.store(val, order);
             val
@@ -51,6 +55,10 @@ mod atomic_impl {
             previous_val
         }
 
+        pub fn load(&self, _order: Ordering) -> u64 {
+            *self.0.read().unwrap()
+        }
+
         pub fn revert(&self, val: u64, _order: Ordering) -> u64 {
             let mut lock = self.0.write().unwrap();
             *lock = val;
@@ -66,20 +74,29 @@ use self::atomic_impl::AtomicU64Wrapper;
 ///
 /// Cloning does not "fork" the stamp generation. The stamper actually wraps an `Arc`.
 #[derive(Clone, Default)]
-pub struct Stamper(Arc<AtomicU64Wrapper>);
+pub struct Stamper(Arc<AtomicU64Wrapper>, Arc<RwLock<()>>);
 
 impl Stamper {
     pub fn new(first_opstamp: Opstamp) -> Stamper {
-        Stamper(Arc::new(AtomicU64Wrapper::new(first_opstamp)))
+        Stamper(
+            Arc::new(AtomicU64Wrapper::new(first_opstamp)),
+            Arc::new(RwLock::new(())),
+        )
     }
 
     pub fn stamp(&self) -> Opstamp {
+        let _lock = self.1.read().unwrap();
         self.0.fetch_add(1u64, Ordering::SeqCst) as u64
     }
 
     /// Given a desired count `n`, `stamps` returns an iterator that
     /// will supply `n` number of u64 stamps.
     pub fn stamps(&self, n: u64) -> Range<Opstamp> {
+        let _lock = self.1.read().unwrap();
+        self.reserve(n)
+    }
+
+    fn reserve(&self, n: u64) -> Range<Opstamp> {
         let start = self.0.fetch_add(n, Ordering::SeqCst);
         Range {
             start,
@@ -87,12 +104,45 @@ impl Stamper {
         }
     }
 
+    /// Returns the last opstamp given so far, without waiting for the
+    /// stamper to be unlocked.
+    pub fn last_opstamp(&self) -> Opstamp {
+        self.0.load(Ordering::SeqCst).saturating_sub(1u64)
+    }
+
+    /// Locks the stamper: until the returned guard is dropped, the stamps
+    /// are only given through the guard, and are hence contiguous.
+    ///
+    /// Calling `stamp` or `stamps` on the stamper itself blocks until then.
+    pub fn lock(&self) -> StamperGuard<'_> {
+        StamperGuard {
+            stamper: self,
+            _lock: self.1.write().unwrap(),
+        }
+    }
+
     /// Reverts the stamper to a given `Opstamp` value and returns it
     pub fn revert(&self, to_opstamp: Opstamp) -> Opstamp {
         self.0.revert(to_opstamp, Ordering::SeqCst)
     }
 }
 
+/// Exclusive access to a `Stamper`, see [`Stamper::lock`](struct.Stamper.html#method.lock).
+pub struct StamperGuard<'a> {
+    stamper: &'a Stamper,
+    _lock: RwLockWriteGuard<'a, ()>,
+}
+
+impl<'a> StamperGuard<'a> {
+    pub fn stamp(&self) -> Opstamp {
+        self.stamper.reserve(1u64).start
+    }
+
+    pub fn stamps(&self, n: u64) -> Range<Opstamp> {
+        self.stamper.reserve(n)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -126,4 +176,17 @@ mod test {
         assert_eq!(stamper.stamp(), 6);
         assert_eq!(stamper_clone.stamp(), 7);
     }
+
+    #[test]
+    fn test_stamper_lock() {
+        let stamper = Stamper::new(7u64);
+        assert_eq!(stamper.last_opstamp(), 6u64);
+        {
+            let stamper_guard = stamper.lock();
+            assert_eq!(stamper_guard.stamps(3u64), (7..10));
+            assert_eq!(stamper_guard.stamp(), 10u64);
+            assert_eq!(stamper.last_opstamp(), 10u64);
+        }
+        assert_eq!(stamper.stamp(), 11u64);
+    }
 }
@@ -165,6 +165,7 @@ pub use crate::core::{InvertedIndexReader, SegmentReader};
 pub use crate::directory::Directory;
 pub use crate::indexer::operation::UserOperation;
 pub use crate::indexer::IndexWriter;
+pub use crate::indexer::RollbackResult;
 pub use crate::postings::Postings;
 pub use crate::reader::LeasedItem;
 pub use crate::schema::{Document, Term};
@@ -844,6 +845,110 @@ mod tests {
         assert_eq!(values[0].text(), Some("short"));
     }
 
+    #[test]
+    fn test_stored_pre_tokenized_text() -> crate::Result<()> {
+        use crate::tokenizer::{PreTokenizedString, Token};
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let pre_tokenized_text = PreTokenizedString {
+            text: String::from("Hello world"),
+            tokens: vec![
+                Token {
+                    offset_from: 0,
+                    offset_to: 5,
+                    position: 0,
+                    text: String::from("hello"),
+                    position_length: 1,
+                },
+                Token {
+                    offset_from: 6,
+                    offset_to: 11,
+                    position: 1,
+                    text: String::from("world"),
+                    position_length: 1,
+                },
+            ],
+        };
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_pre_tokenized_text(text_field, &pre_tokenized_text);
+        index_writer.add_document(doc.clone());
+        doc.set_keep_pre_tokenized_text(true);
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc = searcher.doc(DocAddress(0, 0))?;
+        assert_eq!(
+            stored_doc.get_first(text_field),
+            Some(&Value::Str(String::from("Hello world")))
+        );
+        let stored_doc = searcher.doc(DocAddress(0, 1))?;
+        assert_eq!(
+            stored_doc.get_first(text_field),
+            Some(&Value::PreTokStr(pre_tokenized_text))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_ip_addr() -> crate::Result<()> {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+        use std::str::FromStr;
+        let mut schema_builder = Schema::builder();
+        let ip_field = schema_builder.add_bytes_field("ip", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let ipv4 = IpAddr::from_str("192.168.0.1").unwrap();
+        let ipv6 = Ipv6Addr::from_str("2001:db8::1").unwrap();
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_ip_addr(ip_field, ipv4);
+        doc.add_ip_addr(ip_field, IpAddr::V6(ipv6));
+        // values built directly hold the IPv6 form of IPv4 addresses as well.
+        doc.add(FieldValue::new(
+            ip_field,
+            Value::IpAddr(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped()),
+        ));
+        index_writer.add_document(doc.clone());
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc = searcher.doc(DocAddress(0, 0))?;
+        assert_eq!(stored_doc, doc);
+        assert_eq!(
+            stored_doc
+                .get_all(ip_field)
+                .flat_map(Value::as_ip_addr)
+                .collect::<Vec<_>>(),
+            vec![
+                Ipv6Addr::from_str("::ffff:192.168.0.1").unwrap(),
+                ipv6,
+                Ipv6Addr::from_str("::ffff:10.0.0.1").unwrap()
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_array() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let color_field = schema_builder.add_bytes_field("color", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let rgb = vec![Value::U64(255u64), Value::U64(128u64), Value::U64(0u64)];
+        let mut doc = Document::default();
+        doc.add_array(color_field, rgb.clone());
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc = searcher.doc(DocAddress(0, 0))?;
+        assert_eq!(stored_doc.get_all(color_field).count(), 1);
+        assert_eq!(
+            stored_doc.get_first(color_field).and_then(Value::as_array),
+            Some(&rgb[..])
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_wrong_fast_field_type() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -218,6 +218,7 @@ pub mod tests {
                        text_field => "a b a c a d a a.",
                        text_field => "d d d d a"
                     ),
+                    delete_term: None,
                 };
                 segment_writer.add_document(op, &schema)?;
             }
@@ -225,6 +226,7 @@ pub mod tests {
                 let op = AddOperation {
                     opstamp: 1u64,
                     document: doc!(text_field => "b a"),
+                    delete_term: None,
                 };
                 segment_writer.add_document(op, &schema).unwrap();
             }
@@ -234,6 +236,7 @@ pub mod tests {
                 let op = AddOperation {
                     opstamp: 2u64,
                     document: doc!(text_field => text),
+                    delete_term: None,
                 };
                 segment_writer.add_document(op, &schema).unwrap();
             }
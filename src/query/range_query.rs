@@ -260,11 +260,19 @@ impl Query for RangeQuery {
             );
             return Err(TantivyError::SchemaError(err_msg));
         }
-        Ok(Box::new(RangeWeight {
+        Ok(Box::new(self.range_weight()))
+    }
+}
+
+impl RangeQuery {
+    /// Creates the `RangeWeight` associated to the query,
+    /// without checking the value type of the field.
+    pub(crate) fn range_weight(&self) -> RangeWeight {
+        RangeWeight {
             field: self.field,
             left_bound: self.left_bound.clone(),
             right_bound: self.right_bound.clone(),
-        }))
+        }
     }
 }
 
@@ -62,6 +62,8 @@ impl IndexReaderBuilder {
     /// to open different segment readers. It may take hundreds of milliseconds
     /// of time and it may return an error.
     pub fn try_into(self) -> crate::Result<IndexReader> {
+        self.index
+            .check_doc_store_codec(&self.index.load_metas()?)?;
         let inner_reader = InnerIndexReader {
             index: self.index,
             num_searchers: self.num_searchers,
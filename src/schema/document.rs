@@ -1,10 +1,25 @@
 use super::*;
 use crate::common::BinarySerializable;
 use crate::common::VInt;
-use crate::tokenizer::PreTokenizedString;
+use crate::common::{ChecksumReader, ChecksumWriter, CountingReader};
+use crate::tokenizer::{PreTokenizedString, Token};
 use crate::DateTime;
-use std::io::{self, Read, Write};
+use chrono::{TimeZone, Utc};
+use rayon::prelude::*;
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::iter::{self, FromIterator};
 use std::mem;
+use std::net::IpAddr;
+use std::slice;
+use std::time::Duration;
+use std::vec;
 
 /// Tantivy's Document is the object that can
 /// be indexed and then searched for.
@@ -19,33 +34,199 @@ use std::mem;
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct Document {
     field_values: Vec<FieldValue>,
+    #[serde(skip)]
+    keep_pre_tokenized_text: bool,
 }
 
 impl From<Vec<FieldValue>> for Document {
     fn from(field_values: Vec<FieldValue>) -> Self {
-        Document { field_values }
+        Document {
+            field_values,
+            keep_pre_tokenized_text: false,
+        }
+    }
+}
+
+impl FromIterator<FieldValue> for Document {
+    fn from_iter<I: IntoIterator<Item = FieldValue>>(field_values: I) -> Self {
+        Document::from(field_values.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl FromIterator<(Field, Value)> for Document {
+    fn from_iter<I: IntoIterator<Item = (Field, Value)>>(field_values: I) -> Self {
+        field_values
+            .into_iter()
+            .map(|(field, value)| FieldValue::new(field, value))
+            .collect()
+    }
+}
+
+impl Extend<FieldValue> for Document {
+    fn extend<I: IntoIterator<Item = FieldValue>>(&mut self, field_values: I) {
+        self.field_values.extend(field_values);
+    }
+}
+
+impl IntoIterator for Document {
+    type Item = (Field, Value);
+    type IntoIter = iter::Map<vec::IntoIter<FieldValue>, fn(FieldValue) -> (Field, Value)>;
+
+    /// Consumes the document, and iterates over its `(field, value)` pairs.
+    fn into_iter(self) -> Self::IntoIter {
+        self.field_values
+            .into_iter()
+            .map(|field_value| (field_value.field(), field_value.into_value()))
+    }
+}
+
+impl<'a> IntoIterator for &'a Document {
+    type Item = (Field, &'a Value);
+    type IntoIter =
+        iter::Map<slice::Iter<'a, FieldValue>, fn(&'a FieldValue) -> (Field, &'a Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.field_values
+            .iter()
+            .map(|field_value| (field_value.field(), field_value.value()))
     }
 }
 
 impl PartialEq for Document {
     fn eq(&self, other: &Document) -> bool {
-        // super slow, but only here for tests
-        let mut self_field_values: Vec<&_> = self.field_values.iter().collect();
-        let mut other_field_values: Vec<&_> = other.field_values.iter().collect();
-        self_field_values.sort();
-        other_field_values.sort();
-        self_field_values.eq(&other_field_values)
+        // Equality does not depend on the order of the field values.
+        if self.field_values.len() != other.field_values.len() {
+            return false;
+        }
+        // Documents being compared very often have their field values in the
+        // same order: we skip their common prefix without allocating anything,
+        // and only sort what remains.
+        let num_common = self
+            .field_values
+            .iter()
+            .zip(other.field_values.iter())
+            .take_while(|(left, right)| left == right)
+            .count();
+        if num_common == self.field_values.len() {
+            return true;
+        }
+        let mut self_field_values: Vec<&_> = self.field_values[num_common..].iter().collect();
+        let mut other_field_values: Vec<&_> = other.field_values[num_common..].iter().collect();
+        self_field_values.sort_unstable();
+        other_field_values.sort_unstable();
+        self_field_values == other_field_values
     }
 }
 
 impl Eq for Document {}
 
+impl Hash for Document {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Just like equality, hashing does not depend on the order
+        // of the field values: we sum the hash of each of them.
+        let mut field_values_hash = 0u64;
+        for field_value in &self.field_values {
+            let mut hasher = DefaultHasher::new();
+            field_value.hash(&mut hasher);
+            field_values_hash = field_values_hash.wrapping_add(hasher.finish());
+        }
+        self.field_values.len().hash(state);
+        field_values_hash.hash(state);
+    }
+}
+
 impl Document {
     /// Creates a new, empty document object
     pub fn new() -> Document {
         Document::default()
     }
 
+    /// Creates a new, empty document object, with room for
+    /// `capacity` field values.
+    ///
+    /// ```
+    /// use tantivy::schema::{Document, Schema, TEXT};
+    ///
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let body = schema_builder.add_text_field("body", TEXT);
+    ///
+    /// let mut doc = Document::with_capacity(2);
+    /// doc.add_text(title, "The Old Man and the Sea");
+    /// doc.add_text(body, "He was an old man who fished alone in a skiff in the Gulf Stream.");
+    /// assert_eq!(doc.len(), 2);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Document {
+        Document::from(Vec::with_capacity(capacity))
+    }
+
+    /// Reserves room for at least `additional` more field values.
+    pub fn reserve(&mut self, additional: usize) {
+        self.field_values.reserve(additional);
+    }
+
+    /// Removes all of the field values, keeping the allocated capacity.
+    ///
+    /// This makes it possible to reuse a single document allocation
+    /// to build several documents.
+    pub fn clear(&mut self) {
+        self.field_values.clear();
+    }
+
+    /// Builds a document from a json-object, resolving field names against
+    /// the schema.
+    ///
+    /// Values are converted to the type declared in the schema.
+    /// Arrays are expanded into several values for the same field.
+    ///
+    /// An error is returned if the json value is not an object, if one of its keys
+    /// is not a field of the schema, or if one of its values cannot be converted
+    /// to the type of its field. (e.g. A nested object for a `u64` field.)
+    pub fn from_json_value(
+        schema: &Schema,
+        json_value: &JsonValue,
+    ) -> Result<Document, DocParsingError> {
+        if let JsonValue::Object(json_obj) = json_value {
+            schema.json_object_to_doc(json_obj)
+        } else {
+            // Like in `Schema::parse_document`, only a sample of the
+            // json value is kept in the error.
+            let json_text = json_value.to_string();
+            let json_sample: String = if json_text.len() < 20 {
+                json_text
+            } else {
+                format!("{:?}...", json_text.chars().take(20).collect::<String>())
+            };
+            Err(DocParsingError::NotJSON(format!(
+                "Expected a json object, got {}",
+                json_sample
+            )))
+        }
+    }
+
+    /// Converts the document into a json object keyed by the schema field names.
+    ///
+    /// Fields with a single value are emitted as a scalar, fields with several
+    /// values as an array. Dates are emitted as RFC3339 strings.
+    ///
+    /// This is the inverse of [`from_json_value`](#method.from_json_value).
+    pub fn to_named_json(&self, schema: &Schema) -> JsonValue {
+        let mut json_obj = serde_json::Map::new();
+        for (field, field_values) in self.get_sorted_field_values() {
+            let mut json_values: Vec<JsonValue> = field_values
+                .into_iter()
+                .map(|field_value| value_to_json(field_value.value()))
+                .collect();
+            let json_value = if json_values.len() == 1 {
+                json_values.pop().unwrap()
+            } else {
+                JsonValue::Array(json_values)
+            };
+            json_obj.insert(schema.get_field_name(field).to_string(), json_value);
+        }
+        JsonValue::Object(json_obj)
+    }
+
     /// Returns the number of `(field, value)` pairs.
     pub fn len(&self) -> usize {
         self.field_values.len()
@@ -56,26 +237,324 @@ impl Document {
         self.field_values.is_empty()
     }
 
+    /// Returns an estimate of the memory used by the document, in bytes.
+    ///
+    /// Each field value counts for the size of its field, plus the length
+    /// of its text, bytes or facet path, or the fixed size of its numeric value.
+    /// The estimate does not account for the unused capacity of the
+    /// underlying `Vec` and `String` buffers.
+    pub fn byte_size(&self) -> usize {
+        self.field_values
+            .iter()
+            .map(|field_value| mem::size_of::<Field>() + field_value.value().byte_size())
+            .sum()
+    }
+
+    /// Returns the total length, in bytes, of the `Bytes` values of the document.
+    ///
+    /// If `include_text` is true, the length of the texts, in bytes, is
+    /// counted as well. Values nested in arrays and json objects are counted.
+    /// Raw field values are not.
+    pub fn total_bytes_payload(&self, include_text: bool) -> usize {
+        self.field_values
+            .iter()
+            .map(|field_value| bytes_payload(field_value.value(), include_text))
+            .sum()
+    }
+
     /// Retain only the field that are matching the
     /// predicate given in argument.
     pub fn filter_fields<P: Fn(Field) -> bool>(&mut self, predicate: P) {
+        self.retain_values(|field, _| predicate(field));
+    }
+
+    /// Retain only the field values that are matching the
+    /// predicate given in argument.
+    ///
+    /// Unlike `filter_fields`, the predicate receives the value
+    /// as well as its field, so that some of the values of a field
+    /// can be removed while the others are kept.
+    pub fn retain_values<P: Fn(Field, &Value) -> bool>(&mut self, predicate: P) {
         self.field_values
-            .retain(|field_value| predicate(field_value.field()));
+            .retain(|field_value| predicate(field_value.field(), field_value.value()));
+    }
+
+    /// Removes the facets of `field` that are neither `prefix`
+    /// nor one of its descendants.
+    ///
+    /// For instance, with the prefix `/category`, the facet `/category/book`
+    /// is kept while `/tag/book` is removed. The values of `field` that are not
+    /// facets, as well as the values of the other fields, are left untouched.
+    pub fn retain_facets_with_prefix(&mut self, field: Field, prefix: &Facet) {
+        if prefix.is_root() {
+            return;
+        }
+        self.retain_values(|value_field, value| match value {
+            Value::Facet(facet) if value_field == field => {
+                facet == prefix || prefix.is_prefix_of(facet)
+            }
+            _ => true,
+        });
+    }
+
+    /// Removes the text values that are empty strings.
+    ///
+    /// Texts made only of whitespace are kept, see
+    /// [`remove_blank_strings`](#method.remove_blank_strings).
+    pub fn remove_empty_strings(&mut self) {
+        self.retain_values(|_, value| value.as_str() != Some(""));
+    }
+
+    /// Removes the text values that are empty or made only of whitespace.
+    pub fn remove_blank_strings(&mut self) {
+        self.retain_values(|_, value| {
+            value
+                .as_str()
+                .map(|text| !text.trim().is_empty())
+                .unwrap_or(true)
+        });
+    }
+
+    /// Applies the Unicode default case folding to the texts of the given fields.
+    ///
+    /// Contrary to lowercasing, case folding maps `ß` to `ss`, and the final
+    /// sigma `ς` to `σ`. The folding is not locale-specific: the Turkish
+    /// dotless `ı` is kept as is, and `I` is folded to `i`.
+    /// Values of other types, including pre-tokenized texts, are left intact.
+    pub fn fold_case(&mut self, fields: &[Field]) {
+        for field_value in &mut self.field_values {
+            if !fields.contains(&field_value.field()) {
+                continue;
+            }
+            if let Value::Str(text) = field_value.value_mut() {
+                *text = fold_case(text);
+            }
+        }
+    }
+
+    /// Removes all of the values associated with the given field.
+    ///
+    /// Returns the number of `FieldValue`s removed.
+    pub fn remove_field(&mut self, field: Field) -> usize {
+        let num_field_values = self.field_values.len();
+        self.filter_fields(|candidate| candidate != field);
+        num_field_values - self.field_values.len()
+    }
+
+    /// Removes all of the values associated with the given field
+    /// and returns them, in the order they were added.
+    pub fn take_field(&mut self, field: Field) -> Vec<Value> {
+        let mut values = Vec::new();
+        for field_value in mem::take(&mut self.field_values) {
+            if field_value.field() == field {
+                values.push(field_value.into_value());
+            } else {
+                self.field_values.push(field_value);
+            }
+        }
+        values
+    }
+
+    /// Appends all of the field values of `other` to this document.
+    pub fn merge_from(&mut self, other: Document) {
+        self.field_values.extend(other.field_values);
+    }
+
+    /// Appends a copy of all of the field values of `template` to this document.
+    ///
+    /// This is the borrowing counterpart of [`merge_from`](#method.merge_from),
+    /// for the documents built from a shared template.
+    pub fn extend_from(&mut self, template: &Document) {
+        self.field_values.extend_from_slice(&template.field_values);
+    }
+
+    /// Computes the field values to add to, and remove from, this document
+    /// to obtain `other`.
+    ///
+    /// Field values are compared as multisets, regardless of their order:
+    /// if a field goes from two identical values to three, one of them is
+    /// reported as added. Raw field values are not taken into account.
+    pub fn diff(&self, other: &Document) -> DocumentDiff {
+        let mut self_field_values: Vec<&FieldValue> = self.field_values.iter().collect();
+        let mut other_field_values: Vec<&FieldValue> = other.field_values.iter().collect();
+        self_field_values.sort_unstable();
+        other_field_values.sort_unstable();
+        let mut diff = DocumentDiff::default();
+        let mut self_it = self_field_values.into_iter().peekable();
+        let mut other_it = other_field_values.into_iter().peekable();
+        loop {
+            match (self_it.peek(), other_it.peek()) {
+                (Some(self_field_value), Some(other_field_value)) => {
+                    match self_field_value.cmp(other_field_value) {
+                        Ordering::Less => diff.removed.extend(self_it.next().cloned()),
+                        Ordering::Greater => diff.added.extend(other_it.next().cloned()),
+                        Ordering::Equal => {
+                            self_it.next();
+                            other_it.next();
+                        }
+                    }
+                }
+                (Some(_), None) => diff.removed.extend(self_it.next().cloned()),
+                (None, Some(_)) => diff.added.extend(other_it.next().cloned()),
+                (None, None) => break,
+            }
+        }
+        diff
+    }
+
+    /// Returns true if both documents are equal once the values of the
+    /// `ignore` fields are left out, e.g. bookkeeping fields such as a version
+    /// or an indexing date.
+    ///
+    /// Just like `eq`, the comparison does not depend on the order of
+    /// the field values.
+    pub fn eq_ignoring(&self, other: &Document, ignore: &[Field]) -> bool {
+        fn retained_field_values<'a>(doc: &'a Document, ignore: &[Field]) -> Vec<&'a FieldValue> {
+            let mut field_values: Vec<&FieldValue> = doc
+                .field_values
+                .iter()
+                .filter(|field_value| !ignore.contains(&field_value.field()))
+                .collect();
+            field_values.sort_unstable();
+            field_values
+        }
+        retained_field_values(self, ignore) == retained_field_values(other, ignore)
+    }
+
+    /// Removes the values of the given `fields` from this document,
+    /// then appends all of the field values of `other`.
+    ///
+    /// This makes it possible to implement last-writer-wins semantics
+    /// for the listed fields.
+    pub fn merge_from_replacing(&mut self, other: Document, fields: &[Field]) {
+        self.filter_fields(|field| !fields.contains(&field));
+        self.merge_from(other);
+    }
+
+    /// Applies a partial update to the document.
+    ///
+    /// The values of each field of the `patch` replace all of the values
+    /// this field had in the document. The fields that do not appear
+    /// in the `patch` are left intact.
+    ///
+    /// If a field appears several times in the `patch`, all of its
+    /// values are kept.
+    pub fn apply_patch(&mut self, patch: &[(Field, Value)]) {
+        self.filter_fields(|field| !patch.iter().any(|(patched, _)| *patched == field));
+        self.apply_patch_append(patch);
+    }
+
+    /// Adds the values of the `defaults` for the fields that have no value
+    /// in the document.
+    ///
+    /// A field holding an explicit `Value::Null` has a value, and is
+    /// therefore not backfilled. If a field appears several times in
+    /// the `defaults`, all of its values are added.
+    pub fn apply_defaults(&mut self, defaults: &[(Field, Value)]) {
+        let missing_fields: HashSet<Field> = defaults
+            .iter()
+            .map(|(field, _)| *field)
+            .filter(|field| self.get_first(*field).is_none())
+            .collect();
+        self.field_values.extend(
+            defaults
+                .iter()
+                .filter(|(field, _)| missing_fields.contains(field))
+                .map(|(field, value)| FieldValue::new(*field, value.clone())),
+        );
+    }
+
+    /// Appends the values of the `patch` to the document, without removing
+    /// any of the existing values.
+    pub fn apply_patch_append(&mut self, patch: &[(Field, Value)]) {
+        self.field_values.extend(
+            patch
+                .iter()
+                .map(|(field, value)| FieldValue::new(*field, value.clone())),
+        );
+    }
+
+    /// Removes the field values that are exact duplicates of a previous
+    /// field value, keeping the first occurrence of each.
+    ///
+    /// The order of the remaining field values is preserved.
+    pub fn deduplicate_values(&mut self) {
+        let keep: Vec<bool> = {
+            let mut seen = HashSet::with_capacity(self.field_values.len());
+            self.field_values
+                .iter()
+                .map(|field_value| seen.insert(field_value))
+                .collect()
+        };
+        let mut keep_it = keep.into_iter();
+        self.field_values.retain(|_| keep_it.next().unwrap_or(true));
     }
 
     /// Adding a facet to the document.
-    pub fn add_facet<F>(&mut self, field: Field, path: F)
+    pub fn add_facet<F>(&mut self, field: Field, path: F) -> &mut Self
     where
         Facet: From<F>,
     {
         let facet = Facet::from(path);
         let value = Value::Facet(facet);
-        self.add(FieldValue::new(field, value));
+        self.add(FieldValue::new(field, value))
+    }
+
+    /// Adds a facet built from the segments of its path.
+    ///
+    /// Segments are taken verbatim: unlike when parsing a facet from
+    /// its text representation, a `/` or a `\` within a segment
+    /// does not need to be escaped.
+    /// For instance, the segments `["category", "rock/pop"]` form the facet
+    /// displayed as `/category/rock\/pop`.
+    pub fn add_facet_segments(&mut self, field: Field, segments: &[&str]) -> &mut Self {
+        self.add_facet(field, Facet::from_path(segments))
+    }
+
+    /// Add a facet field, along with each of its ancestors.
+    ///
+    /// For instance, adding `/a/b/c` adds the facets `/a`, `/a/b` and `/a/b/c`,
+    /// in this order. The root facet `/` is only added if it is the facet itself.
+    /// Facets that the field already holds are not added again.
+    pub fn add_facet_with_ancestors<F>(&mut self, field: Field, path: F) -> &mut Self
+    where
+        Facet: From<F>,
+    {
+        let facet = Facet::from(path);
+        let encoded_str = facet.encoded_str();
+        let ancestor_lens = encoded_str
+            .bytes()
+            .enumerate()
+            .filter(|&(_, byte)| byte == FACET_SEP_BYTE)
+            .map(|(pos, _)| pos)
+            .chain(iter::once(encoded_str.len()));
+        for ancestor_len in ancestor_lens {
+            let ancestor = Facet::from_encoded_string(encoded_str[..ancestor_len].to_string());
+            let is_present = self
+                .get_all(field)
+                .any(|value| value.as_facet() == Some(&ancestor));
+            if !is_present {
+                self.add(FieldValue::new(field, Value::Facet(ancestor)));
+            }
+        }
+        self
     }
 
     /// Add a text field.
-    pub fn add_text<S: ToString>(&mut self, field: Field, text: S) {
-        self.add(FieldValue::new(field, Value::Str(text.to_string())));
+    ///
+    /// The text is always copied. See [`add_string`](#method.add_string)
+    /// to move an owned text into the document instead.
+    pub fn add_text<S: ToString>(&mut self, field: Field, text: S) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Str(text.to_string())))
+    }
+
+    /// Add a text field, taking ownership of the text.
+    ///
+    /// Contrary to `add_text`, a `String` or a `Cow::Owned` text is
+    /// moved into the document as is, without any allocation.
+    /// Borrowed texts are copied.
+    pub fn add_string<S: Into<String>>(&mut self, field: Field, text: S) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Str(text.into())))
     }
 
     /// Add a pre-tokenized text field.
@@ -83,39 +562,270 @@ impl Document {
         &mut self,
         field: Field,
         pre_tokenized_text: &PreTokenizedString,
-    ) {
+    ) -> &mut Self {
         let value = Value::PreTokStr(pre_tokenized_text.clone());
-        self.add(FieldValue::new(field, value));
+        self.add(FieldValue::new(field, value))
+    }
+
+    /// Appends a chunk of pre-tokenized text to the last pre-tokenized
+    /// text of the given field.
+    ///
+    /// `text` is appended as is to the existing text, and the offsets and
+    /// positions of `tokens` are rebased to follow those of the existing text:
+    /// the first token of the chunk is placed right after the last existing token.
+    ///
+    /// If the field has no pre-tokenized text yet, this behaves like
+    /// [`add_pre_tokenized_text`](#method.add_pre_tokenized_text).
+    pub fn append_pre_tokenized(
+        &mut self,
+        field: Field,
+        text: &str,
+        tokens: &[Token],
+    ) -> &mut Self {
+        let existing_opt = self
+            .field_values
+            .iter_mut()
+            .rev()
+            .filter(|field_value| field_value.field() == field)
+            .find_map(|field_value| match field_value.value_mut() {
+                Value::PreTokStr(pre_tokenized_text) => Some(pre_tokenized_text),
+                _ => None,
+            });
+        let pre_tokenized_text = if let Some(pre_tokenized_text) = existing_opt {
+            pre_tokenized_text
+        } else {
+            return self.add_pre_tokenized_text(
+                field,
+                &PreTokenizedString {
+                    text: text.to_string(),
+                    tokens: tokens.to_vec(),
+                },
+            );
+        };
+        let offset = pre_tokenized_text.text.len();
+        let position_offset = pre_tokenized_text
+            .tokens
+            .last()
+            .map(|token| token.position + 1)
+            .unwrap_or(0);
+        pre_tokenized_text.text.push_str(text);
+        pre_tokenized_text
+            .tokens
+            .extend(tokens.iter().map(|token| Token {
+                offset_from: token.offset_from + offset,
+                offset_to: token.offset_to + offset,
+                position: token.position + position_offset,
+                text: token.text.clone(),
+                position_length: token.position_length,
+            }));
+        self
     }
 
     /// Add a u64 field
-    pub fn add_u64(&mut self, field: Field, value: u64) {
-        self.add(FieldValue::new(field, Value::U64(value)));
+    pub fn add_u64(&mut self, field: Field, value: u64) -> &mut Self {
+        self.add(FieldValue::new(field, Value::U64(value)))
+    }
+
+    /// Add a u128 field
+    pub fn add_u128(&mut self, field: Field, value: u128) -> &mut Self {
+        self.add(FieldValue::new(field, Value::U128(value)))
+    }
+
+    /// Add an IP address field
+    ///
+    /// IPv4 addresses are stored in their IPv4-mapped IPv6 form.
+    pub fn add_ip_addr(&mut self, field: Field, ip_addr: IpAddr) -> &mut Self {
+        self.add(FieldValue::new(field, Value::from(ip_addr)))
+    }
+
+    /// Add an array field
+    ///
+    /// Contrary to adding several values for the same field,
+    /// the values of an array are kept together, in order.
+    pub fn add_array(&mut self, field: Field, values: Vec<Value>) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Array(values)))
+    }
+
+    /// Add a json object field
+    pub fn add_json_object(&mut self, field: Field, object: BTreeMap<String, Value>) -> &mut Self {
+        self.add(FieldValue::new(field, Value::JsonObject(object)))
     }
 
     /// Add a i64 field
-    pub fn add_i64(&mut self, field: Field, value: i64) {
-        self.add(FieldValue::new(field, Value::I64(value)));
+    pub fn add_i64(&mut self, field: Field, value: i64) -> &mut Self {
+        self.add(FieldValue::new(field, Value::I64(value)))
     }
 
     /// Add a f64 field
-    pub fn add_f64(&mut self, field: Field, value: f64) {
-        self.add(FieldValue::new(field, Value::F64(value)));
+    pub fn add_f64(&mut self, field: Field, value: f64) -> &mut Self {
+        self.add(FieldValue::new(field, Value::F64(value)))
+    }
+
+    /// Add a f32 field
+    pub fn add_f32(&mut self, field: Field, value: f32) -> &mut Self {
+        self.add(FieldValue::new(field, Value::F32(value)))
+    }
+
+    /// Add a duration field
+    pub fn add_duration(&mut self, field: Field, duration: Duration) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Duration(duration)))
+    }
+
+    /// Add a geo point field.
+    ///
+    /// Returns an error, and leaves the document unchanged, if the coordinates
+    /// are out of range. See [`Value::geo_point`](./enum.Value.html#method.geo_point).
+    pub fn add_geo_point(
+        &mut self,
+        field: Field,
+        lat: f64,
+        lng: f64,
+    ) -> Result<&mut Self, GeoPointError> {
+        let value = Value::geo_point(lat, lng)?;
+        Ok(self.add(FieldValue::new(field, value)))
     }
 
     /// Add a date field
-    pub fn add_date(&mut self, field: Field, value: &DateTime) {
-        self.add(FieldValue::new(field, Value::Date(*value)));
+    ///
+    /// The date is stored with its full precision, down to the nanosecond,
+    /// but it is indexed with the precision of the second.
+    pub fn add_date(&mut self, field: Field, value: &DateTime) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Date(*value)))
+    }
+
+    /// Add a date field, given as a number of seconds since the unix epoch.
+    ///
+    /// Timestamps before the epoch are negative.
+    pub fn add_date_from_timestamp_secs(&mut self, field: Field, secs: i64) -> &mut Self {
+        self.add_date(field, &Utc.timestamp(secs, 0))
+    }
+
+    /// Add a date field, given as a number of milliseconds since the unix epoch.
+    ///
+    /// Timestamps before the epoch are negative. For instance, `-1` is
+    /// `1969-12-31T23:59:59.999Z`.
+    pub fn add_date_from_timestamp_millis(&mut self, field: Field, millis: i64) -> &mut Self {
+        let secs = millis.div_euclid(1_000);
+        let nanos = (millis.rem_euclid(1_000) * 1_000_000) as u32;
+        self.add_date(field, &Utc.timestamp(secs, nanos))
+    }
+
+    /// Add a bool field
+    pub fn add_bool(&mut self, field: Field, value: bool) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Bool(value)))
+    }
+
+    /// Add a null value, recording that the field
+    /// is explicitly present but has no value.
+    pub fn add_null(&mut self, field: Field) -> &mut Self {
+        self.add(FieldValue::new(field, Value::Null))
     }
 
     /// Add a bytes field
-    pub fn add_bytes<T: Into<Vec<u8>>>(&mut self, field: Field, value: T) {
+    pub fn add_bytes<T: Into<Vec<u8>>>(&mut self, field: Field, value: T) -> &mut Self {
         self.add(FieldValue::new(field, Value::Bytes(value.into())))
     }
 
     /// Add a field value
-    pub fn add(&mut self, field_value: FieldValue) {
+    ///
+    /// Like all of the `add_*` methods, it returns the document,
+    /// so that calls can be chained:
+    /// `doc.add_text(title, "The Old Man and the Sea").add_u64(year, 1952);`
+    pub fn add(&mut self, field_value: FieldValue) -> &mut Self {
         self.field_values.push(field_value);
+        self
+    }
+
+    /// Add a value with an index-time boost.
+    ///
+    /// The boost of values added with the other methods is `1.0`.
+    /// Only boosts different from `1.0` take space in the doc store.
+    pub fn add_with_boost<V: Into<Value>>(
+        &mut self,
+        field: Field,
+        value: V,
+        boost: f32,
+    ) -> &mut Self {
+        self.add(FieldValue::with_boost(field, value.into(), boost))
+    }
+
+    /// Add several values for the same field.
+    ///
+    /// Values are appended in order, after the existing field values.
+    pub fn add_many<I: IntoIterator<Item = Value>>(
+        &mut self,
+        field: Field,
+        values: I,
+    ) -> &mut Self {
+        let values = values.into_iter();
+        self.field_values.reserve(values.size_hint().0);
+        for value in values {
+            self.field_values.push(FieldValue::new(field, value));
+        }
+        self
+    }
+
+    /// Add several u64 values for the same field.
+    ///
+    /// This is equivalent to calling `add_u64` for each value, but reserves
+    /// the room for all of the values at once, which is noticeably faster
+    /// when adding many values.
+    pub fn add_u64s<I: IntoIterator<Item = u64>>(&mut self, field: Field, values: I) -> &mut Self {
+        self.add_many(field, values.into_iter().map(Value::U64))
+    }
+
+    /// Add several texts for the same field.
+    pub fn add_texts<S: ToString, I: IntoIterator<Item = S>>(
+        &mut self,
+        field: Field,
+        texts: I,
+    ) -> &mut Self {
+        self.add_many(
+            field,
+            texts.into_iter().map(|text| Value::Str(text.to_string())),
+        )
+    }
+
+    /// Add a text field, provided the text is `Some`.
+    ///
+    /// Nothing is added if the text is `None`.
+    pub fn add_text_opt<S: ToString>(&mut self, field: Field, text: Option<S>) -> &mut Self {
+        if let Some(text) = text {
+            self.add_text(field, text);
+        }
+        self
+    }
+
+    /// Add a u64 field, provided the value is `Some`.
+    pub fn add_u64_opt(&mut self, field: Field, value: Option<u64>) -> &mut Self {
+        if let Some(value) = value {
+            self.add_u64(field, value);
+        }
+        self
+    }
+
+    /// Add a i64 field, provided the value is `Some`.
+    pub fn add_i64_opt(&mut self, field: Field, value: Option<i64>) -> &mut Self {
+        if let Some(value) = value {
+            self.add_i64(field, value);
+        }
+        self
+    }
+
+    /// Add a f64 field, provided the value is `Some`.
+    pub fn add_f64_opt(&mut self, field: Field, value: Option<f64>) -> &mut Self {
+        if let Some(value) = value {
+            self.add_f64(field, value);
+        }
+        self
+    }
+
+    /// Add a date field, provided the date is `Some`.
+    pub fn add_date_opt(&mut self, field: Field, value: Option<&DateTime>) -> &mut Self {
+        if let Some(value) = value {
+            self.add_date(field, value);
+        }
+        self
     }
 
     /// field_values accessor
@@ -123,41 +833,112 @@ impl Document {
         &self.field_values
     }
 
-    /// Sort and groups the field_values by field.
+    /// Consumes the document and returns its field values.
     ///
-    /// The result of this method is not cached and is
-    /// computed on the fly when this method is called.
-    pub fn get_sorted_field_values(&self) -> Vec<(Field, Vec<&FieldValue>)> {
-        let mut field_values: Vec<&FieldValue> = self.field_values().iter().collect();
-        field_values.sort_by_key(|field_value| field_value.field());
+    /// This is the converse of `Document::from(Vec<FieldValue>)`.
+    pub fn into_field_values(self) -> Vec<FieldValue> {
+        self.field_values
+    }
 
-        let mut grouped_field_values = vec![];
+    /// Iterates over the `(field, value)` pairs of the document.
+    pub fn iter(&self) -> impl Iterator<Item = (Field, &Value)> {
+        self.into_iter()
+    }
 
-        let mut current_field;
-        let mut current_group;
+    /// Mutable field_values accessor.
+    ///
+    /// Values can be rewritten in place, without reallocating the document.
+    pub fn field_values_mut(&mut self) -> &mut [FieldValue] {
+        &mut self.field_values
+    }
 
-        let mut field_values_it = field_values.into_iter();
-        if let Some(field_value) = field_values_it.next() {
-            current_field = field_value.field();
-            current_group = vec![field_value]
-        } else {
-            return grouped_field_values;
+    /// Truncates the dates of the given field to `precision`, in place.
+    ///
+    /// Dates nested in arrays or json objects are left as is.
+    /// See [`DatePrecision::truncate`](./enum.DatePrecision.html#method.truncate).
+    pub fn truncate_dates(&mut self, field: Field, precision: DatePrecision) {
+        for field_value in &mut self.field_values {
+            if field_value.field() != field {
+                continue;
+            }
+            if let Value::Date(date) = field_value.value_mut() {
+                *date = precision.truncate(date);
+            }
         }
+    }
 
-        for field_value in field_values_it {
-            if field_value.field() == current_field {
-                current_group.push(field_value);
-            } else {
-                grouped_field_values.push((
-                    current_field,
-                    mem::replace(&mut current_group, vec![field_value]),
-                ));
-                current_field = field_value.field();
+    /// Applies `f` to every value of the document, in place.
+    pub fn map_values<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        for field_value in &mut self.field_values {
+            f(field_value.value_mut());
+        }
+    }
+
+    /// Applies `f` to every facet of the given field, in place.
+    ///
+    /// Values of the field that are not facets are left untouched.
+    pub fn rewrite_facets<F: FnMut(&mut Facet)>(&mut self, field: Field, mut f: F) {
+        for field_value in &mut self.field_values {
+            if field_value.field() != field {
+                continue;
+            }
+            if let Value::Facet(facet) = field_value.value_mut() {
+                f(facet);
             }
         }
+    }
+
+    /// Sorts the field_values by field, in place.
+    ///
+    /// The sort is stable: the values of a given field keep their relative order.
+    /// Once sorted, `get_sorted_field_values` does not need to sort
+    /// the field values anymore.
+    pub fn sort_field_values(&mut self) {
+        self.field_values.sort_by_key(FieldValue::field);
+    }
 
-        grouped_field_values.push((current_field, current_group));
-        grouped_field_values
+    /// Sorts the field_values in the order in which their fields
+    /// are declared in the schema, in place.
+    ///
+    /// The sort is stable: the values of a given field keep their relative order.
+    /// The values of the fields that do not belong to the schema are moved last.
+    ///
+    /// As the fields of a schema are numbered in the order of their declaration,
+    /// the document is then also sorted as with
+    /// [`sort_field_values`](#method.sort_field_values).
+    pub fn sort_fields_by_schema(&mut self, schema: &Schema) {
+        let positions: HashMap<Field, usize> = schema
+            .fields()
+            .enumerate()
+            .map(|(position, (field, _))| (field, position))
+            .collect();
+        self.field_values.sort_by_key(|field_value| {
+            positions
+                .get(&field_value.field())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Sort and groups the field_values by field.
+    ///
+    /// The result of this method is not cached and is
+    /// computed on the fly when this method is called.
+    ///
+    /// If the field values are already sorted (see
+    /// [`sort_field_values`](#method.sort_field_values)), they are
+    /// grouped directly, without being sorted again.
+    pub fn get_sorted_field_values(&self) -> Vec<(Field, Vec<&FieldValue>)> {
+        let is_sorted = self
+            .field_values
+            .windows(2)
+            .all(|pair| pair[0].field() <= pair[1].field());
+        if is_sorted {
+            return group_sorted_field_values(self.field_values.iter());
+        }
+        let mut field_values: Vec<&FieldValue> = self.field_values().iter().collect();
+        field_values.sort_by_key(|field_value| field_value.field());
+        group_sorted_field_values(field_values.into_iter())
     }
 
     /// Returns all of the `FieldValue`s associated the given field
@@ -173,54 +954,627 @@ impl Document {
         self.get_all(field).next()
     }
 
-    /// Prepares Document for being stored in the document store
+    /// Returns the first value of the given field, provided it is of the `Str` type.
     ///
-    /// Method transforms PreTokenizedString values into String
-    /// values.
-    pub fn prepare_for_store(&mut self) {
-        for field_value in &mut self.field_values {
-            if let Value::PreTokStr(pre_tokenized_text) = field_value.value() {
-                *field_value = FieldValue::new(
-                    field_value.field(),
-                    Value::Str(pre_tokenized_text.text.clone()), //< TODO somehow remove .clone()
-                );
+    /// Returns None if the field has no value, or if its first value
+    /// is not of the `Str` type, even if one of its other values is.
+    pub fn get_first_str(&self, field: Field) -> Option<&str> {
+        self.get_first(field).and_then(Value::as_str)
+    }
+
+    /// Returns the first value of the given field, provided it is of the `U64` type.
+    ///
+    /// See [`get_first_str`](#method.get_first_str).
+    pub fn get_first_u64(&self, field: Field) -> Option<u64> {
+        self.get_first(field).and_then(Value::as_u64)
+    }
+
+    /// Returns the first value of the given field, provided it is of the `I64` type.
+    ///
+    /// See [`get_first_str`](#method.get_first_str).
+    pub fn get_first_i64(&self, field: Field) -> Option<i64> {
+        self.get_first(field).and_then(Value::as_i64)
+    }
+
+    /// Returns the first value of the given field, provided it is of the `F64` type.
+    ///
+    /// See [`get_first_str`](#method.get_first_str).
+    pub fn get_first_f64(&self, field: Field) -> Option<f64> {
+        self.get_first(field).and_then(Value::as_f64)
+    }
+
+    /// Returns the first value of the given field, provided it is of the `Date` type.
+    ///
+    /// See [`get_first_str`](#method.get_first_str).
+    pub fn get_first_date(&self, field: Field) -> Option<&DateTime> {
+        self.get_first(field).and_then(Value::as_date)
+    }
+
+    /// Returns the first value of the given field, provided it is of the `Bytes` type.
+    ///
+    /// See [`get_first_str`](#method.get_first_str).
+    pub fn get_first_bytes(&self, field: Field) -> Option<&[u8]> {
+        self.get_first(field).and_then(Value::as_bytes)
+    }
+
+    /// Returns the number of values associated the given field
+    pub fn num_values(&self, field: Field) -> usize {
+        self.get_all(field).count()
+    }
+
+    /// Returns true iff the document has at least one value for the given field
+    pub fn contains_field(&self, field: Field) -> bool {
+        self.get_first(field).is_some()
+    }
+
+    /// Returns the number of values associated to each field of the document.
+    ///
+    /// Fields without any value do not appear in the map.
+    pub fn field_value_count_by_field(&self) -> HashMap<Field, usize> {
+        let mut counts = HashMap::new();
+        for field_value in &self.field_values {
+            *counts.entry(field_value.field()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Sets whether pre-tokenized texts should be stored with their tokens.
+    ///
+    /// By default, only the text of a `PreTokenizedString` is stored, and
+    /// it is read back from the store as a `Value::Str`. When this option is set,
+    /// the `PreTokenizedString` is stored intact and read back as a `Value::PreTokStr`.
+    ///
+    /// This option is not serialized with the document.
+    pub fn set_keep_pre_tokenized_text(&mut self, keep_pre_tokenized_text: bool) {
+        self.keep_pre_tokenized_text = keep_pre_tokenized_text;
+    }
+
+    /// Checks that the document can be indexed with the given schema.
+    ///
+    /// Every value must target a field of the schema, and be of the type
+    /// declared for this field. `Null` values are only accepted for fields
+    /// that are not indexed.
+    ///
+    /// Fields that are declared as single-valued fast fields must not
+    /// have more than one value.
+    pub fn validate(&self, schema: &Schema) -> Result<(), ValidationError> {
+        let num_fields = schema.fields().count();
+        for (field, field_values) in self.get_sorted_field_values() {
+            if field.field_id() as usize >= num_fields {
+                return Err(ValidationError::NoSuchFieldInSchema(field));
             }
+            let field_entry = schema.get_field_entry(field);
+            let field_type = field_entry.field_type();
+            for field_value in &field_values {
+                let value = field_value.value();
+                let is_valid = match (field_type, value) {
+                    (_, Value::Null) => !field_type.is_indexed(),
+                    (FieldType::Str(_), Value::Str(_))
+                    | (FieldType::Str(_), Value::PreTokStr(_))
+                    | (FieldType::U64(_), Value::U64(_))
+                    | (FieldType::I64(_), Value::I64(_))
+                    | (FieldType::F64(_), Value::F64(_))
+                    | (FieldType::Date(_), Value::Date(_))
+                    | (FieldType::HierarchicalFacet, Value::Facet(_))
+                    | (FieldType::Bytes(_), Value::Bytes(_)) => true,
+                    _ => false,
+                };
+                if !is_valid {
+                    return Err(ValidationError::TypeError {
+                        field_name: field_entry.name().to_string(),
+                        expected: field_type.value_type(),
+                        actual: value.type_name(),
+                    });
+                }
+            }
+            let is_single_valued = match field_type {
+                FieldType::U64(options)
+                | FieldType::I64(options)
+                | FieldType::F64(options)
+                | FieldType::Date(options) => {
+                    options.get_fastfield_cardinality() == Some(Cardinality::SingleValue)
+                }
+                FieldType::Bytes(options) => options.is_fast(),
+                _ => false,
+            };
+            if is_single_valued && field_values.len() > 1 {
+                return Err(ValidationError::TooManyValues {
+                    field_name: field_entry.name().to_string(),
+                    num_values: field_values.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the numeric values of the document to the numeric type
+    /// declared for their field in the schema, when this is lossless.
+    ///
+    /// - `i64` values are converted to `u64` if they are not negative.
+    /// - `u64` values are converted to `i64` if they are not greater than `i64::MAX`.
+    /// - `u64` and `i64` values are converted to `f64` if they can be represented
+    ///   exactly as a `f64`.
+    ///
+    /// `f64` values are never converted to integers. Other values, or values
+    /// targeting non-numeric fields, are left untouched: `validate` reports them.
+    ///
+    /// If one of the conversions would be lossy, an error is returned and
+    /// the document is left unchanged.
+    pub fn coerce_to_schema(&mut self, schema: &Schema) -> Result<(), ValidationError> {
+        let num_fields = schema.fields().count();
+        let mut coerced_values = Vec::new();
+        for (ord, field_value) in self.field_values.iter().enumerate() {
+            let field = field_value.field();
+            if field.field_id() as usize >= num_fields {
+                return Err(ValidationError::NoSuchFieldInSchema(field));
+            }
+            let field_entry = schema.get_field_entry(field);
+            let expected = field_entry.field_type().value_type();
+            let value = field_value.value();
+            let coerced_value = match (expected, value) {
+                (Type::U64, Value::I64(val)) => u64::try_from(*val).ok().map(Value::U64),
+                (Type::I64, Value::U64(val)) => i64::try_from(*val).ok().map(Value::I64),
+                (Type::F64, Value::U64(val)) => exact_f64_value(i128::from(*val)),
+                (Type::F64, Value::I64(val)) => exact_f64_value(i128::from(*val)),
+                _ => continue,
+            };
+            match coerced_value {
+                Some(coerced_value) => coerced_values.push((ord, coerced_value)),
+                None => {
+                    return Err(ValidationError::LossyConversion {
+                        field_name: field_entry.name().to_string(),
+                        expected,
+                        value: value.clone(),
+                    })
+                }
+            }
+        }
+        for (ord, coerced_value) in coerced_values {
+            *self.field_values[ord].value_mut() = coerced_value;
         }
+        Ok(())
+    }
+
+    /// Prepares Document for being stored in the document store
+    ///
+    /// Method transforms PreTokenizedString values into String
+    /// values, unless the document was set to keep pre-tokenized texts.
+    /// (See [`set_keep_pre_tokenized_text`](#method.set_keep_pre_tokenized_text).)
+    pub fn prepare_for_store(&mut self) {
+        if self.keep_pre_tokenized_text {
+            return;
+        }
+        self.map_values(|value| {
+            if let Value::PreTokStr(pre_tokenized_text) = value {
+                *value = Value::Str(mem::take(&mut pre_tokenized_text.text));
+            }
+        });
     }
 }
 
+fn group_sorted_field_values<'a>(
+    mut field_values_it: impl Iterator<Item = &'a FieldValue>,
+) -> Vec<(Field, Vec<&'a FieldValue>)> {
+    let mut grouped_field_values = vec![];
+
+    let mut current_field;
+    let mut current_group;
+
+    if let Some(field_value) = field_values_it.next() {
+        current_field = field_value.field();
+        current_group = vec![field_value]
+    } else {
+        return grouped_field_values;
+    }
+
+    for field_value in field_values_it {
+        if field_value.field() == current_field {
+            current_group.push(field_value);
+        } else {
+            grouped_field_values.push((
+                current_field,
+                mem::replace(&mut current_group, vec![field_value]),
+            ));
+            current_field = field_value.field();
+        }
+    }
+
+    grouped_field_values.push((current_field, current_group));
+    grouped_field_values
+}
+
+// Returns the `f64` value equal to `val`, if `val` can be represented exactly as a `f64`.
+fn exact_f64_value(val: i128) -> Option<Value> {
+    let float_val = val as f64;
+    // the conversion back to i128 is exact for any integral f64 within the range of u64 and i64.
+    if float_val as i128 == val {
+        Some(Value::F64(float_val))
+    } else {
+        None
+    }
+}
+
+/// Returns the Unicode default case folding of the text.
+///
+/// The default case folding of a char is the lowercase form of the uppercase
+/// form of its lowercase form, with two exceptions: the dotless `ı` has no
+/// folding, and the Cherokee small letters fold to the capital letters.
+fn fold_case(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            'ı' | '\u{13A0}'..='\u{13F5}' => folded.push(c),
+            '\u{13F8}'..='\u{13FD}' | '\u{AB70}'..='\u{ABBF}' => {
+                let capital = if c < '\u{AB70}' {
+                    c as u32 - 0x8
+                } else {
+                    c as u32 - 0xAB70 + 0x13A0
+                };
+                folded.push(char::from_u32(capital).unwrap_or(c));
+            }
+            _ => {
+                for lower in c.to_lowercase() {
+                    for upper in lower.to_uppercase() {
+                        folded.extend(upper.to_lowercase());
+                    }
+                }
+            }
+        }
+    }
+    folded
+}
+
+fn bytes_payload(value: &Value, include_text: bool) -> usize {
+    match value {
+        Value::Bytes(bytes) => bytes.len(),
+        Value::Str(text) if include_text => text.len(),
+        Value::PreTokStr(pre_tokenized_text) if include_text => pre_tokenized_text.text.len(),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| bytes_payload(value, include_text))
+            .sum(),
+        Value::JsonObject(object) => object
+            .values()
+            .map(|value| bytes_payload(value, include_text))
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        // json numbers cannot hold integers over u64::MAX.
+        Value::U128(val) if *val > u128::from(u64::MAX) => JsonValue::String(val.to_string()),
+        Value::JsonObject(object) => JsonValue::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), value_to_json(value)))
+                .collect(),
+        ),
+        Value::Array(values) => JsonValue::Array(values.iter().map(value_to_json).collect()),
+        _ => serde_json::to_value(value).expect("value encoding failed. This is a bug"),
+    }
+}
+
+/// Difference between two versions of a document, as returned by
+/// `Document::diff`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocumentDiff {
+    /// Field values present in the new version only
+    pub added: Vec<FieldValue>,
+    /// Field values present in the old version only
+    pub removed: Vec<FieldValue>,
+}
+
+impl DocumentDiff {
+    /// Returns true if both versions hold the same field values.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Error locating the field value of a document that failed to deserialize.
+///
+/// `Document::deserialize` returns it wrapped in an `io::Error` of the same
+/// kind as the underlying error. It can be retrieved with `io::Error::get_ref`,
+/// followed by `downcast_ref::<DocumentDeserializationError>()`.
+#[derive(Debug, Error)]
+#[error(
+    "Failed to deserialize the field value #{field_value_index} of the document, \
+     at byte offset {byte_offset}: {source}"
+)]
+pub struct DocumentDeserializationError {
+    /// Index of the field value that failed to deserialize
+    pub field_value_index: usize,
+    /// Offset of the field value, in bytes from the start of the document
+    pub byte_offset: u64,
+    /// Underlying error
+    pub source: io::Error,
+}
+
+/// Error returned by `Document::validate` when a document
+/// does not match the schema.
+#[derive(Debug, Error, PartialEq)]
+pub enum ValidationError {
+    /// The document contains a field that is not declared in the schema.
+    #[error("The document contains a field that is not declared in the schema: {0:?}")]
+    NoSuchFieldInSchema(Field),
+    /// One of the values is not of the type declared for its field.
+    #[error("The field '{field_name}' expects a {expected:?} value, got a {actual} value")]
+    TypeError {
+        /// Name of the field
+        field_name: String,
+        /// Type declared in the schema
+        expected: Type,
+        /// Type of the value in the document
+        actual: &'static str,
+    },
+    /// A single-valued field has more than one value.
+    #[error("The field '{field_name}' is single-valued, got {num_values} values")]
+    TooManyValues {
+        /// Name of the field
+        field_name: String,
+        /// Number of values in the document
+        num_values: usize,
+    },
+    /// A numeric value cannot be converted to the type declared for its field
+    /// without loss.
+    #[error("The value {value:?} of the field '{field_name}' cannot be converted to {expected:?} without loss")]
+    LossyConversion {
+        /// Name of the field
+        field_name: String,
+        /// Type declared in the schema
+        expected: Type,
+        /// Value that cannot be converted
+        value: Value,
+    },
+}
+
 impl BinarySerializable for Document {
     fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        let field_values = self.field_values();
-        VInt(field_values.len() as u64).serialize(writer)?;
-        for field_value in field_values {
-            field_value.serialize(writer)?;
-        }
-        Ok(())
+        self.serialize_with_bytes_compression(writer, None)
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let num_field_values = VInt::deserialize(reader)?.val() as usize;
+        let mut counting_reader = CountingReader::wrap(&mut *reader);
+        let num_field_values = VInt::deserialize(&mut counting_reader)?.val() as usize;
         let field_values = (0..num_field_values)
-            .map(|_| FieldValue::deserialize(reader))
+            .map(|field_value_index| {
+                let byte_offset = counting_reader.read_bytes();
+                FieldValue::deserialize(&mut counting_reader).map_err(|source| {
+                    let kind = source.kind();
+                    let error = DocumentDeserializationError {
+                        field_value_index,
+                        byte_offset,
+                        source,
+                    };
+                    io::Error::new(kind, error)
+                })
+            })
             .collect::<io::Result<Vec<FieldValue>>>()?;
         Ok(Document::from(field_values))
     }
 }
 
+impl Document {
+    /// Serializes the document, compressing the `Value::Bytes` longer than
+    /// `bytes_compression_threshold` bytes, if any.
+    ///
+    /// Compressed bytes are transparently decompressed by `deserialize`.
+    pub(crate) fn serialize_with_bytes_compression<W: Write>(
+        &self,
+        writer: &mut W,
+        bytes_compression_threshold: Option<usize>,
+    ) -> io::Result<()> {
+        self.serialize_with_raw_field_values(writer, bytes_compression_threshold, &[], 0)
+    }
+
+    /// Serializes the document, followed by `num_raw_field_values` field values
+    /// already serialized in `raw_field_values`.
+    ///
+    /// The raw field values are spliced as is, and the number of field values
+    /// written upfront accounts for them. See `StoreWriter::store_raw`.
+    pub(crate) fn serialize_with_raw_field_values<W: Write>(
+        &self,
+        writer: &mut W,
+        bytes_compression_threshold: Option<usize>,
+        raw_field_values: &[u8],
+        num_raw_field_values: usize,
+    ) -> io::Result<()> {
+        VInt((self.field_values.len() + num_raw_field_values) as u64).serialize(writer)?;
+        serialize_field_values(
+            self.field_values.iter(),
+            writer,
+            bytes_compression_threshold,
+        )?;
+        writer.write_all(raw_field_values)
+    }
+
+    /// Serializes the document with its field values sorted by field,
+    /// as in [`get_sorted_field_values`](#method.get_sorted_field_values).
+    ///
+    /// The values of a given field keep their relative order. Two documents
+    /// holding the same values in the same order for each field are hence
+    /// serialized to the same bytes, whatever the order of their fields.
+    ///
+    /// The document can be read back with `deserialize`.
+    pub fn serialize_sorted<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut field_values: Vec<&FieldValue> = self.field_values.iter().collect();
+        field_values.sort_by_key(|field_value| field_value.field());
+        VInt(field_values.len() as u64).serialize(writer)?;
+        serialize_field_values(field_values.into_iter(), writer, None)
+    }
+}
+
+/// Number of chunks of documents serialized per thread by
+/// `serialize_documents_parallel`, so that the load stays
+/// balanced when some documents are larger than others.
+const NUM_CHUNKS_PER_THREAD: usize = 4;
+
+/// Serializes the documents one after the other, as successive calls to
+/// `Document::serialize` would, using all of the threads of the rayon thread pool.
+///
+/// The documents are serialized in chunks, each into its own buffer,
+/// and the buffers are then written in the order of the documents:
+/// the output is exactly the same as the sequential one.
+pub fn serialize_documents_parallel<W: Write>(docs: &[Document], writer: &mut W) -> io::Result<()> {
+    if docs.is_empty() {
+        return Ok(());
+    }
+    let num_chunks = rayon::current_num_threads() * NUM_CHUNKS_PER_THREAD;
+    let chunk_size = (docs.len() + num_chunks - 1) / num_chunks;
+    let buffers = docs
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            let mut buffer = Vec::new();
+            for doc in chunk {
+                doc.serialize(&mut buffer)?;
+            }
+            Ok(buffer)
+        })
+        .collect::<io::Result<Vec<Vec<u8>>>>()?;
+    for buffer in buffers {
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+fn serialize_field_values<'a, W: Write>(
+    field_values: impl Iterator<Item = &'a FieldValue>,
+    writer: &mut W,
+    bytes_compression_threshold: Option<usize>,
+) -> io::Result<()> {
+    for field_value in field_values {
+        if let Some(bytes_compression_threshold) = bytes_compression_threshold {
+            field_value.serialize_header(writer)?;
+            field_value
+                .value()
+                .serialize_compressing_bytes(bytes_compression_threshold, writer)?;
+        } else {
+            field_value.serialize(writer)?;
+        }
+    }
+    Ok(())
+}
+
+impl Document {
+    /// Advances the reader past a serialized `Document`.
+    ///
+    /// Contrary to `Document::deserialize`, this does not
+    /// allocate anything.
+    pub fn skip_deserialize<R: Read>(reader: &mut R) -> io::Result<()> {
+        let num_field_values = VInt::deserialize(reader)?.val();
+        for _ in 0..num_field_values {
+            FieldValue::deserialize_header(reader)?;
+            Value::skip_deserialize(reader)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the document as `serialize` does, followed by
+    /// the crc32 checksum of the serialized bytes.
+    ///
+    /// Checksummed documents must be read back with `deserialize_checksummed`.
+    pub fn serialize_checksummed<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut checksum_writer = ChecksumWriter::wrap(&mut *writer);
+        self.serialize(&mut checksum_writer)?;
+        let (writer, checksum) = checksum_writer.finish();
+        checksum.serialize(writer)
+    }
+
+    /// Deserializes a document serialized with `serialize_checksummed`.
+    ///
+    /// An error is returned if the checksum is missing, or if it does not
+    /// match the bytes of the document.
+    pub fn deserialize_checksummed<R: Read>(reader: &mut R) -> io::Result<Document> {
+        let mut checksum_reader = ChecksumReader::wrap(&mut *reader);
+        let doc = Document::deserialize(&mut checksum_reader)?;
+        let (reader, computed_checksum) = checksum_reader.finish();
+        let checksum = u32::deserialize(reader)?;
+        if checksum != computed_checksum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Document checksum mismatch: expected {}, got {}.",
+                    checksum, computed_checksum
+                ),
+            ));
+        }
+        Ok(doc)
+    }
+
+    /// Serializes the document, prefixed with its length in bytes as a `VInt`.
+    ///
+    /// Framed documents can be read back with `deserialize_framed`, or skipped
+    /// without being read with `skip_framed`.
+    pub fn serialize_framed<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        VInt(buffer.len() as u64).serialize(writer)?;
+        writer.write_all(&buffer)
+    }
+
+    /// Deserializes a document serialized with `serialize_framed`.
+    ///
+    /// An error is returned if the document does not span its entire frame.
+    pub fn deserialize_framed<R: Read>(reader: &mut R) -> io::Result<Document> {
+        let num_bytes = VInt::deserialize(reader)?.val();
+        let mut frame_reader = reader.take(num_bytes);
+        let doc = Document::deserialize(&mut frame_reader)?;
+        if frame_reader.limit() > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The document does not span its entire frame.",
+            ));
+        }
+        Ok(doc)
+    }
+
+    /// Advances the reader past a document serialized with `serialize_framed`.
+    ///
+    /// Contrary to `skip_deserialize`, the document is not read at all.
+    pub fn skip_framed<R: Read + Seek>(reader: &mut R) -> io::Result<()> {
+        let num_bytes = VInt::deserialize(reader)?.val();
+        reader.seek(SeekFrom::Current(num_bytes as i64))?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
+    use crate::common::BinarySerializable;
+    use crate::common::VInt;
     use crate::schema::*;
     use crate::tokenizer::{PreTokenizedString, Token};
+    use chrono::{TimeZone, Utc};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::io;
 
     #[test]
     fn test_doc() {
         let mut schema_builder = Schema::builder();
         let text_field = schema_builder.add_text_field("title", TEXT);
+        let count_field = schema_builder.add_u64_field("count", STORED);
         let mut doc = Document::default();
         doc.add_text(text_field, "My title");
         assert_eq!(doc.field_values().len(), 1);
+        doc.add_text(text_field, "My subtitle")
+            .add_u64(count_field, 3u64)
+            .add_facet(text_field, "/a/b")
+            .add(FieldValue::new(count_field, Value::U64(4u64)));
+        assert_eq!(doc.field_values().len(), 5);
+        assert_eq!(
+            doc.get_all(count_field).collect::<Vec<_>>(),
+            vec![&Value::U64(3u64), &Value::U64(4u64)]
+        );
     }
 
     #[test]
@@ -256,4 +1610,1827 @@ mod tests {
             _ => panic!("Incorrect variant of Value"),
         }
     }
+
+    #[test]
+    fn test_prepare_for_store_keeping_tokens() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("title", TEXT);
+        let mut doc = Document::default();
+        let pre_tokenized_text = PreTokenizedString {
+            text: String::from("A b"),
+            tokens: vec![
+                Token {
+                    offset_from: 0,
+                    offset_to: 1,
+                    position: 0,
+                    text: String::from("a"),
+                    position_length: 1,
+                },
+                Token {
+                    offset_from: 2,
+                    offset_to: 3,
+                    position: 1,
+                    text: String::from("b"),
+                    position_length: 2,
+                },
+            ],
+        };
+        doc.add_pre_tokenized_text(text_field, &pre_tokenized_text);
+        doc.set_keep_pre_tokenized_text(true);
+        doc.prepare_for_store();
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_first(text_field),
+            Some(&Value::PreTokStr(pre_tokenized_text))
+        );
+    }
+
+    #[test]
+    fn test_doc_bool_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let is_active = schema_builder.add_u64_field("is_active", STORED);
+        let is_deleted = schema_builder.add_u64_field("is_deleted", STORED);
+        let mut doc = Document::default();
+        doc.add_bool(is_active, true);
+        doc.add_bool(is_deleted, false);
+        doc.prepare_for_store();
+        assert_eq!(doc.get_first(is_active), Some(&Value::Bool(true)));
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deser_doc.get_first(is_active), Some(&Value::Bool(true)));
+        assert_eq!(
+            deser_doc.get_all(is_deleted).collect::<Vec<_>>(),
+            vec![&Value::Bool(false)]
+        );
+        assert_eq!(deser_doc, doc);
+    }
+
+    #[test]
+    fn test_doc_null() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_null(body);
+        doc.add_null(title);
+        doc.prepare_for_store();
+        assert_eq!(doc.get_first(body), Some(&Value::Null));
+        let sorted_field_values = doc.get_sorted_field_values();
+        assert_eq!(sorted_field_values.len(), 2);
+        let (field, title_values) = &sorted_field_values[0];
+        assert_eq!(*field, title);
+        assert_eq!(title_values[0].value(), &Value::Str("hello".to_string()));
+        assert_eq!(title_values[1].value(), &Value::Null);
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_all(title).collect::<Vec<_>>(),
+            vec![&Value::Str("hello".to_string()), &Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_doc_remove_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let absent = schema_builder.add_text_field("absent", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_text(body, "body");
+        doc.add_text(title, "happy tax payer");
+        assert_eq!(doc.remove_field(absent), 0);
+        assert_eq!(doc.len(), 3);
+        assert_eq!(doc.remove_field(title), 2);
+        assert_eq!(doc.len(), 1);
+        assert_eq!(doc.get_first(body), Some(&Value::Str("body".to_string())));
+        assert!(doc.get_first(title).is_none());
+    }
+
+    #[test]
+    fn test_doc_take_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let absent = schema_builder.add_text_field("absent", STORED);
+        let mut doc = Document::default();
+        doc.add_bytes(payload, vec![1u8, 2u8]);
+        doc.add_text(title, "hello");
+        doc.add_bytes(payload, vec![3u8]);
+        assert!(doc.take_field(absent).is_empty());
+        assert_eq!(doc.len(), 3);
+        assert_eq!(
+            doc.take_field(payload),
+            vec![Value::Bytes(vec![1u8, 2u8]), Value::Bytes(vec![3u8])]
+        );
+        assert_eq!(doc.len(), 1);
+        assert_eq!(doc.get_first(title), Some(&Value::Str("hello".to_string())));
+    }
+
+    #[test]
+    fn test_doc_field_values_mut() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "  Hello ");
+        doc.add_u64(count, 3u64);
+        for field_value in doc.field_values_mut() {
+            if field_value.field() == count {
+                *field_value.value_mut() = Value::U64(4u64);
+            }
+        }
+        assert_eq!(doc.get_first(count), Some(&Value::U64(4u64)));
+        assert_eq!(
+            doc.get_first(title),
+            Some(&Value::Str("  Hello ".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_doc_map_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "  Hello ");
+        doc.add_u64(count, 3u64);
+        doc.add_text(title, "World");
+        doc.map_values(|value| match value {
+            Value::Str(text) => *text = text.trim().to_lowercase(),
+            Value::U64(val) => *val *= 1_000,
+            _ => {}
+        });
+        assert_eq!(doc.len(), 3);
+        assert_eq!(
+            doc.get_all(title).collect::<Vec<_>>(),
+            vec![
+                &Value::Str("hello".to_string()),
+                &Value::Str("world".to_string())
+            ]
+        );
+        assert_eq!(doc.get_first(count), Some(&Value::U64(3_000u64)));
+    }
+
+    #[test]
+    fn test_doc_json_object() {
+        use std::collections::BTreeMap;
+        let mut schema_builder = Schema::builder();
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let mut tags = BTreeMap::new();
+        tags.insert("env".to_string(), Value::from("prod"));
+        let mut object = BTreeMap::new();
+        object.insert("status".to_string(), Value::U64(200u64));
+        object.insert("tags".to_string(), Value::JsonObject(tags));
+        let mut doc = Document::default();
+        doc.add_json_object(payload, object.clone());
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_first(payload).and_then(Value::as_json_object),
+            Some(&object)
+        );
+        assert_eq!(deser_doc.get_all(payload).count(), 1);
+    }
+
+    #[test]
+    fn test_doc_validate() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let score = schema_builder.add_i64_field("score", STORED);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let tags = schema_builder.add_u64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let schema = schema_builder.build();
+
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 3u64);
+        doc.add_null(score);
+        doc.add_u64(popularity, 1u64);
+        doc.add_u64(tags, 1u64);
+        doc.add_u64(tags, 2u64);
+        assert_eq!(doc.validate(&schema), Ok(()));
+
+        let mut doc = Document::default();
+        doc.add_u64(title, 3u64);
+        assert_eq!(
+            doc.validate(&schema),
+            Err(ValidationError::TypeError {
+                field_name: "title".to_string(),
+                expected: Type::Str,
+                actual: "u64",
+            })
+        );
+
+        let mut doc = Document::default();
+        doc.add_null(count);
+        assert_eq!(
+            doc.validate(&schema),
+            Err(ValidationError::TypeError {
+                field_name: "count".to_string(),
+                expected: Type::U64,
+                actual: "null",
+            })
+        );
+
+        let mut doc = Document::default();
+        doc.add_u64(popularity, 1u64);
+        doc.add_u64(popularity, 2u64);
+        assert_eq!(
+            doc.validate(&schema),
+            Err(ValidationError::TooManyValues {
+                field_name: "popularity".to_string(),
+                num_values: 2,
+            })
+        );
+
+        let mut doc = Document::default();
+        doc.add_text(Field::from_field_id(5), "hello");
+        assert_eq!(
+            doc.validate(&schema),
+            Err(ValidationError::NoSuchFieldInSchema(Field::from_field_id(
+                5
+            )))
+        );
+    }
+
+    // The former implementation of `Document::eq`.
+    fn sorted_field_values_eq(left: &Document, right: &Document) -> bool {
+        let mut left_field_values: Vec<&FieldValue> = left.field_values().iter().collect();
+        let mut right_field_values: Vec<&FieldValue> = right.field_values().iter().collect();
+        left_field_values.sort();
+        right_field_values.sort();
+        left_field_values == right_field_values
+    }
+
+    #[test]
+    fn test_doc_eq_matches_sorted_field_values_eq() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut rng = StdRng::seed_from_u64(42u64);
+        for _ in 0..1_000 {
+            let num_values = rng.gen_range(0..6);
+            let mut field_values: Vec<FieldValue> = (0..num_values)
+                .map(|_| {
+                    if rng.gen_bool(0.5) {
+                        FieldValue::new(title, Value::from(["a", "b"][rng.gen_range(0..2)]))
+                    } else {
+                        FieldValue::new(count, Value::U64(rng.gen_range(0..2)))
+                    }
+                })
+                .collect();
+            let left: Document = field_values.iter().cloned().collect();
+            let shuffled: Document = {
+                field_values.shuffle(&mut rng);
+                field_values.iter().cloned().collect()
+            };
+            assert_eq!(left, shuffled);
+            let mut right = shuffled.clone();
+            if rng.gen_bool(0.5) && !right.is_empty() {
+                let ord = rng.gen_range(0..right.len());
+                right.field_values_mut()[ord] = FieldValue::new(count, Value::U64(2u64));
+            } else if rng.gen_bool(0.5) {
+                right.add_u64(count, rng.gen_range(0..2));
+            }
+            assert_eq!(left == right, sorted_field_values_eq(&left, &right));
+            assert_eq!(right == left, sorted_field_values_eq(&right, &left));
+        }
+    }
+
+    #[test]
+    fn test_doc_append_pre_tokenized() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let token = |offset_from: usize, offset_to: usize, position: usize, text: &str| Token {
+            offset_from,
+            offset_to,
+            position,
+            text: String::from(text),
+            position_length: 1,
+        };
+        let mut doc = Document::default();
+        doc.add_text(text, "not pre-tokenized");
+        doc.append_pre_tokenized(
+            text,
+            "hello happy",
+            &[token(0, 5, 0, "hello"), token(6, 11, 1, "happy")],
+        );
+        doc.add_text(title, "title").append_pre_tokenized(
+            text,
+            " tax payer",
+            &[token(1, 4, 0, "tax"), token(5, 10, 1, "payer")],
+        );
+        assert_eq!(doc.len(), 3);
+        let pre_tokenized_text = match doc.get_all(text).nth(1) {
+            Some(Value::PreTokStr(pre_tokenized_text)) => pre_tokenized_text,
+            _ => panic!("Expected a pre-tokenized text"),
+        };
+        assert_eq!(pre_tokenized_text.text, "hello happy tax payer");
+        assert_eq!(
+            pre_tokenized_text.tokens,
+            vec![
+                token(0, 5, 0, "hello"),
+                token(6, 11, 1, "happy"),
+                token(12, 15, 2, "tax"),
+                token(16, 21, 3, "payer"),
+            ]
+        );
+        for token in &pre_tokenized_text.tokens {
+            assert_eq!(
+                &pre_tokenized_text.text[token.offset_from..token.offset_to],
+                token.text
+            );
+        }
+    }
+
+    #[test]
+    fn test_doc_retain_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "");
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 0u64);
+        doc.add_text(body, "");
+        doc.add_text(title, "");
+        doc.add_text(title, "happy tax payer");
+        doc.retain_values(|_, value| value.as_str() != Some(""));
+        assert_eq!(
+            doc.get_all(title).collect::<Vec<_>>(),
+            vec![&Value::from("hello"), &Value::from("happy tax payer")]
+        );
+        assert!(!doc.contains_field(body));
+        assert_eq!(doc.get_first(count), Some(&Value::U64(0u64)));
+        doc.retain_values(|field, value| field != title || value.as_str() == Some("hello"));
+        assert_eq!(doc.len(), 2);
+        assert_eq!(doc.get_first(title), Some(&Value::from("hello")));
+    }
+
+    #[test]
+    fn test_doc_retain_facets_with_prefix() {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("facet");
+        let other_facet_field = schema_builder.add_facet_field("other_facet");
+        let mut doc = Document::default();
+        for path in &[
+            "/category",
+            "/category/book",
+            "/category/book/novel",
+            "/tag/book",
+            "/categoryless",
+            "/",
+        ] {
+            doc.add_facet(facet_field, *path);
+        }
+        doc.add_text(facet_field, "/tag/not-a-facet");
+        doc.add_facet(other_facet_field, "/tag/book");
+        let mut root_doc = doc.clone();
+        root_doc.retain_facets_with_prefix(facet_field, &Facet::root());
+        assert_eq!(root_doc, doc);
+        doc.retain_facets_with_prefix(facet_field, &Facet::from("/category"));
+        assert_eq!(
+            doc.get_all(facet_field).collect::<Vec<_>>(),
+            vec![
+                &Value::Facet(Facet::from("/category")),
+                &Value::Facet(Facet::from("/category/book")),
+                &Value::Facet(Facet::from("/category/book/novel")),
+                &Value::from("/tag/not-a-facet"),
+            ]
+        );
+        assert_eq!(
+            doc.get_first(other_facet_field),
+            Some(&Value::Facet(Facet::from("/tag/book")))
+        );
+        doc.retain_facets_with_prefix(facet_field, &Facet::from("/category/book/novel"));
+        assert_eq!(
+            doc.get_all(facet_field).collect::<Vec<_>>(),
+            vec![
+                &Value::Facet(Facet::from("/category/book/novel")),
+                &Value::from("/tag/not-a-facet"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_doc_remove_empty_strings() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_texts(title, vec!["", " ", "hello", "\t\n", "", " world "]);
+        doc.add_bytes(title, Vec::new());
+        doc.add_u64(count, 0u64);
+        let mut blank_doc = doc.clone();
+        doc.remove_empty_strings();
+        assert_eq!(
+            doc.get_all(title)
+                .flat_map(Value::as_str)
+                .collect::<Vec<_>>(),
+            vec![" ", "hello", "\t\n", " world "]
+        );
+        assert_eq!(doc.len(), 6);
+        blank_doc.remove_blank_strings();
+        assert_eq!(
+            blank_doc
+                .get_all(title)
+                .flat_map(Value::as_str)
+                .collect::<Vec<_>>(),
+            vec!["hello", " world "]
+        );
+        assert_eq!(blank_doc.get_first_bytes(title), Some(&[][..]));
+        assert_eq!(blank_doc.get_first_u64(count), Some(0u64));
+        assert_eq!(blank_doc.len(), 4);
+    }
+
+    #[test]
+    fn test_doc_coerce_to_schema() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let score = schema_builder.add_i64_field("score", STORED);
+        let ratio = schema_builder.add_f64_field("ratio", STORED);
+        let schema = schema_builder.build();
+
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_i64(count, i64::MAX);
+        doc.add_u64(count, u64::MAX);
+        doc.add_u64(score, i64::MAX as u64);
+        doc.add_i64(score, i64::MIN);
+        doc.add_u64(ratio, 1u64 << 53);
+        doc.add_i64(ratio, i64::MIN);
+        doc.add_f64(ratio, 0.5f64);
+        assert_eq!(doc.coerce_to_schema(&schema), Ok(()));
+        assert_eq!(doc.validate(&schema), Ok(()));
+        assert_eq!(
+            doc.get_all(count).cloned().collect::<Vec<_>>(),
+            vec![Value::U64(i64::MAX as u64), Value::U64(u64::MAX)]
+        );
+        assert_eq!(
+            doc.get_all(score).cloned().collect::<Vec<_>>(),
+            vec![Value::I64(i64::MAX), Value::I64(i64::MIN)]
+        );
+        assert_eq!(
+            doc.get_all(ratio).cloned().collect::<Vec<_>>(),
+            vec![
+                Value::F64(9_007_199_254_740_992f64),
+                Value::F64(-9_223_372_036_854_775_808f64),
+                Value::F64(0.5f64)
+            ]
+        );
+
+        let lossy_conversions = vec![
+            (count, "count", Type::U64, Value::I64(-1i64)),
+            (count, "count", Type::U64, Value::I64(i64::MIN)),
+            (score, "score", Type::I64, Value::U64(i64::MAX as u64 + 1)),
+            (score, "score", Type::I64, Value::U64(u64::MAX)),
+            (ratio, "ratio", Type::F64, Value::U64((1u64 << 53) + 1)),
+            (ratio, "ratio", Type::F64, Value::U64(u64::MAX)),
+            (ratio, "ratio", Type::F64, Value::I64(i64::MAX)),
+        ];
+        for (field, field_name, expected, value) in lossy_conversions {
+            let mut doc = Document::default();
+            doc.add_i64(score, 1i64);
+            doc.add(FieldValue::new(field, value.clone()));
+            let doc_before = doc.clone();
+            assert_eq!(
+                doc.coerce_to_schema(&schema),
+                Err(ValidationError::LossyConversion {
+                    field_name: field_name.to_string(),
+                    expected,
+                    value,
+                })
+            );
+            assert_eq!(doc, doc_before);
+        }
+    }
+
+    #[test]
+    fn test_doc_with_capacity() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut doc = Document::with_capacity(10);
+        assert!(doc.is_empty());
+        assert!(doc.field_values.capacity() >= 10);
+        doc.add_text(title, "hello");
+        doc.reserve(20);
+        assert!(doc.field_values.capacity() >= 21);
+        assert_eq!(doc.len(), 1);
+    }
+
+    #[test]
+    fn test_doc_num_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let absent = schema_builder.add_text_field("absent", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_texts(tag, vec!["a", "b", "c"]);
+        assert_eq!(doc.num_values(absent), 0);
+        assert!(!doc.contains_field(absent));
+        assert_eq!(doc.num_values(title), 1);
+        assert!(doc.contains_field(title));
+        assert_eq!(doc.num_values(tag), 3);
+        assert!(doc.contains_field(tag));
+    }
+
+    #[test]
+    fn test_doc_iter() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 3u64);
+        doc.add_u64(count, 4u64);
+        let pairs: Vec<(Field, &Value)> = doc.iter().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (title, &Value::from("hello")),
+                (count, &Value::U64(3u64)),
+                (count, &Value::U64(4u64))
+            ]
+        );
+        let mut values_by_field: HashMap<Field, Vec<&Value>> = HashMap::new();
+        for (field, value) in &doc {
+            values_by_field.entry(field).or_default().push(value);
+        }
+        assert_eq!(values_by_field[&title], vec![&Value::from("hello")]);
+        assert_eq!(
+            values_by_field[&count],
+            vec![&Value::U64(3u64), &Value::U64(4u64)]
+        );
+        let owned_values: HashMap<Field, Value> = doc.into_iter().collect();
+        assert_eq!(owned_values.len(), 2);
+        assert_eq!(owned_values[&title], Value::from("hello"));
+        assert_eq!(owned_values[&count], Value::U64(4u64));
+    }
+
+    #[test]
+    fn test_doc_rewrite_facets() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_facet_field("category");
+        let other = schema_builder.add_facet_field("other");
+        let mut doc = Document::default();
+        doc.add_facet(category, "/old/root/books");
+        doc.add_facet(category, "/unrelated/books");
+        doc.add_facet(other, "/old/root/books");
+        let old_prefix = Facet::from("/old/root");
+        let new_prefix = Facet::from("/new/root");
+        doc.rewrite_facets(category, |facet| {
+            facet.replace_prefix(&old_prefix, &new_prefix);
+        });
+        assert_eq!(
+            doc.get_all(category).cloned().collect::<Vec<_>>(),
+            vec![
+                Value::Facet(Facet::from("/new/root/books")),
+                Value::Facet(Facet::from("/unrelated/books"))
+            ]
+        );
+        assert_eq!(
+            doc.get_first(other),
+            Some(&Value::Facet(Facet::from("/old/root/books")))
+        );
+    }
+
+    #[test]
+    fn test_doc_field_value_count_by_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let absent = schema_builder.add_text_field("absent", STORED);
+        let mut doc = Document::default();
+        doc.add_text(tag, "a");
+        doc.add_text(title, "hello");
+        doc.add_texts(tag, vec!["b", "c"]);
+        let counts = doc.field_value_count_by_field();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts.get(&title), Some(&1));
+        assert_eq!(counts.get(&tag), Some(&3));
+        assert_eq!(counts.get(&absent), None);
+        assert!(Document::default().field_value_count_by_field().is_empty());
+    }
+
+    #[test]
+    fn test_doc_clear() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut doc = Document::default();
+        doc.add_texts(title, vec!["a", "b", "c"]);
+        let capacity = doc.field_values.capacity();
+        doc.clear();
+        assert_eq!(doc.len(), 0);
+        assert_eq!(doc.field_values.capacity(), capacity);
+        doc.add_text(title, "d");
+        assert_eq!(doc.len(), 1);
+    }
+
+    #[test]
+    fn test_doc_add_many() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_many(count, (0u64..3u64).map(Value::U64));
+        doc.add_texts(tag, vec!["c", "a", "b"]);
+        assert_eq!(doc.len(), 7);
+        assert_eq!(
+            doc.get_all(count).collect::<Vec<_>>(),
+            vec![&Value::U64(0u64), &Value::U64(1u64), &Value::U64(2u64)]
+        );
+        assert_eq!(
+            doc.get_all(tag).flat_map(Value::as_str).collect::<Vec<_>>(),
+            vec!["c", "a", "b"]
+        );
+        assert_eq!(doc.field_values()[0].field(), title);
+    }
+
+    #[test]
+    fn test_doc_add_u64s() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        let mut expected_doc = Document::default();
+        doc.add_text(title, "hello");
+        expected_doc.add_text(title, "hello");
+        doc.add_u64s(count, (0u64..1_000u64).map(|val| val * 3));
+        for val in 0u64..1_000u64 {
+            expected_doc.add_u64(count, val * 3);
+        }
+        doc.add_u64s(count, Vec::new());
+        assert_eq!(doc.field_values(), expected_doc.field_values());
+        assert_eq!(doc.len(), 1_001);
+        assert_eq!(doc.get_first_u64(count), Some(0u64));
+    }
+
+    #[test]
+    fn test_doc_add_string() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut doc = Document::default();
+        let mut text = String::with_capacity(64);
+        text.push_str("owned");
+        let text_ptr = text.as_ptr();
+        doc.add_string(title, text);
+        let owned_cow: Cow<'_, str> = Cow::Owned(String::from("owned cow"));
+        let owned_cow_ptr = owned_cow.as_ptr();
+        doc.add_string(title, owned_cow);
+        doc.add_string(title, Cow::Borrowed("borrowed cow"));
+        doc.add_string(title, "borrowed");
+        let texts: Vec<&str> = doc.get_all(title).flat_map(Value::text).collect();
+        assert_eq!(
+            texts,
+            vec!["owned", "owned cow", "borrowed cow", "borrowed"]
+        );
+        // the owned texts were moved, not reallocated.
+        assert_eq!(texts[0].as_ptr(), text_ptr);
+        assert_eq!(texts[1].as_ptr(), owned_cow_ptr);
+        if let Some(Value::Str(text)) = doc.get_first(title) {
+            assert_eq!(text.capacity(), 64);
+        } else {
+            panic!("expected a text value");
+        }
+    }
+
+    #[test]
+    fn test_doc_sort_fields_by_schema() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        // a field unknown to the schema.
+        let other = Field::from_field_id(10);
+        let mut doc = Document::default();
+        doc.add_u64(other, 1u64);
+        doc.add_u64(count, 2u64);
+        doc.add_text(body, "first body");
+        doc.add_text(title, "title");
+        doc.add_u64(count, 1u64);
+        doc.add_text(body, "second body");
+        doc.add_u64(other, 0u64);
+        doc.sort_fields_by_schema(&schema);
+        let field_values: Vec<(Field, &Value)> = doc.iter().collect();
+        assert_eq!(
+            field_values,
+            vec![
+                (title, &Value::from("title")),
+                (body, &Value::from("first body")),
+                (body, &Value::from("second body")),
+                (count, &Value::U64(2u64)),
+                (count, &Value::U64(1u64)),
+                (other, &Value::U64(1u64)),
+                (other, &Value::U64(0u64)),
+            ]
+        );
+    }
+
+    fn deserialization_error(buffer: &[u8]) -> (io::ErrorKind, usize, u64) {
+        let err = Document::deserialize(&mut &buffer[..]).unwrap_err();
+        let deserialization_error = err
+            .get_ref()
+            .and_then(|err| err.downcast_ref::<DocumentDeserializationError>())
+            .unwrap();
+        (
+            err.kind(),
+            deserialization_error.field_value_index,
+            deserialization_error.byte_offset,
+        )
+    }
+
+    #[test]
+    fn test_doc_deserialization_error() {
+        let mut schema_builder = Schema::builder();
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_u64(count, 1u64);
+        doc.add_u64(count, 2u64);
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        // truncated right after the number of field values.
+        assert_eq!(
+            deserialization_error(&buffer[..1]),
+            (io::ErrorKind::UnexpectedEof, 0, 1)
+        );
+        // truncated within the second field value.
+        assert_eq!(
+            deserialization_error(&buffer[..20]),
+            (io::ErrorKind::UnexpectedEof, 1, 14)
+        );
+        // unknown type code for the second field value: 1 byte for the number
+        // of field values, 13 bytes for the first one, 4 bytes for the field.
+        let mut corrupted_buffer = buffer.clone();
+        corrupted_buffer[18] = 255u8;
+        assert_eq!(
+            deserialization_error(&corrupted_buffer),
+            (io::ErrorKind::InvalidData, 1, 14)
+        );
+        let err = Document::deserialize(&mut &corrupted_buffer[..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to deserialize the field value #1 of the document, at byte offset 14: No \
+             field type is associated with code 255"
+        );
+    }
+
+    #[test]
+    fn test_doc_from_iter() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let doc: Document = (0u64..10u64)
+            .filter(|val| val % 2 == 0)
+            .map(|val| (count, Value::U64(val)))
+            .collect();
+        assert_eq!(doc.len(), 5);
+        let mut doc: Document = doc
+            .field_values()
+            .iter()
+            .filter(|field_value| field_value.value().as_u64() != Some(0u64))
+            .cloned()
+            .collect();
+        assert_eq!(doc.len(), 4);
+        doc.extend(vec![FieldValue::new(title, Value::from("hello"))]);
+        assert_eq!(doc.len(), 5);
+        assert_eq!(doc.get_first(title), Some(&Value::from("hello")));
+    }
+
+    #[test]
+    fn test_doc_into_field_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let field_values = vec![
+            FieldValue::new(count, Value::U64(2u64)),
+            FieldValue::new(title, Value::from("hello")),
+            FieldValue::with_boost(count, Value::U64(1u64), 2.0f32),
+        ];
+        let doc = Document::from(field_values.clone());
+        let mut recovered_field_values = doc.into_field_values();
+        assert_eq!(recovered_field_values, field_values);
+        recovered_field_values.sort();
+        let doc = Document::from(recovered_field_values);
+        assert_eq!(doc, Document::from(field_values));
+        assert!(Document::default().into_field_values().is_empty());
+    }
+
+    #[test]
+    fn test_doc_u128_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_bytes_field("id", STORED);
+        let mut doc = Document::default();
+        doc.add_u128(id, u128::MAX - 1);
+        doc.add_u128(id, 1u128 << 64);
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        // 1 byte for the number of field values, then 4 + 1 + 16 bytes per value.
+        assert_eq!(buffer.len(), 1 + 2 * (4 + 1 + 16));
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_all(id).collect::<Vec<_>>(),
+            vec![&Value::U128(u128::MAX - 1), &Value::U128(1u128 << 64)]
+        );
+        assert_eq!(
+            deser_doc.get_first(id).and_then(Value::u128_value),
+            Some(u128::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_doc_get_first_typed() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let thumbnail = schema_builder.add_bytes_field("thumbnail", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_text(title, "happy tax payer");
+        doc.add_i64(views, -3i64);
+        doc.add_u64(views, 3u64);
+        doc.add_bytes(thumbnail, vec![1u8, 2u8]);
+        assert_eq!(doc.get_first_str(title), Some("hello"));
+        assert_eq!(doc.get_first_u64(title), None);
+        assert_eq!(doc.get_first_bytes(thumbnail), Some(&[1u8, 2u8][..]));
+        assert_eq!(doc.get_first_str(thumbnail), None);
+        // only the first value is considered.
+        assert_eq!(doc.get_first_i64(views), Some(-3i64));
+        assert_eq!(doc.get_first_u64(views), None);
+        assert_eq!(doc.get_first_f64(views), None);
+        assert_eq!(doc.get_first_date(date), None);
+        assert_eq!(doc.get_first_str(date), None);
+    }
+
+    #[test]
+    fn test_doc_add_facet_segments() {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("category");
+        let mut doc = Document::default();
+        doc.add_facet_segments(facet_field, &["music", "rock/pop", "a\\b"]);
+        doc.add_facet_segments(facet_field, &[]);
+        let facets: Vec<&Facet> = doc.get_all(facet_field).flat_map(Value::as_facet).collect();
+        assert_eq!(facets.len(), 2);
+        assert_eq!(facets[0].to_path(), vec!["music", "rock/pop", "a\\b"]);
+        assert_eq!(facets[0].to_string(), "/music/rock\\/pop/a\\\\b");
+        assert_eq!(facets[0], &Facet::from_text("/music/rock\\/pop/a\\\\b"));
+        assert_ne!(facets[0], &Facet::from("/music/rock/pop/a\\\\b"));
+        assert!(facets[1].is_root());
+    }
+
+    #[test]
+    fn test_doc_add_facet_with_ancestors() {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_facet_field("category");
+        let mut doc = Document::default();
+        doc.add_facet(category, "/a/b");
+        doc.add_facet_with_ancestors(category, "/a/b/c");
+        doc.add_facet_with_ancestors(category, "/a/d");
+        let facets = |doc: &Document| -> Vec<String> {
+            doc.get_all(category)
+                .filter_map(Value::as_facet)
+                .map(Facet::to_path_string)
+                .collect()
+        };
+        assert_eq!(facets(&doc), vec!["/a/b", "/a", "/a/b/c", "/a/d"]);
+        let mut doc = Document::default();
+        doc.add_facet_with_ancestors(category, "/");
+        doc.add_facet_with_ancestors(category, Facet::root());
+        doc.add_facet_with_ancestors(category, "/a");
+        assert_eq!(facets(&doc), vec!["/", "/a"]);
+    }
+
+    #[test]
+    fn test_doc_framed() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let docs: Vec<Document> = ["hello", "happy tax payer", "tax"]
+            .iter()
+            .map(|text| {
+                let mut doc = Document::default();
+                doc.add_text(title, text);
+                doc
+            })
+            .collect();
+        let mut buffer = Vec::new();
+        for doc in &docs {
+            doc.serialize_framed(&mut buffer).unwrap();
+        }
+        let mut cursor = io::Cursor::new(&buffer[..]);
+        assert_eq!(Document::deserialize_framed(&mut cursor).unwrap(), docs[0]);
+        Document::skip_framed(&mut cursor).unwrap();
+        assert_eq!(Document::deserialize_framed(&mut cursor).unwrap(), docs[2]);
+        assert_eq!(cursor.position() as usize, buffer.len());
+        let mut cursor = &buffer[..];
+        for doc in &docs {
+            assert_eq!(&Document::deserialize_framed(&mut cursor).unwrap(), doc);
+        }
+        assert!(cursor.is_empty());
+        // a frame larger than the document.
+        let mut padded_doc = Vec::new();
+        docs[0].serialize(&mut padded_doc).unwrap();
+        padded_doc.push(0u8);
+        let mut padded_buffer = Vec::new();
+        VInt(padded_doc.len() as u64)
+            .serialize(&mut padded_buffer)
+            .unwrap();
+        padded_buffer.extend_from_slice(&padded_doc);
+        assert!(Document::deserialize_framed(&mut &padded_buffer[..]).is_err());
+    }
+
+    #[test]
+    fn test_doc_fold_case() {
+        let mut schema_builder = Schema::builder();
+        let keyword = schema_builder.add_text_field("keyword", STRING);
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut doc = Document::default();
+        for text in &[
+            "Hello",
+            "Straße",
+            "GROẞ",
+            "İstanbul",
+            "DİYARBAKIR",
+            "ırmak",
+            "ΣΊΣΥΦΟΣ",
+            "ﬁle",
+            "Ꭰꭰᏸ",
+        ] {
+            doc.add_text(keyword, text);
+        }
+        doc.add_text(title, "Straße");
+        doc.add_u64(keyword, 1u64);
+        doc.fold_case(&[keyword]);
+        assert_eq!(
+            doc.get_all(keyword)
+                .flat_map(Value::as_str)
+                .collect::<Vec<_>>(),
+            vec![
+                "hello",
+                "strasse",
+                "gross",
+                "i\u{307}stanbul",
+                "di\u{307}yarbakir",
+                "ırmak",
+                "σίσυφοσ",
+                "file",
+                "ᎠᎠᏰ",
+            ]
+        );
+        assert_eq!(doc.get_first_str(title), Some("Straße"));
+        assert_eq!(doc.get_all(keyword).last(), Some(&Value::U64(1u64)));
+    }
+
+    #[test]
+    fn test_doc_add_opt() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let delta = schema_builder.add_i64_field("delta", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let mut doc = Document::default();
+        doc.add_text_opt(title, None::<&str>)
+            .add_u64_opt(views, None)
+            .add_i64_opt(delta, None)
+            .add_f64_opt(score, None)
+            .add_date_opt(date, None);
+        assert!(doc.is_empty());
+        let now = Utc.timestamp(1_600_000_000, 0);
+        doc.add_text_opt(title, Some("hello"))
+            .add_u64_opt(views, Some(3u64))
+            .add_i64_opt(delta, Some(-1i64))
+            .add_f64_opt(score, Some(0.5f64))
+            .add_date_opt(date, Some(&now));
+        assert_eq!(
+            doc,
+            doc!(title=>"hello", views=>3u64, delta=>-1i64, score=>0.5f64, date=>now)
+        );
+    }
+
+    #[test]
+    fn test_doc_total_bytes_payload() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let thumbnail = schema_builder.add_bytes_field("thumbnail", STORED);
+        let attributes = schema_builder.add_bytes_field("attributes", STORED);
+        let mut doc = Document::default();
+        assert_eq!(doc.total_bytes_payload(true), 0);
+        doc.add_text(title, "hello")
+            .add_u64(views, 1_000u64)
+            .add_bytes(thumbnail, vec![0u8; 100])
+            .add_bytes(thumbnail, vec![1u8; 20])
+            .add_array(
+                attributes,
+                vec![Value::from(vec![2u8; 3]), Value::from("abc")],
+            );
+        assert_eq!(doc.total_bytes_payload(false), 123);
+        assert_eq!(doc.total_bytes_payload(true), 131);
+    }
+
+    #[test]
+    fn test_doc_byte_size() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let category = schema_builder.add_facet_field("category");
+        let thumbnail = schema_builder.add_bytes_field("thumbnail", STORED);
+        assert_eq!(Document::default().byte_size(), 0);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_u64(views, 3u64);
+        doc.add_facet(category, "/a/b");
+        assert_eq!(doc.byte_size(), (4 + 5) + (4 + 8) + (4 + 3));
+        let mut doc_with_thumbnail = doc.clone();
+        doc_with_thumbnail.add_bytes(thumbnail, vec![0u8; 1_000_000]);
+        assert_eq!(
+            doc_with_thumbnail.byte_size(),
+            doc.byte_size() + 4 + 1_000_000
+        );
+        let mut doc_with_array = Document::default();
+        doc_with_array.add_array(title, vec![Value::from("abc"), Value::Bool(true)]);
+        doc_with_array.add_f32(views, 1.5f32);
+        doc_with_array.add_null(views);
+        assert_eq!(doc_with_array.byte_size(), (4 + 3 + 1) + (4 + 4) + 4);
+    }
+
+    #[test]
+    fn test_doc_boost_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let num_bytes_without_boost = buffer.len();
+        doc.add_with_boost(title, "happy tax payer", 2.5f32);
+        doc.add_with_boost(title, "tax", 1.0f32);
+        buffer.clear();
+        doc.serialize(&mut buffer).unwrap();
+        // only the non-default boost takes 4 additional bytes.
+        assert_eq!(
+            buffer.len(),
+            num_bytes_without_boost + (4 + 1 + 1 + 15 + 4) + (4 + 1 + 1 + 3)
+        );
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc
+                .field_values()
+                .iter()
+                .map(FieldValue::boost)
+                .collect::<Vec<_>>(),
+            vec![1.0f32, 2.5f32, 1.0f32]
+        );
+        assert_eq!(deser_doc, doc);
+        let mut cursor = &buffer[..];
+        Document::skip_deserialize(&mut cursor).unwrap();
+        assert!(cursor.is_empty());
+        buffer.clear();
+        doc.serialize_with_bytes_compression(&mut buffer, Some(0))
+            .unwrap();
+        assert_eq!(Document::deserialize(&mut &buffer[..]).unwrap(), doc);
+        let mut unboosted_doc = Document::default();
+        unboosted_doc.add_text(title, "hello");
+        unboosted_doc.add_text(title, "happy tax payer");
+        unboosted_doc.add_text(title, "tax");
+        assert_ne!(unboosted_doc, doc);
+    }
+
+    #[test]
+    fn test_doc_serialize_sorted() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let mut doc1 = Document::default();
+        doc1.add_text(title, "hello");
+        doc1.add_u64(views, 3u64);
+        doc1.add_text(title, "happy tax payer");
+        let mut doc2 = Document::default();
+        doc2.add_u64(views, 3u64);
+        doc2.add_text(title, "hello");
+        doc2.add_text(title, "happy tax payer");
+        let serialize = |doc: &Document| {
+            let mut buffer = Vec::new();
+            doc.serialize(&mut buffer).unwrap();
+            buffer
+        };
+        let serialize_sorted = |doc: &Document| {
+            let mut buffer = Vec::new();
+            doc.serialize_sorted(&mut buffer).unwrap();
+            buffer
+        };
+        assert_ne!(serialize(&doc1), serialize(&doc2));
+        assert_eq!(serialize_sorted(&doc1), serialize_sorted(&doc2));
+        let mut sorted_doc = doc1.clone();
+        sorted_doc.sort_field_values();
+        assert_eq!(serialize_sorted(&doc1), serialize(&sorted_doc));
+        let deser_doc = Document::deserialize(&mut &serialize_sorted(&doc1)[..]).unwrap();
+        assert_eq!(deser_doc.field_values(), sorted_doc.field_values());
+    }
+
+    #[test]
+    fn test_doc_add_geo_point() {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_f64_field("location", STORED);
+        let mut doc = Document::default();
+        doc.add_geo_point(location, 90.0, -180.0)
+            .and_then(|doc| doc.add_geo_point(location, 48.8566, 2.3522))
+            .unwrap();
+        assert_eq!(
+            doc.add_geo_point(location, 0.0, 200.0).unwrap_err(),
+            GeoPointError {
+                lat: 0.0,
+                lng: 200.0
+            }
+        );
+        assert_eq!(doc.len(), 2);
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deserialized = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deserialized
+                .get_all(location)
+                .flat_map(Value::as_geo_point)
+                .collect::<Vec<_>>(),
+            vec![(90.0, -180.0), (48.8566, 2.3522)]
+        );
+    }
+
+    #[test]
+    fn test_doc_date_precision() {
+        let mut schema_builder = Schema::builder();
+        let date_field = schema_builder.add_date_field("date", STORED);
+        let date = Utc.timestamp(1_600_000_000, 123_456_789);
+        let mut doc = Document::default();
+        doc.add_date(date_field, &date);
+        doc.add_date_from_timestamp_millis(date_field, -1);
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deserialized = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deserialized, doc);
+        assert_eq!(deserialized.get_first_date(date_field), Some(&date));
+        assert_eq!(
+            deserialized
+                .get_all(date_field)
+                .nth(1)
+                .and_then(Value::as_date)
+                .map(|date| date.timestamp_millis()),
+            Some(-1)
+        );
+    }
+
+    #[test]
+    fn test_doc_f32_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let score = schema_builder.add_f64_field("score", STORED);
+        let mut doc = Document::default();
+        doc.add_f32(score, f32::from_bits(1));
+        doc.add_f32(score, f32::INFINITY);
+        doc.add_f32(score, -0.5f32);
+        doc.prepare_for_store();
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        // 1 byte for the number of field values, then 4 + 1 + 4 bytes per value.
+        assert_eq!(buffer.len(), 1 + 3 * (4 + 1 + 4));
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc
+                .get_all(score)
+                .map(|value| value.as_f32().map(f32::to_bits))
+                .collect::<Vec<_>>(),
+            vec![
+                Some(1),
+                Some(f32::INFINITY.to_bits()),
+                Some((-0.5f32).to_bits())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_doc_merge_from() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "title1");
+        doc.add_text(body, "body1");
+        doc.merge_from(Document::default());
+        assert_eq!(doc.len(), 2);
+        let mut other = Document::default();
+        other.add_text(title, "title2");
+        doc.merge_from(other);
+        assert_eq!(doc.len(), 3);
+        assert_eq!(
+            doc.get_all(title).collect::<Vec<_>>(),
+            vec![
+                &Value::Str("title1".to_string()),
+                &Value::Str("title2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_doc_extend_from() {
+        let mut schema_builder = Schema::builder();
+        let source = schema_builder.add_text_field("source", STORED);
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut template = Document::default();
+        template.add_text(source, "crawler");
+        template.add_text(title, "untitled");
+        let mut doc1 = Document::default();
+        doc1.add_text(title, "title1");
+        doc1.extend_from(&template);
+        let mut doc2 = Document::default();
+        doc2.extend_from(&template);
+        assert_eq!(doc2, template);
+        assert_eq!(
+            doc1.get_all(title).collect::<Vec<_>>(),
+            vec![&Value::from("title1"), &Value::from("untitled")]
+        );
+        doc1.map_values(|value| *value = Value::from("changed"));
+        doc2.remove_field(title);
+        assert_eq!(doc2.get_first_str(source), Some("crawler"));
+        assert_eq!(doc2.get_first(title), None);
+        assert_eq!(template.get_first_str(source), Some("crawler"));
+        assert_eq!(template.get_first_str(title), Some("untitled"));
+        assert_eq!(template.len(), 2);
+    }
+
+    #[test]
+    fn test_doc_truncate_dates() {
+        let mut schema_builder = Schema::builder();
+        let day = schema_builder.add_date_field("day", STORED);
+        let timestamp = schema_builder.add_date_field("timestamp", STORED);
+        let date = Utc.timestamp(1_600_000_000, 123_456_789);
+        let mut doc = Document::default();
+        doc.add_date(day, &date)
+            .add_date(timestamp, &date)
+            .add_array(day, vec![Value::Date(date)]);
+        doc.truncate_dates(day, DatePrecision::Seconds);
+        assert_eq!(
+            doc.get_all(day).collect::<Vec<_>>(),
+            vec![
+                &Value::Date(Utc.timestamp(1_600_000_000, 0)),
+                &Value::Array(vec![Value::Date(date)])
+            ]
+        );
+        assert_eq!(doc.get_first(timestamp), Some(&Value::Date(date)));
+    }
+
+    #[test]
+    fn test_doc_apply_defaults() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let status = schema_builder.add_text_field("status", STORED);
+        let priority = schema_builder.add_u64_field("priority", STORED);
+        let defaults = [
+            (status, Value::from("active")),
+            (priority, Value::U64(0u64)),
+        ];
+        // absent
+        let mut doc = doc!(title=>"hello");
+        doc.apply_defaults(&defaults);
+        assert_eq!(doc, doc!(title=>"hello", status=>"active", priority=>0u64));
+        // present
+        let mut doc = doc!(status=>"archived", priority=>3u64, priority=>4u64);
+        doc.apply_defaults(&defaults);
+        assert_eq!(
+            doc,
+            doc!(status=>"archived", priority=>3u64, priority=>4u64)
+        );
+        // null-present
+        let mut doc = Document::default();
+        doc.add_null(status);
+        doc.apply_defaults(&defaults);
+        assert_eq!(doc.get_all(status).collect::<Vec<_>>(), vec![&Value::Null]);
+        assert_eq!(doc.get_first_u64(priority), Some(0u64));
+        // several defaults for the same field
+        let mut doc = Document::default();
+        doc.apply_defaults(&[(status, Value::from("a")), (status, Value::from("b"))]);
+        assert_eq!(
+            doc.get_all(status)
+                .flat_map(Value::as_str)
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_doc_apply_patch() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_texts(tag, vec!["a", "b", "c"]);
+        doc.add_u64(count, 1u64);
+        doc.apply_patch(&[(tag, Value::from("d"))]);
+        assert_eq!(doc.len(), 3);
+        assert_eq!(
+            doc.get_all(tag).flat_map(Value::as_str).collect::<Vec<_>>(),
+            vec!["d"]
+        );
+        assert_eq!(doc.get_first_str(title), Some("hello"));
+        assert_eq!(doc.get_first_u64(count), Some(1u64));
+        doc.apply_patch(&[
+            (count, Value::U64(2u64)),
+            (count, Value::U64(3u64)),
+            (title, Value::from("world")),
+        ]);
+        assert_eq!(
+            doc.get_all(count).collect::<Vec<_>>(),
+            vec![&Value::U64(2u64), &Value::U64(3u64)]
+        );
+        assert_eq!(doc.get_first_str(title), Some("world"));
+        doc.apply_patch_append(&[(tag, Value::from("e"))]);
+        assert_eq!(
+            doc.get_all(tag).flat_map(Value::as_str).collect::<Vec<_>>(),
+            vec!["d", "e"]
+        );
+        doc.apply_patch(&[]);
+        assert_eq!(doc.len(), 5);
+    }
+
+    #[test]
+    fn test_doc_deduplicate_values() {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(tag, "b");
+        doc.add_u64(count, 1u64);
+        doc.add_text(tag, "a");
+        doc.add_text(tag, "b");
+        doc.add_u64(count, 2u64);
+        doc.add_u64(count, 1u64);
+        doc.add_text(count, "b");
+        doc.add_text(tag, "a");
+        doc.deduplicate_values();
+        assert_eq!(doc.len(), 5);
+        assert_eq!(
+            doc.get_all(tag).flat_map(Value::as_str).collect::<Vec<_>>(),
+            vec!["b", "a"]
+        );
+        assert_eq!(
+            doc.get_all(count).collect::<Vec<_>>(),
+            vec![
+                &Value::U64(1u64),
+                &Value::U64(2u64),
+                &Value::Str("b".to_string())
+            ]
+        );
+        doc.deduplicate_values();
+        assert_eq!(doc.len(), 5);
+    }
+
+    #[test]
+    fn test_doc_merge_from_replacing() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "title1");
+        doc.add_text(title, "title2");
+        doc.add_text(body, "body1");
+        let mut other = Document::default();
+        other.add_text(title, "title3");
+        other.add_text(body, "body2");
+        doc.merge_from_replacing(other, &[title]);
+        assert_eq!(
+            doc.get_all(title).collect::<Vec<_>>(),
+            vec![&Value::Str("title3".to_string())]
+        );
+        assert_eq!(doc.get_all(body).count(), 2);
+        doc.merge_from_replacing(Document::default(), &[body]);
+        assert_eq!(doc.len(), 1);
+    }
+
+    #[test]
+    fn test_doc_from_json_value() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let score = schema_builder.add_i64_field("score", STORED);
+        let schema = schema_builder.build();
+        let json_value = serde_json::json!({
+            "title": ["hello", "happy tax payer"],
+            "count": 18_446_744_073_709_551_615u64,
+            "score": -3
+        });
+        let doc = Document::from_json_value(&schema, &json_value).unwrap();
+        assert_eq!(doc.len(), 4);
+        assert_eq!(doc.get_all(title).count(), 2);
+        assert_eq!(doc.get_first(count), Some(&Value::U64(u64::MAX)));
+        assert_eq!(doc.get_first(score), Some(&Value::I64(-3)));
+    }
+
+    #[test]
+    fn test_doc_sort_field_values() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_u64(count, 2u64);
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 1u64);
+        doc.add_text(title, "world");
+        let expected: Vec<(Field, Vec<FieldValue>)> = vec![
+            (
+                title,
+                vec![
+                    FieldValue::new(title, Value::Str("hello".to_string())),
+                    FieldValue::new(title, Value::Str("world".to_string())),
+                ],
+            ),
+            (
+                count,
+                vec![
+                    FieldValue::new(count, Value::U64(2u64)),
+                    FieldValue::new(count, Value::U64(1u64)),
+                ],
+            ),
+        ];
+        let to_owned = |groups: Vec<(Field, Vec<&FieldValue>)>| {
+            groups
+                .into_iter()
+                .map(|(field, values)| (field, values.into_iter().cloned().collect::<Vec<_>>()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(to_owned(doc.get_sorted_field_values()), expected);
+        doc.sort_field_values();
+        assert_eq!(
+            doc.field_values()
+                .iter()
+                .map(FieldValue::field)
+                .collect::<Vec<_>>(),
+            vec![title, title, count, count]
+        );
+        assert_eq!(to_owned(doc.get_sorted_field_values()), expected);
+        assert!(Document::default().get_sorted_field_values().is_empty());
+    }
+
+    #[test]
+    fn test_doc_to_named_json() {
+        use crate::DateTime;
+        use std::str::FromStr;
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        schema_builder.add_i64_field("score", STORED);
+        let schema = schema_builder.build();
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 3u64);
+        doc.add_text(title, "happy tax payer");
+        doc.add_date(date, &DateTime::from_str("1990-10-10T10:10:10Z").unwrap());
+        let json_value = doc.to_named_json(&schema);
+        assert_eq!(
+            json_value,
+            serde_json::json!({
+                "title": ["hello", "happy tax payer"],
+                "count": 3,
+                "date": "1990-10-10T10:10:10+00:00"
+            })
+        );
+        assert_eq!(
+            Document::from_json_value(&schema, &json_value).unwrap(),
+            doc
+        );
+    }
+
+    #[test]
+    fn test_doc_from_json_value_errors() {
+        use crate::schema::field_type::ValueParsingError;
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", STORED);
+        schema_builder.add_i64_field("score", STORED);
+        schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        assert_eq!(
+            Document::from_json_value(&schema, &serde_json::json!(["title"])),
+            Err(DocParsingError::NotJSON(
+                "Expected a json object, got [\"title\"]".to_string()
+            ))
+        );
+        let large_json_value = serde_json::json!(vec!["title"; 1_000]);
+        assert_eq!(
+            Document::from_json_value(&schema, &large_json_value),
+            Err(DocParsingError::NotJSON(
+                "Expected a json object, got \"[\\\"title\\\",\\\"title\\\",\\\"ti\"..."
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            Document::from_json_value(&schema, &serde_json::json!({"body": "hello"})),
+            Err(DocParsingError::NoSuchFieldInSchema("body".to_string()))
+        );
+        assert!(matches!(
+            Document::from_json_value(
+                &schema,
+                &serde_json::json!({ "score": 18_446_744_073_709_551_615u64 })
+            ),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::OverflowError(_)
+            ))
+        ));
+        assert!(matches!(
+            Document::from_json_value(&schema, &serde_json::json!({"count": {"nested": 1}})),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::TypeError(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_doc_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+        use std::hash::{Hash, Hasher};
+        let hash = |doc: &Document| {
+            let mut hasher = DefaultHasher::new();
+            doc.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let mut doc1 = Document::default();
+        doc1.add_text(title, "hello");
+        doc1.add_f64(score, 0.0);
+        doc1.add_text(title, "happy tax payer");
+        let mut doc2 = Document::default();
+        doc2.add_text(title, "happy tax payer");
+        doc2.add_f64(score, -0.0);
+        doc2.add_text(title, "hello");
+        assert_eq!(doc1, doc2);
+        assert_eq!(hash(&doc1), hash(&doc2));
+        let mut doc3 = doc2.clone();
+        doc3.add_text(title, "hello");
+        assert_ne!(doc1, doc3);
+        assert_ne!(hash(&doc1), hash(&doc3));
+        let docs: HashSet<Document> = vec![doc1, doc2, doc3].into_iter().collect();
+        assert_eq!(docs.len(), 2);
+    }
+
+    #[test]
+    fn test_serialize_documents_parallel() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let docs: Vec<Document> = (0..10_000u64)
+            .map(|i| {
+                let mut doc = Document::default();
+                doc.add_text(title, format!("Doc {}", i));
+                for val in 0..i % 7 {
+                    doc.add_u64(count, val);
+                }
+                doc
+            })
+            .collect();
+        for num_docs in &[0, 1, 3, 10_000] {
+            let docs = &docs[..*num_docs];
+            let mut sequential_buffer = Vec::new();
+            for doc in docs {
+                doc.serialize(&mut sequential_buffer).unwrap();
+            }
+            let mut parallel_buffer = Vec::new();
+            serialize_documents_parallel(docs, &mut parallel_buffer).unwrap();
+            assert_eq!(parallel_buffer, sequential_buffer);
+        }
+    }
+
+    #[test]
+    fn test_doc_diff() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let tag = schema_builder.add_text_field("tag", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let old_doc = doc!(title=>"hello", tag=>"a", tag=>"a", count=>1u64);
+        // unchanged, in a different order
+        let diff = old_doc.diff(&doc!(count=>1u64, tag=>"a", title=>"hello", tag=>"a"));
+        assert!(diff.is_empty());
+        assert_eq!(diff, DocumentDiff::default());
+        // additions
+        let diff = old_doc
+            .diff(&doc!(title=>"hello", tag=>"a", tag=>"a", tag=>"a", tag=>"b", count=>1u64));
+        assert_eq!(
+            diff.added,
+            vec![
+                FieldValue::new(tag, Value::from("a")),
+                FieldValue::new(tag, Value::from("b"))
+            ]
+        );
+        assert!(diff.removed.is_empty());
+        // removals
+        let diff = old_doc.diff(&doc!(title=>"hello", tag=>"a"));
+        assert!(diff.added.is_empty());
+        assert_eq!(
+            diff.removed,
+            vec![
+                FieldValue::new(tag, Value::from("a")),
+                FieldValue::new(count, Value::U64(1u64))
+            ]
+        );
+        // both
+        let diff = old_doc.diff(&doc!(title=>"world", tag=>"a", tag=>"a", count=>1u64));
+        assert_eq!(
+            diff.added,
+            vec![FieldValue::new(title, Value::from("world"))]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![FieldValue::new(title, Value::from("hello"))]
+        );
+        assert!(Document::default().diff(&Document::default()).is_empty());
+    }
+
+    #[test]
+    fn test_doc_eq_ignoring() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let version = schema_builder.add_u64_field("_version", STORED);
+        let indexed_at = schema_builder.add_date_field("indexed_at", STORED);
+        let date = Utc.timestamp(1_600_000_000, 0);
+        let doc = doc!(title=>"hello", version=>1u64, indexed_at=>date);
+        let other_doc = doc!(version=>2u64, title=>"hello");
+        assert_ne!(doc, other_doc);
+        assert!(doc.eq_ignoring(&other_doc, &[version, indexed_at]));
+        assert!(other_doc.eq_ignoring(&doc, &[indexed_at, version]));
+        assert!(!doc.eq_ignoring(&other_doc, &[version]));
+        assert!(!doc.eq_ignoring(&other_doc, &[]));
+        assert!(!doc.eq_ignoring(&doc!(title=>"world"), &[version, indexed_at]));
+        assert!(doc.eq_ignoring(&doc, &[]));
+        assert!(doc.eq_ignoring(&Document::default(), &[title, version, indexed_at]));
+    }
+
+    #[test]
+    fn test_doc_checksum() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_u64(count, 3u64);
+        let mut buffer = Vec::new();
+        doc.serialize_checksummed(&mut buffer).unwrap();
+        let mut plain_buffer = Vec::new();
+        doc.serialize(&mut plain_buffer).unwrap();
+        assert_eq!(buffer.len(), plain_buffer.len() + 4);
+        assert_eq!(&buffer[..plain_buffer.len()], &plain_buffer[..]);
+        assert_eq!(
+            Document::deserialize_checksummed(&mut &buffer[..]).unwrap(),
+            doc
+        );
+        // the last byte of the text "hello"
+        let mut corrupted = buffer.clone();
+        corrupted[7] ^= 1u8;
+        let err = Document::deserialize_checksummed(&mut &corrupted[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let truncated = &buffer[..buffer.len() - 1];
+        let err = Document::deserialize_checksummed(&mut &truncated[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_doc_bytes_compression() {
+        let mut schema_builder = Schema::builder();
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let mut small_doc = Document::default();
+        small_doc.add_bytes(payload, vec![1u8; 100]);
+        let mut buffer = Vec::new();
+        small_doc.serialize(&mut buffer).unwrap();
+        let mut compressed_buffer = Vec::new();
+        small_doc
+            .serialize_with_bytes_compression(&mut compressed_buffer, Some(100))
+            .unwrap();
+        // bytes values below the threshold are serialized as is.
+        assert_eq!(compressed_buffer, buffer);
+
+        let mut doc = Document::default();
+        doc.add_bytes(payload, vec![1u8; 100]);
+        doc.add_bytes(payload, vec![2u8; 10_000]);
+        let mut buffer = Vec::new();
+        doc.serialize_with_bytes_compression(&mut buffer, Some(100))
+            .unwrap();
+        assert!(buffer.len() < 10_000);
+        assert_eq!(Document::deserialize(&mut &buffer[..]).unwrap(), doc);
+        assert!(Document::skip_deserialize(&mut &buffer[..]).is_ok());
+    }
+
+    #[test]
+    fn test_doc_missing_checksum() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let mut first_doc = Document::default();
+        first_doc.add_text(title, "hello");
+        let mut second_doc = Document::default();
+        second_doc.add_text(title, "happy tax payer");
+        let mut buffer = Vec::new();
+        first_doc.serialize(&mut buffer).unwrap();
+        let err = Document::deserialize_checksummed(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        // documents serialized without checksum are read one after the other.
+        second_doc.serialize(&mut buffer).unwrap();
+        let mut cursor = &buffer[..];
+        assert_eq!(Document::deserialize(&mut cursor).unwrap(), first_doc);
+        assert_eq!(Document::deserialize(&mut cursor).unwrap(), second_doc);
+        assert!(cursor.is_empty());
+        let mut cursor = &buffer[..];
+        Document::skip_deserialize(&mut cursor).unwrap();
+        assert_eq!(Document::deserialize(&mut cursor).unwrap(), second_doc);
+    }
+
+    #[test]
+    fn test_doc_skip_deserialize() {
+        use std::str::FromStr;
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STORED);
+        let other = schema_builder.add_u64_field("other", STORED);
+        let mut doc = Document::default();
+        doc.add_text(text, "hello");
+        doc.add_u64(other, 3u64);
+        doc.add_i64(other, -3i64);
+        doc.add_f64(other, 3.5f64);
+        doc.add_date(
+            other,
+            &crate::DateTime::from_str("2020-09-13T12:26:40+00:00").unwrap(),
+        );
+        doc.add_facet(other, "/a/b");
+        doc.add_bytes(other, vec![1u8; 300]);
+        doc.add_bool(other, true);
+        doc.add_null(other);
+        doc.add_u128(other, 3u128);
+        doc.add_pre_tokenized_text(
+            text,
+            &PreTokenizedString {
+                text: String::from("A"),
+                tokens: vec![Token {
+                    offset_from: 0,
+                    offset_to: 1,
+                    position: 0,
+                    text: String::from("A"),
+                    position_length: 1,
+                }],
+            },
+        );
+        let mut last_doc = Document::default();
+        last_doc.add_text(text, "last");
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        last_doc.serialize(&mut buffer).unwrap();
+        let mut cursor = &buffer[..];
+        Document::skip_deserialize(&mut cursor).unwrap();
+        assert_eq!(Document::deserialize(&mut cursor).unwrap(), last_doc);
+        assert!(cursor.is_empty());
+        let mut truncated = &buffer[..buffer.len() - 2];
+        Document::skip_deserialize(&mut truncated).unwrap();
+        assert!(Document::skip_deserialize(&mut truncated).is_err());
+    }
+
+    #[test]
+    fn test_add_date_from_timestamp() {
+        use crate::DateTime;
+        use std::str::FromStr;
+        let mut schema_builder = Schema::builder();
+        let date = schema_builder.add_date_field("date", STORED);
+        let mut doc = Document::default();
+        doc.add_date_from_timestamp_secs(date, 1_600_000_000);
+        doc.add_date_from_timestamp_secs(date, -1);
+        doc.add_date_from_timestamp_millis(date, 1_600_000_000_123);
+        doc.add_date_from_timestamp_millis(date, -1);
+        doc.add_date_from_timestamp_millis(date, -1_500);
+        let expected: Vec<Value> = [
+            "2020-09-13T12:26:40Z",
+            "1969-12-31T23:59:59Z",
+            "2020-09-13T12:26:40.123Z",
+            "1969-12-31T23:59:59.999Z",
+            "1969-12-31T23:59:58.500Z",
+        ]
+        .iter()
+        .map(|date_str| Value::Date(DateTime::from_str(date_str).unwrap()))
+        .collect();
+        assert_eq!(doc.get_all(date).cloned().collect::<Vec<_>>(), expected);
+    }
 }
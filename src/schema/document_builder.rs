@@ -0,0 +1,190 @@
+use super::*;
+use crate::tokenizer::PreTokenizedString;
+use crate::DateTime;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Builds a document with chained calls, designating fields by their name
+/// in the schema.
+///
+/// Field names are resolved as values are added. If one of them is not
+/// declared in the schema, `build` returns a `DocParsingError::NoSuchFieldInSchema`
+/// error for the first of them.
+///
+/// ```
+/// use tantivy::schema::{DocumentBuilder, Schema, STORED, TEXT};
+///
+/// # fn main() -> Result<(), tantivy::schema::DocParsingError> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let views = schema_builder.add_u64_field("views", STORED);
+/// let schema = schema_builder.build();
+///
+/// let doc = DocumentBuilder::new(&schema)
+///     .text("title", "The Old Man and the Sea")
+///     .u64("views", 10)
+///     .build()?;
+/// assert_eq!(
+///     doc.get_first(title).and_then(|value| value.as_str()),
+///     Some("The Old Man and the Sea")
+/// );
+/// assert_eq!(doc.get_first(views).and_then(|value| value.as_u64()), Some(10));
+///
+/// assert!(DocumentBuilder::new(&schema).text("titel", "typo").build().is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub struct DocumentBuilder<'a> {
+    schema: &'a Schema,
+    doc: Document,
+    error: Option<DocParsingError>,
+}
+
+impl<'a> DocumentBuilder<'a> {
+    /// Creates a new `DocumentBuilder`, resolving field names with `schema`.
+    pub fn new(schema: &'a Schema) -> DocumentBuilder<'a> {
+        DocumentBuilder {
+            schema,
+            doc: Document::default(),
+            error: None,
+        }
+    }
+
+    /// Adds a value to the field named `field_name`.
+    pub fn value<V: Into<Value>>(mut self, field_name: &str, value: V) -> DocumentBuilder<'a> {
+        if self.error.is_some() {
+            return self;
+        }
+        match self.schema.get_field(field_name) {
+            Some(field) => {
+                self.doc.add(FieldValue::new(field, value.into()));
+            }
+            None => self.error = Some(DocParsingError::NoSuchFieldInSchema(field_name.to_string())),
+        }
+        self
+    }
+
+    /// Adds a text value.
+    pub fn text(self, field_name: &str, text: &str) -> DocumentBuilder<'a> {
+        self.value(field_name, text)
+    }
+
+    /// Adds a pre-tokenized text value.
+    pub fn pre_tokenized_text(
+        self,
+        field_name: &str,
+        pre_tokenized_text: PreTokenizedString,
+    ) -> DocumentBuilder<'a> {
+        self.value(field_name, pre_tokenized_text)
+    }
+
+    /// Adds a u64 value.
+    pub fn u64(self, field_name: &str, value: u64) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a i64 value.
+    pub fn i64(self, field_name: &str, value: i64) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a f64 value.
+    pub fn f64(self, field_name: &str, value: f64) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a f32 value.
+    pub fn f32(self, field_name: &str, value: f32) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a duration value.
+    pub fn duration(self, field_name: &str, duration: Duration) -> DocumentBuilder<'a> {
+        self.value(field_name, duration)
+    }
+
+    /// Adds a bool value.
+    pub fn bool(self, field_name: &str, value: bool) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a date value.
+    pub fn date(self, field_name: &str, value: DateTime) -> DocumentBuilder<'a> {
+        self.value(field_name, value)
+    }
+
+    /// Adds a facet value.
+    pub fn facet<F>(self, field_name: &str, path: F) -> DocumentBuilder<'a>
+    where
+        Facet: From<F>,
+    {
+        self.value(field_name, Facet::from(path))
+    }
+
+    /// Adds a bytes value.
+    pub fn bytes<T: Into<Vec<u8>>>(self, field_name: &str, value: T) -> DocumentBuilder<'a> {
+        self.value(field_name, value.into())
+    }
+
+    /// Adds an ip address value.
+    pub fn ip_addr(self, field_name: &str, ip_addr: IpAddr) -> DocumentBuilder<'a> {
+        self.value(field_name, ip_addr)
+    }
+
+    /// Returns the document built, or an error if one of
+    /// the field names is not declared in the schema.
+    pub fn build(self) -> Result<Document, DocParsingError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(self.doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::schema::*;
+
+    #[test]
+    fn test_document_builder() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let views = schema_builder.add_u64_field("views", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let category = schema_builder.add_facet_field("category");
+        let schema = schema_builder.build();
+        let doc = DocumentBuilder::new(&schema)
+            .text("title", "The Old Man and the Sea")
+            .u64("views", 10)
+            .f64("score", 0.5)
+            .facet("category", "/novel/sea")
+            .text("title", "Le Vieil Homme et la Mer")
+            .build()
+            .unwrap();
+        let mut expected_doc = Document::default();
+        expected_doc.add_text(title, "The Old Man and the Sea");
+        expected_doc.add_u64(views, 10);
+        expected_doc.add_f64(score, 0.5);
+        expected_doc.add_facet(category, "/novel/sea");
+        expected_doc.add_text(title, "Le Vieil Homme et la Mer");
+        assert_eq!(doc, expected_doc);
+        assert!(DocumentBuilder::new(&schema).build().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_document_builder_unknown_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let result = DocumentBuilder::new(&schema)
+            .text("title", "The Old Man and the Sea")
+            .u64("viewz", 10)
+            .text("titel", "typo")
+            .build();
+        assert_eq!(
+            result,
+            Err(DocParsingError::NoSuchFieldInSchema("viewz".to_string()))
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use super::*;
+use crate::common::BinarySerializable;
+use crate::common::VInt;
+use crate::tokenizer::PreTokenizedString;
+use crate::DateTime;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Serializes a document field value by field value, directly into a `Write`.
+///
+/// The bytes produced are the same as those of `Document::serialize`, but
+/// the field values are never all held in memory at the same time.
+///
+/// Since the serialized format starts with the number of field values,
+/// this number needs to be given upfront. `finish` returns an error if
+/// the number of field values added does not match it.
+///
+/// ```
+/// use tantivy::schema::{Document, DocumentWriter, Schema, STORED, TEXT};
+/// use tantivy::BinarySerializable;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let year = schema_builder.add_u64_field("year", STORED);
+/// let _schema = schema_builder.build();
+///
+/// let mut buffer = Vec::new();
+/// let mut doc_writer = DocumentWriter::new(&mut buffer, 2)?;
+/// doc_writer.add_text(title, "The Old Man and the Sea")?;
+/// doc_writer.add_u64(year, 1952)?;
+/// doc_writer.finish()?;
+///
+/// let doc = Document::deserialize(&mut &buffer[..])?;
+/// assert_eq!(doc.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DocumentWriter<W: Write> {
+    writer: W,
+    num_field_values: usize,
+    num_written_field_values: usize,
+}
+
+impl<W: Write> DocumentWriter<W> {
+    /// Creates a new `DocumentWriter`, for a document of `num_field_values` field values.
+    pub fn new(mut writer: W, num_field_values: usize) -> io::Result<DocumentWriter<W>> {
+        VInt(num_field_values as u64).serialize(&mut writer)?;
+        Ok(DocumentWriter {
+            writer,
+            num_field_values,
+            num_written_field_values: 0,
+        })
+    }
+
+    fn start_field_value(&mut self, field: Field) -> io::Result<()> {
+        self.count_field_value()?;
+        field.serialize(&mut self.writer)
+    }
+
+    fn count_field_value(&mut self) -> io::Result<()> {
+        if self.num_written_field_values == self.num_field_values {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The document was declared with {} field values.",
+                    self.num_field_values
+                ),
+            ));
+        }
+        self.num_written_field_values += 1;
+        Ok(())
+    }
+
+    /// Add a value
+    pub fn add_value(&mut self, field: Field, value: &Value) -> io::Result<()> {
+        self.start_field_value(field)?;
+        value.serialize(&mut self.writer)
+    }
+
+    /// Add a field value
+    pub fn add(&mut self, field_value: &FieldValue) -> io::Result<()> {
+        self.count_field_value()?;
+        field_value.serialize(&mut self.writer)
+    }
+
+    /// Add a facet
+    pub fn add_facet<F>(&mut self, field: Field, path: F) -> io::Result<()>
+    where
+        Facet: From<F>,
+    {
+        self.add_value(field, &Value::Facet(Facet::from(path)))
+    }
+
+    /// Add a text field.
+    pub fn add_text(&mut self, field: Field, text: &str) -> io::Result<()> {
+        self.start_field_value(field)?;
+        Value::serialize_str(text, &mut self.writer)
+    }
+
+    /// Add a pre-tokenized text field.
+    pub fn add_pre_tokenized_text(
+        &mut self,
+        field: Field,
+        pre_tokenized_text: &PreTokenizedString,
+    ) -> io::Result<()> {
+        self.add_value(field, &Value::PreTokStr(pre_tokenized_text.clone()))
+    }
+
+    /// Add a u64 field
+    pub fn add_u64(&mut self, field: Field, value: u64) -> io::Result<()> {
+        self.add_value(field, &Value::U64(value))
+    }
+
+    /// Add a u128 field
+    pub fn add_u128(&mut self, field: Field, value: u128) -> io::Result<()> {
+        self.add_value(field, &Value::U128(value))
+    }
+
+    /// Add an IP address field
+    ///
+    /// IPv4 addresses are stored in their IPv4-mapped IPv6 form.
+    pub fn add_ip_addr(&mut self, field: Field, ip_addr: IpAddr) -> io::Result<()> {
+        self.add_value(field, &Value::from(ip_addr))
+    }
+
+    /// Add a i64 field
+    pub fn add_i64(&mut self, field: Field, value: i64) -> io::Result<()> {
+        self.add_value(field, &Value::I64(value))
+    }
+
+    /// Add a f64 field
+    pub fn add_f64(&mut self, field: Field, value: f64) -> io::Result<()> {
+        self.add_value(field, &Value::F64(value))
+    }
+
+    /// Add a f32 field
+    pub fn add_f32(&mut self, field: Field, value: f32) -> io::Result<()> {
+        self.add_value(field, &Value::F32(value))
+    }
+
+    /// Add a duration field
+    pub fn add_duration(&mut self, field: Field, duration: Duration) -> io::Result<()> {
+        self.add_value(field, &Value::Duration(duration))
+    }
+
+    /// Add a date field
+    pub fn add_date(&mut self, field: Field, value: &DateTime) -> io::Result<()> {
+        self.add_value(field, &Value::Date(*value))
+    }
+
+    /// Add a bool field
+    pub fn add_bool(&mut self, field: Field, value: bool) -> io::Result<()> {
+        self.add_value(field, &Value::Bool(value))
+    }
+
+    /// Add a null value
+    pub fn add_null(&mut self, field: Field) -> io::Result<()> {
+        self.add_value(field, &Value::Null)
+    }
+
+    /// Add a bytes field
+    pub fn add_bytes(&mut self, field: Field, value: &[u8]) -> io::Result<()> {
+        self.start_field_value(field)?;
+        Value::serialize_bytes(value, &mut self.writer)
+    }
+
+    /// Checks that all of the declared field values were written,
+    /// and returns the underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        if self.num_written_field_values != self.num_field_values {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The document was declared with {} field values, but {} were written.",
+                    self.num_field_values, self.num_written_field_values
+                ),
+            ));
+        }
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DocumentWriter;
+    use crate::common::BinarySerializable;
+    use crate::schema::*;
+
+    #[test]
+    fn test_document_writer_same_as_document() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = Document::default();
+        doc.add_text(title, "hello");
+        doc.add_bytes(payload, vec![1u8, 2u8, 3u8]);
+        doc.add_u64(count, 3u64);
+        doc.add_facet(title, "/a/b");
+        let mut expected = Vec::new();
+        doc.serialize(&mut expected).unwrap();
+
+        let mut buffer = Vec::new();
+        let mut doc_writer = DocumentWriter::new(&mut buffer, 4).unwrap();
+        doc_writer.add_text(title, "hello").unwrap();
+        doc_writer.add_bytes(payload, &[1u8, 2u8, 3u8]).unwrap();
+        doc_writer.add_u64(count, 3u64).unwrap();
+        doc_writer.add_facet(title, "/a/b").unwrap();
+        doc_writer.finish().unwrap();
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_document_writer_wrong_num_field_values() {
+        let mut schema_builder = Schema::builder();
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut buffer = Vec::new();
+        let mut doc_writer = DocumentWriter::new(&mut buffer, 2).unwrap();
+        doc_writer.add_u64(count, 1u64).unwrap();
+        assert!(doc_writer.finish().is_err());
+        let mut buffer = Vec::new();
+        let mut doc_writer = DocumentWriter::new(&mut buffer, 1).unwrap();
+        doc_writer.add_u64(count, 1u64).unwrap();
+        assert!(doc_writer.add_u64(count, 2u64).is_err());
+        assert!(doc_writer.finish().is_ok());
+    }
+}
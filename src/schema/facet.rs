@@ -118,6 +118,38 @@ impl Facet {
             && other_str.as_bytes()[self_str.len()] == FACET_SEP_BYTE
     }
 
+    /// Replaces the `old_prefix` of the facet by `new_prefix`, in place.
+    ///
+    /// For instance, replacing the prefix `/old/root` by `/new` turns
+    /// `/old/root/child` into `/new/child`.
+    ///
+    /// Both prefixes are facets, so the rewritten facet is always made
+    /// of valid path steps.
+    /// Returns `false`, and leaves the facet untouched, if `old_prefix`
+    /// is neither the facet itself nor one of its ancestors.
+    pub fn replace_prefix(&mut self, old_prefix: &Facet, new_prefix: &Facet) -> bool {
+        let rest = if old_prefix.is_root() {
+            self.encoded_str()
+        } else if old_prefix == self || old_prefix.is_prefix_of(self) {
+            // skips the separator following the prefix, if any.
+            let rest = &self.encoded_str()[old_prefix.encoded_str().len()..];
+            rest.get(1..).unwrap_or("")
+        } else {
+            return false;
+        };
+        let mut facet_string =
+            String::with_capacity(new_prefix.encoded_str().len() + rest.len() + 1);
+        facet_string.push_str(new_prefix.encoded_str());
+        if !rest.is_empty() {
+            if !new_prefix.is_root() {
+                facet_string.push(FACET_SEP_CHAR);
+            }
+            facet_string.push_str(rest);
+        }
+        self.0 = facet_string;
+        true
+    }
+
     /// Extract path from the `Facet`.
     pub fn to_path(&self) -> Vec<&str> {
         self.encoded_str().split(|c| c == FACET_SEP_CHAR).collect()
@@ -195,7 +227,7 @@ impl Display for Facet {
 
 fn escape_slashes(s: &str) -> Cow<'_, str> {
     static SLASH_PTN: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\\/]").unwrap());
-    SLASH_PTN.replace_all(s, "\\/")
+    SLASH_PTN.replace_all(s, r"\$0")
 }
 
 impl Serialize for Facet {
@@ -256,6 +288,12 @@ mod tests {
             let facet = Facet::from_path(v.iter());
             assert_eq!(format!("{}", facet), "/first/sec\\/ond/third");
         }
+        {
+            let v = ["first", "sec\\ond", "th\\/ird"];
+            let facet = Facet::from_path(v.iter());
+            assert_eq!(format!("{}", facet), "/first/sec\\\\ond/th\\\\\\/ird");
+            assert_eq!(Facet::from(&facet.to_string()), facet);
+        }
     }
 
     #[test]
@@ -288,4 +326,26 @@ mod tests {
         let facet = Facet::from_path(v.iter());
         assert_eq!(facet.to_path_string(), "/");
     }
+
+    #[test]
+    fn test_replace_prefix() {
+        let old_prefix = Facet::from("/old/root");
+        let new_prefix = Facet::from("/new");
+        let mut facet = Facet::from("/old/root/a/b");
+        assert!(facet.replace_prefix(&old_prefix, &new_prefix));
+        assert_eq!(facet, Facet::from("/new/a/b"));
+        let mut facet = Facet::from("/old/root");
+        assert!(facet.replace_prefix(&old_prefix, &new_prefix));
+        assert_eq!(facet, Facet::from("/new"));
+        let mut facet = Facet::from("/old/rooted/a");
+        assert!(!facet.replace_prefix(&old_prefix, &new_prefix));
+        assert_eq!(facet, Facet::from("/old/rooted/a"));
+        let mut facet = Facet::from("/old/root/a");
+        assert!(facet.replace_prefix(&old_prefix, &Facet::root()));
+        assert_eq!(facet, Facet::from("/a"));
+        let mut facet = Facet::from("/a");
+        assert!(facet.replace_prefix(&Facet::root(), &new_prefix));
+        assert_eq!(facet, Facet::from("/new/a"));
+        assert_eq!(facet.to_path_string(), "/new/a");
+    }
 }
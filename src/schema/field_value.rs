@@ -1,19 +1,50 @@
 use crate::common::BinarySerializable;
+use crate::schema::value::{cmp_f32, f32_hash_bits};
 use crate::schema::Field;
 use crate::schema::Value;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::io::{self, Read, Write};
 
+/// Boost of the field values added without an explicit boost.
+const DEFAULT_BOOST: f32 = 1.0;
+
+// The field id of a boosted field value is serialized with this bit set,
+// and followed by the boost, serialized over 4 bytes.
+const BOOSTED_FIELD_FLAG: u32 = 1u32 << 31;
+
+fn is_default_boost(boost: &f32) -> bool {
+    boost.to_bits() == DEFAULT_BOOST.to_bits()
+}
+
+fn default_boost() -> f32 {
+    DEFAULT_BOOST
+}
+
 /// `FieldValue` holds together a `Field` and its `Value`.
-#[derive(Debug, Clone, Ord, PartialEq, Eq, PartialOrd, serde::Serialize, serde::Deserialize)]
+///
+/// A field value may also carry an index-time boost, which defaults to `1.0`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FieldValue {
     field: Field,
     value: Value,
+    #[serde(default = "default_boost", skip_serializing_if = "is_default_boost")]
+    boost: f32,
 }
 
 impl FieldValue {
     /// Constructor
     pub fn new(field: Field, value: Value) -> FieldValue {
-        FieldValue { field, value }
+        FieldValue::with_boost(field, value, DEFAULT_BOOST)
+    }
+
+    /// Creates a field value with an index-time boost.
+    pub fn with_boost(field: Field, value: Value, boost: f32) -> FieldValue {
+        FieldValue {
+            field,
+            value,
+            boost,
+        }
     }
 
     /// Field accessor
@@ -25,17 +56,153 @@ impl FieldValue {
     pub fn value(&self) -> &Value {
         &self.value
     }
+
+    /// Mutable value accessor
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+
+    /// Boost accessor
+    pub fn boost(&self) -> f32 {
+        self.boost
+    }
+
+    /// Returns the text of the value, provided it is of the `Str` type.
+    ///
+    /// Same as `self.value().as_str()`.
+    pub fn as_str(&self) -> Option<&str> {
+        self.value.as_str()
+    }
+
+    /// Returns the bytes of the value, provided it is of the `Bytes` type.
+    ///
+    /// Same as `self.value().as_bytes()`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.value.as_bytes()
+    }
+
+    /// Returns a reader over the bytes of the value, provided it is
+    /// of the `Bytes` type.
+    ///
+    /// This makes it possible to copy a large blob out incrementally,
+    /// for instance with `std::io::copy`.
+    pub fn bytes_reader(&self) -> Option<impl Read + '_> {
+        self.as_bytes()
+    }
+
+    /// Consumes the `FieldValue` and returns its `Value`.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Serializes the field and the boost of the field value, that is
+    /// everything but its value.
+    ///
+    /// The boost is only serialized if it is not the default boost.
+    pub(crate) fn serialize_header<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if is_default_boost(&self.boost) {
+            self.field.serialize(writer)
+        } else {
+            (self.field.field_id() | BOOSTED_FIELD_FLAG).serialize(writer)?;
+            self.boost.serialize(writer)
+        }
+    }
+
+    /// Deserializes the field and the boost serialized by `serialize_header`.
+    pub(crate) fn deserialize_header<R: Read>(reader: &mut R) -> io::Result<(Field, f32)> {
+        let field_id = u32::deserialize(reader)?;
+        if field_id & BOOSTED_FIELD_FLAG == 0 {
+            return Ok((Field::from_field_id(field_id), DEFAULT_BOOST));
+        }
+        let boost = f32::deserialize(reader)?;
+        Ok((Field::from_field_id(field_id & !BOOSTED_FIELD_FLAG), boost))
+    }
+}
+
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &FieldValue) -> bool {
+        self.field == other.field
+            && self.value == other.value
+            && cmp_f32(self.boost, other.boost) == Ordering::Equal
+    }
+}
+
+impl Eq for FieldValue {}
+
+impl PartialOrd for FieldValue {
+    fn partial_cmp(&self, other: &FieldValue) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FieldValue {
+    fn cmp(&self, other: &FieldValue) -> Ordering {
+        self.field
+            .cmp(&other.field)
+            .then_with(|| self.value.cmp(&other.value))
+            .then_with(|| cmp_f32(self.boost, other.boost))
+    }
+}
+
+impl Hash for FieldValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.field.hash(state);
+        self.value.hash(state);
+        f32_hash_bits(self.boost).hash(state);
+    }
 }
 
 impl BinarySerializable for FieldValue {
     fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        self.field.serialize(writer)?;
+        self.serialize_header(writer)?;
         self.value.serialize(writer)
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let field = Field::deserialize(reader)?;
+        let (field, boost) = FieldValue::deserialize_header(reader)?;
         let value = Value::deserialize(reader)?;
-        Ok(FieldValue::new(field, value))
+        Ok(FieldValue::with_boost(field, value, boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::schema::{Field, FieldValue, Value};
+    use std::io::Read;
+
+    #[test]
+    fn test_field_value_accessors() {
+        let field = Field::from_field_id(1);
+        let text = FieldValue::new(field, Value::from("hello"));
+        let bytes = FieldValue::new(field, Value::from(vec![1u8, 2u8]));
+        assert_eq!(text.as_str(), Some("hello"));
+        assert_eq!(text.as_bytes(), None);
+        assert_eq!(bytes.as_bytes(), Some(&[1u8, 2u8][..]));
+        assert_eq!(bytes.as_str(), None);
+        let number = FieldValue::new(field, Value::U64(3u64));
+        assert_eq!(number.as_str(), None);
+        assert_eq!(number.as_bytes(), None);
+    }
+
+    #[test]
+    fn test_field_value_bytes_reader() {
+        let field = Field::from_field_id(1);
+        let blob: Vec<u8> = (0..1_000u32).map(|i| (i % 251) as u8).collect();
+        let bytes = FieldValue::new(field, Value::from(blob.clone()));
+        let mut reader = bytes.bytes_reader().unwrap();
+        let mut chunk = [0u8; 7];
+        let mut read_bytes = Vec::new();
+        loop {
+            let num_bytes = reader.read(&mut chunk).unwrap();
+            if num_bytes == 0 {
+                break;
+            }
+            assert!(num_bytes <= chunk.len());
+            read_bytes.extend_from_slice(&chunk[..num_bytes]);
+        }
+        assert_eq!(read_bytes, blob);
+        let text = FieldValue::new(field, Value::from("hello"));
+        assert!(text.bytes_reader().is_none());
     }
 }
@@ -103,6 +103,10 @@ let schema = schema_builder.build();
 */
 
 mod document;
+mod document_builder;
+mod document_writer;
+#[cfg(feature = "msgpack")]
+mod msgpack;
 mod facet;
 mod schema;
 mod term;
@@ -116,20 +120,27 @@ mod field;
 mod index_record_option;
 mod int_options;
 mod named_field_document;
+mod schema_aware_document;
 mod text_options;
 mod value;
 
 mod flags;
 
 pub use self::named_field_document::NamedFieldDocument;
+pub use self::schema_aware_document::SchemaAwareDocument;
 pub use self::schema::DocParsingError;
 pub use self::schema::{Schema, SchemaBuilder};
-pub use self::value::Value;
+pub use self::value::{DatePrecision, GeoPointError, Value, ValueType, ValueTypeError};
 
 pub use self::facet::Facet;
 pub(crate) use self::facet::FACET_SEP_BYTE;
 
-pub use self::document::Document;
+pub use self::document::{
+    serialize_documents_parallel, Document, DocumentDeserializationError, DocumentDiff,
+    ValidationError,
+};
+pub use self::document_builder::DocumentBuilder;
+pub use self::document_writer::DocumentWriter;
 pub use self::field::Field;
 pub use self::term::Term;
 
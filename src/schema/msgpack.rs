@@ -0,0 +1,484 @@
+//! MessagePack serialization of documents.
+//!
+//! This format is meant as an interchange format for readers that are not
+//! written in Rust. The native `BinarySerializable` format remains the one
+//! used by the doc store by default, but the store can be given a
+//! `MsgpackCodec` to store the documents in this format.
+
+use crate::schema::{Document, Facet, Field, FieldValue, Value};
+use crate::tokenizer::PreTokenizedString;
+use chrono::{TimeZone, Utc};
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
+use serde::ser::{self, SerializeSeq, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::Ipv6Addr;
+use std::time::Duration;
+
+const STR_TAG: &str = "str";
+const PRE_TOK_STR_TAG: &str = "pre_tok_str";
+const U64_TAG: &str = "u64";
+const I64_TAG: &str = "i64";
+const F64_TAG: &str = "f64";
+const DATE_TAG: &str = "date";
+const FACET_TAG: &str = "facet";
+const BYTES_TAG: &str = "bytes";
+const BOOL_TAG: &str = "bool";
+const NULL_TAG: &str = "null";
+const U128_TAG: &str = "u128";
+const IP_ADDR_TAG: &str = "ip_addr";
+const JSON_OBJECT_TAG: &str = "json_object";
+const ARRAY_TAG: &str = "array";
+const F32_TAG: &str = "f32";
+const DURATION_TAG: &str = "duration";
+const GEO_POINT_TAG: &str = "geo_point";
+const PRECISE_DATE_TAG: &str = "precise_date";
+
+impl Document {
+    /// Serializes the document in the MessagePack format.
+    ///
+    /// A document is serialized as an array of `[field_id, value]` pairs.
+    /// Each value is serialized as a `[type_tag, payload]` pair:
+    ///
+    /// | type tag         | payload                                        |
+    /// |------------------|------------------------------------------------|
+    /// | `"str"`          | str                                            |
+    /// | `"pre_tok_str"`  | map `{"text": str, "tokens": [token map]}`     |
+    /// | `"u64"`          | int                                            |
+    /// | `"i64"`          | int                                            |
+    /// | `"f64"`          | float 64                                       |
+    /// | `"date"`         | int, seconds since the unix epoch              |
+    /// | `"precise_date"` | array `[seconds, nanoseconds]` of int          |
+    /// | `"facet"`        | str, the facet path (e.g. `"/category/book"`)  |
+    /// | `"bytes"`        | bin                                            |
+    /// | `"bool"`         | bool                                           |
+    /// | `"null"`         | nil                                            |
+    /// | `"u128"`         | bin, 16 bytes in big endian                    |
+    /// | `"ip_addr"`      | bin, the 16 bytes of the IPv6 form             |
+    /// | `"json_object"`  | map from str to value                          |
+    /// | `"array"`        | array of values                                |
+    /// | `"f32"`          | float 32                                       |
+    /// | `"duration"`     | bin, 16 bytes of nanoseconds in big endian     |
+    /// | `"geo_point"`    | array `[lat, lng]` of float 64                 |
+    ///
+    /// Dates with a sub-second part are serialized as `"precise_date"`,
+    /// other dates as `"date"`.
+    ///
+    /// The boosts of the field values are not serialized.
+    pub fn serialize_msgpack<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        rmp_serde::encode::write_named(writer, &MsgpackDocument(self))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// Deserializes a document serialized with `serialize_msgpack`.
+    pub fn deserialize_msgpack<R: Read>(reader: R) -> io::Result<Document> {
+        let field_values: Vec<(u32, OwnedMsgpackValue)> = rmp_serde::decode::from_read(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        Ok(field_values
+            .into_iter()
+            .map(|(field_id, value)| FieldValue::new(Field::from_field_id(field_id), value.0))
+            .collect())
+    }
+
+    /// Adds a value of any serializable type to the given bytes field,
+    /// encoded in the MessagePack format.
+    ///
+    /// The value is encoded with `rmp-serde`, already pulled by the `msgpack`
+    /// feature, rather than with `bincode`, to avoid another dependency.
+    /// MessagePack is self-describing and keeps the names of the struct
+    /// fields, so the payload is a bit larger than its `bincode` counterpart,
+    /// but it stays readable from other languages.
+    ///
+    /// The value can be read back with `get_first_serde`.
+    pub fn add_serde<T: Serialize>(&mut self, field: Field, value: &T) -> io::Result<()> {
+        let bytes = rmp_serde::to_vec_named(value)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        self.add_bytes(field, bytes);
+        Ok(())
+    }
+
+    /// Decodes the first value of the given field, added with `add_serde`.
+    ///
+    /// The bytes are expected in the MessagePack format, as written by
+    /// `add_serde`. Values encoded with `bincode` can not be read back.
+    ///
+    /// Returns None if the field has no value, or if its first value
+    /// is not of the `Bytes` type.
+    pub fn get_first_serde<T: DeserializeOwned>(&self, field: Field) -> Option<io::Result<T>> {
+        let bytes = self.get_first(field)?.as_bytes()?;
+        Some(
+            rmp_serde::from_slice(bytes)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string())),
+        )
+    }
+}
+
+struct MsgpackDocument<'a>(&'a Document);
+
+impl<'a> Serialize for MsgpackDocument<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let field_values = self.0.field_values();
+        let mut seq = serializer.serialize_seq(Some(field_values.len()))?;
+        for field_value in field_values {
+            seq.serialize_element(&(
+                field_value.field().field_id(),
+                MsgpackValue(field_value.value()),
+            ))?;
+        }
+        seq.end()
+    }
+}
+
+struct MsgpackValue<'a>(&'a Value);
+
+struct Bin<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bin<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+fn serialize_tagged<S, T>(serializer: S, tag: &str, payload: &T) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize + ?Sized,
+{
+    let mut tuple = serializer.serialize_tuple(2)?;
+    tuple.serialize_element(tag)?;
+    tuple.serialize_element(payload)?;
+    tuple.end()
+}
+
+impl<'a> Serialize for MsgpackValue<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Str(text) => serialize_tagged(serializer, STR_TAG, text),
+            Value::PreTokStr(tok_str) => serialize_tagged(serializer, PRE_TOK_STR_TAG, tok_str),
+            Value::U64(val) => serialize_tagged(serializer, U64_TAG, val),
+            Value::I64(val) => serialize_tagged(serializer, I64_TAG, val),
+            Value::F64(val) => serialize_tagged(serializer, F64_TAG, val),
+            Value::F32(val) => serialize_tagged(serializer, F32_TAG, val),
+            Value::Duration(duration) => serialize_tagged(
+                serializer,
+                DURATION_TAG,
+                &Bin(&duration.as_nanos().to_be_bytes()),
+            ),
+            Value::GeoPoint { lat, lng } => {
+                Value::geo_point(*lat, *lng).map_err(ser::Error::custom)?;
+                serialize_tagged(serializer, GEO_POINT_TAG, &(lat, lng))
+            }
+            Value::Date(date) => match date.timestamp_subsec_nanos() {
+                0 => serialize_tagged(serializer, DATE_TAG, &date.timestamp()),
+                nanos => serialize_tagged(serializer, PRECISE_DATE_TAG, &(date.timestamp(), nanos)),
+            },
+            Value::Facet(facet) => serialize_tagged(serializer, FACET_TAG, &facet.to_string()),
+            Value::Bytes(bytes) => serialize_tagged(serializer, BYTES_TAG, &Bin(bytes)),
+            Value::Bool(val) => serialize_tagged(serializer, BOOL_TAG, val),
+            Value::Null => serialize_tagged(serializer, NULL_TAG, &()),
+            Value::U128(val) => serialize_tagged(serializer, U128_TAG, &Bin(&val.to_be_bytes())),
+            Value::IpAddr(ip_addr) => {
+                serialize_tagged(serializer, IP_ADDR_TAG, &Bin(&ip_addr.octets()))
+            }
+            Value::JsonObject(object) => {
+                let object: BTreeMap<&str, MsgpackValue<'_>> = object
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), MsgpackValue(value)))
+                    .collect();
+                serialize_tagged(serializer, JSON_OBJECT_TAG, &object)
+            }
+            Value::Array(values) => {
+                let values: Vec<MsgpackValue<'_>> = values.iter().map(MsgpackValue).collect();
+                serialize_tagged(serializer, ARRAY_TAG, &values)
+            }
+        }
+    }
+}
+
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ByteBufVisitor;
+
+        impl<'de> Visitor<'de> for ByteBufVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("bytes")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(ByteBuf(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(ByteBufVisitor)
+    }
+}
+
+fn to_16_bytes<E: de::Error>(bytes: ByteBuf) -> Result<[u8; 16], E> {
+    let mut octets = [0u8; 16];
+    if bytes.0.len() != octets.len() {
+        return Err(E::invalid_length(bytes.0.len(), &"16 bytes"));
+    }
+    octets.copy_from_slice(&bytes.0);
+    Ok(octets)
+}
+
+struct OwnedMsgpackValue(Value);
+
+impl<'de> Deserialize<'de> for OwnedMsgpackValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MsgpackValueVisitor;
+
+        impl<'de> Visitor<'de> for MsgpackValueVisitor {
+            type Value = OwnedMsgpackValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a [type_tag, payload] pair")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let tag: String = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let missing_payload = || de::Error::invalid_length(1, &self);
+                let value = match tag.as_str() {
+                    STR_TAG => Value::Str(seq.next_element()?.ok_or_else(missing_payload)?),
+                    PRE_TOK_STR_TAG => Value::PreTokStr(
+                        seq.next_element::<PreTokenizedString>()?
+                            .ok_or_else(missing_payload)?,
+                    ),
+                    U64_TAG => Value::U64(seq.next_element()?.ok_or_else(missing_payload)?),
+                    I64_TAG => Value::I64(seq.next_element()?.ok_or_else(missing_payload)?),
+                    F64_TAG => Value::F64(seq.next_element()?.ok_or_else(missing_payload)?),
+                    F32_TAG => Value::F32(seq.next_element()?.ok_or_else(missing_payload)?),
+                    DURATION_TAG => {
+                        let bytes: ByteBuf = seq.next_element()?.ok_or_else(missing_payload)?;
+                        let nanos = u128::from_be_bytes(to_16_bytes(bytes)?);
+                        let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| {
+                            de::Error::custom(format!("Invalid duration of {} nanoseconds", nanos))
+                        })?;
+                        Value::Duration(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+                    }
+                    GEO_POINT_TAG => {
+                        let (lat, lng): (f64, f64) =
+                            seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::geo_point(lat, lng).map_err(de::Error::custom)?
+                    }
+                    DATE_TAG => {
+                        let timestamp: i64 = seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::Date(Utc.timestamp(timestamp, 0))
+                    }
+                    PRECISE_DATE_TAG => {
+                        let (timestamp, nanos): (i64, u32) =
+                            seq.next_element()?.ok_or_else(missing_payload)?;
+                        let date =
+                            Utc.timestamp_opt(timestamp, nanos)
+                                .single()
+                                .ok_or_else(|| {
+                                    de::Error::custom(format!(
+                                        "Invalid date {}s and {}ns",
+                                        timestamp, nanos
+                                    ))
+                                })?;
+                        Value::Date(date)
+                    }
+                    FACET_TAG => {
+                        let path: String = seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::Facet(Facet::from_text(&path))
+                    }
+                    BYTES_TAG => {
+                        let bytes: ByteBuf = seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::Bytes(bytes.0)
+                    }
+                    BOOL_TAG => Value::Bool(seq.next_element()?.ok_or_else(missing_payload)?),
+                    NULL_TAG => {
+                        seq.next_element::<()>()?.ok_or_else(missing_payload)?;
+                        Value::Null
+                    }
+                    U128_TAG => {
+                        let bytes: ByteBuf = seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::U128(u128::from_be_bytes(to_16_bytes(bytes)?))
+                    }
+                    IP_ADDR_TAG => {
+                        let bytes: ByteBuf = seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::IpAddr(Ipv6Addr::from(to_16_bytes(bytes)?))
+                    }
+                    JSON_OBJECT_TAG => {
+                        let object: BTreeMap<String, OwnedMsgpackValue> =
+                            seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::JsonObject(
+                            object
+                                .into_iter()
+                                .map(|(key, value)| (key, value.0))
+                                .collect(),
+                        )
+                    }
+                    ARRAY_TAG => {
+                        let values: Vec<OwnedMsgpackValue> =
+                            seq.next_element()?.ok_or_else(missing_payload)?;
+                        Value::Array(values.into_iter().map(|value| value.0).collect())
+                    }
+                    _ => {
+                        return Err(de::Error::unknown_variant(
+                            &tag,
+                            &[
+                                STR_TAG,
+                                PRE_TOK_STR_TAG,
+                                U64_TAG,
+                                I64_TAG,
+                                F64_TAG,
+                                DATE_TAG,
+                                FACET_TAG,
+                                BYTES_TAG,
+                                BOOL_TAG,
+                                NULL_TAG,
+                                U128_TAG,
+                                IP_ADDR_TAG,
+                                JSON_OBJECT_TAG,
+                                ARRAY_TAG,
+                            ],
+                        ))
+                    }
+                };
+                Ok(OwnedMsgpackValue(value))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, MsgpackValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::BinarySerializable;
+    use crate::schema::*;
+    use crate::tokenizer::{PreTokenizedString, Token};
+    use crate::DateTime;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_bytes_field("field", STORED);
+        let pre_tokenized_text = PreTokenizedString {
+            text: String::from("A"),
+            tokens: vec![Token {
+                offset_from: 0,
+                offset_to: 1,
+                position: 0,
+                text: String::from("a"),
+                position_length: 1,
+            }],
+        };
+        let mut object = BTreeMap::new();
+        object.insert("a".to_string(), Value::U64(1u64));
+        object.insert(
+            "b".to_string(),
+            Value::Array(vec![Value::from("c"), Value::Null]),
+        );
+        let values = vec![
+            Value::Str("hello".to_string()),
+            Value::PreTokStr(pre_tokenized_text),
+            Value::U64(u64::MAX),
+            Value::I64(-3i64),
+            Value::F64(-1.5f64),
+            Value::F32(0.25f32),
+            Value::Duration(Duration::new(3_600, 500)),
+            Value::GeoPoint {
+                lat: -33.8688,
+                lng: 151.2093,
+            },
+            Value::Date(DateTime::from_str("1960-12-20T00:39:57+00:00").unwrap()),
+            Value::Date(DateTime::from_str("1960-12-20T00:39:57.123456789+00:00").unwrap()),
+            Value::Facet(Facet::from("/a/b c")),
+            Value::Bytes(vec![0u8, 1u8, 255u8]),
+            Value::Bool(true),
+            Value::Null,
+            Value::U128(u128::MAX - 1),
+            Value::from(IpAddr::from_str("192.168.0.1").unwrap()),
+            Value::JsonObject(object),
+            Value::Array(vec![Value::U64(1u64), Value::Array(vec![Value::I64(2i64)])]),
+        ];
+        let doc: Document = values.iter().cloned().map(|value| (field, value)).collect();
+        let mut buffer = Vec::new();
+        doc.serialize_msgpack(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize_msgpack(&buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_all(field).cloned().collect::<Vec<_>>(),
+            values
+        );
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Author {
+        name: String,
+        emails: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Book {
+        title: String,
+        year: u16,
+        authors: Vec<Author>,
+        rating: Option<f64>,
+    }
+
+    #[test]
+    fn test_serde_value() {
+        let mut schema_builder = Schema::builder();
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let title = schema_builder.add_text_field("title", STORED);
+        let book = Book {
+            title: "Dune".to_string(),
+            year: 1965,
+            authors: vec![Author {
+                name: "Frank Herbert".to_string(),
+                emails: vec![],
+            }],
+            rating: Some(4.5),
+        };
+        let mut doc = doc!(title=>"Dune");
+        doc.add_serde(payload, &book).unwrap();
+        assert_eq!(doc.get_first_serde::<Book>(payload).unwrap().unwrap(), book);
+        // the payload survives the doc store serialization.
+        let mut buffer = Vec::new();
+        doc.serialize(&mut buffer).unwrap();
+        let deser_doc = Document::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(
+            deser_doc.get_first_serde::<Book>(payload).unwrap().unwrap(),
+            book
+        );
+        assert!(doc.get_first_serde::<Author>(payload).unwrap().is_err());
+        assert!(doc.get_first_serde::<Book>(title).is_none());
+        assert!(Document::default()
+            .get_first_serde::<Book>(payload)
+            .is_none());
+    }
+
+    #[test]
+    fn test_msgpack_invalid() {
+        assert!(Document::deserialize_msgpack(&b"\x91\x92\x00\x92\xa3abc\x00"[..]).is_err());
+        assert!(Document::deserialize_msgpack(&b"\xff"[..]).is_err());
+    }
+}
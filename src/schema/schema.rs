@@ -307,7 +307,14 @@ impl Schema {
                 };
                 DocParsingError::NotJSON(doc_json_sample)
             })?;
+        self.json_object_to_doc(&json_obj)
+    }
 
+    /// Build a document object from a json-object, already parsed.
+    pub(crate) fn json_object_to_doc(
+        &self,
+        json_obj: &JsonObject<String, JsonValue>,
+    ) -> Result<Document, DocParsingError> {
         let mut doc = Document::default();
         for (field_name, json_value) in json_obj.iter() {
             let field = self
@@ -0,0 +1,108 @@
+use crate::schema::{Document, Schema, Value};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+/// Wrapper serializing a document as a map keyed by the names
+/// of its fields in the schema.
+///
+/// Unlike the derived serialization of `Document`, which emits the
+/// field ids, this makes it possible to serialize a document to any
+/// serde format directly, e.g. `serde_json::to_string(&SchemaAwareDocument(&doc, &schema))`.
+///
+/// Fields are emitted in the order of the schema. A field with a single
+/// value is emitted as that value, a field with several values as a
+/// sequence of values.
+///
+/// The fields of the document must belong to the schema.
+#[derive(Clone, Copy)]
+pub struct SchemaAwareDocument<'a>(pub &'a Document, pub &'a Schema);
+
+impl<'a> Serialize for SchemaAwareDocument<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let SchemaAwareDocument(doc, schema) = *self;
+        let sorted_field_values = doc.get_sorted_field_values();
+        let mut map = serializer.serialize_map(Some(sorted_field_values.len()))?;
+        for (field, field_values) in sorted_field_values {
+            let field_name = schema.get_field_name(field);
+            if let [field_value] = field_values.as_slice() {
+                map.serialize_entry(field_name, field_value.value())?;
+            } else {
+                let values: Vec<&Value> = field_values
+                    .into_iter()
+                    .map(|field_value| field_value.value())
+                    .collect();
+                map.serialize_entry(field_name, &values)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SchemaAwareDocument;
+    use crate::schema::{Document, Facet, Schema, STORED, TEXT};
+    use crate::DateTime;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn test_document() -> (Schema, Document) {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let category = schema_builder.add_facet_field("category");
+        schema_builder.add_text_field("missing", STORED);
+        let mut doc = Document::default();
+        doc.add_u64(count, 3u64);
+        doc.add_text(title, "The Old Man and the Sea");
+        doc.add_u64(count, 1u64);
+        doc.add_f64(score, -0.5f64);
+        doc.add_date(
+            date,
+            &DateTime::from_str("1952-09-01T00:00:00+00:00").unwrap(),
+        );
+        doc.add_facet(category, Facet::from("/books/novel"));
+        (schema_builder.build(), doc)
+    }
+
+    fn expected_json() -> serde_json::Value {
+        json!({
+            "title": "The Old Man and the Sea",
+            "count": [3, 1],
+            "score": -0.5,
+            "date": "1952-09-01T00:00:00+00:00",
+            "category": "/books/novel",
+        })
+    }
+
+    #[test]
+    fn test_schema_aware_document_json() {
+        let (schema, doc) = test_document();
+        let json = serde_json::to_string(&SchemaAwareDocument(&doc, &schema)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"title":"The Old Man and the Sea","count":[3,1],"score":-0.5,"date":"1952-09-01T00:00:00+00:00","category":"/books/novel"}"#
+        );
+        let json_value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json_value, expected_json());
+        let empty_doc = Document::default();
+        assert_eq!(
+            serde_json::to_string(&SchemaAwareDocument(&empty_doc, &schema)).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_schema_aware_document_cbor() {
+        let (schema, doc) = test_document();
+        let cbor = serde_cbor::to_vec(&SchemaAwareDocument(&doc, &schema)).unwrap();
+        let json_value: serde_json::Value = serde_cbor::from_slice(&cbor).unwrap();
+        assert_eq!(json_value, expected_json());
+    }
+}
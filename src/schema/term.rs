@@ -2,7 +2,7 @@ use std::fmt;
 
 use super::Field;
 use crate::common;
-use crate::schema::Facet;
+use crate::schema::{Facet, Value};
 use crate::DateTime;
 use std::str;
 
@@ -59,6 +59,13 @@ impl Term {
     }
 
     /// Creates a `Term` given a facet.
+    ///
+    /// The term is built from the encoded path of the facet, just like the
+    /// terms emitted when indexing a facet field, so that it can be used to
+    /// search or delete the documents with a given facet. Since indexing a
+    /// facet also indexes all of its ancestors, the term matches the documents
+    /// having the facet or any of its descendants. In particular, the term of
+    /// the root facet matches all of the documents having a facet.
     pub fn from_facet(field: Field, facet: &Facet) -> Term {
         let bytes = facet.encoded_str().as_bytes();
         let buffer = Vec::with_capacity(4 + bytes.len());
@@ -103,6 +110,27 @@ impl Term {
         term
     }
 
+    /// Builds the term matching a value of a field, as it is indexed.
+    ///
+    /// Returns `None` if the value cannot be indexed as a single term.
+    /// This is the case for `PreTokStr` values, which are indexed as several tokens,
+    /// and for the values of types that cannot be indexed.
+    ///
+    /// `Str` values are not tokenized: the term only matches the value
+    /// of a field indexed without tokenization, like a `STRING` field.
+    pub fn from_value(field: Field, value: &Value) -> Option<Term> {
+        match value {
+            Value::Str(text) => Some(Term::from_field_text(field, text)),
+            Value::U64(val) => Some(Term::from_field_u64(field, *val)),
+            Value::I64(val) => Some(Term::from_field_i64(field, *val)),
+            Value::F64(val) => Some(Term::from_field_f64(field, *val)),
+            Value::Date(date) => Some(Term::from_field_date(field, date)),
+            Value::Facet(facet) => Some(Term::from_facet(field, facet)),
+            Value::Bytes(bytes) => Some(Term::from_field_bytes(field, bytes)),
+            _ => None,
+        }
+    }
+
     /// Creates a new Term for a given field.
     pub(crate) fn for_field(field: Field) -> Term {
         let mut term = Term(Vec::with_capacity(100));
@@ -243,7 +271,12 @@ impl fmt::Debug for Term {
 #[cfg(test)]
 mod tests {
 
+    use crate::collector::Count;
+    use crate::query::TermQuery;
     use crate::schema::*;
+    use crate::tokenizer::PreTokenizedString;
+    use crate::Index;
+    use chrono::{TimeZone, Utc};
 
     #[test]
     pub fn test_term() {
@@ -272,4 +305,89 @@ mod tests {
             assert_eq!(term.as_slice()[11], (983u64 % 256u64) as u8);
         }
     }
+
+    #[test]
+    fn test_term_from_value() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let views = schema_builder.add_u64_field("views", INDEXED);
+        let delta = schema_builder.add_i64_field("delta", INDEXED);
+        let score = schema_builder.add_f64_field("score", INDEXED);
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let category = schema_builder.add_facet_field("category");
+        let hash = schema_builder.add_bytes_field("hash", INDEXED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::default();
+        doc.add_text(id, "doc-1");
+        doc.add_u64(views, 3u64);
+        doc.add_i64(delta, -3i64);
+        doc.add_f64(score, 0.5f64);
+        doc.add_date(date, &Utc.timestamp(1_000_000, 0));
+        doc.add_facet(category, "/a/b");
+        doc.add_bytes(hash, vec![1u8, 2u8]);
+        index_writer.add_document(doc.clone());
+        index_writer.add_document(doc!(id => "doc-2", views => 4u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for field_value in doc.field_values() {
+            let term = Term::from_value(field_value.field(), field_value.value()).unwrap();
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            assert_eq!(searcher.search(&query, &Count)?, 1);
+        }
+        let pre_tokenized = PreTokenizedString {
+            text: "hello".to_string(),
+            tokens: Vec::new(),
+        };
+        assert!(Term::from_value(body, &Value::PreTokStr(pre_tokenized)).is_none());
+        assert!(Term::from_value(body, &Value::Null).is_none());
+        let term = Term::from_value(views, &Value::U64(3u64)).unwrap();
+        index_writer.delete_term(term);
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_from_facet() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_facet_field("category");
+        let title = schema_builder.add_text_field("title", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        // a facet whose step contains an escaped separator.
+        let escaped_facet = Facet::from_text("/a\\/b");
+        assert_eq!(escaped_facet, Facet::from_path(vec!["a/b"]));
+        index_writer.add_document(doc!(category=>Facet::from("/a/b")));
+        index_writer.add_document(doc!(category=>Facet::from("/a/b/c")));
+        index_writer.add_document(doc!(category=>escaped_facet.clone()));
+        index_writer.add_document(doc!(category=>Facet::from("/d")));
+        index_writer.add_document(doc!(title=>"no facet"));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let count = |facet: &Facet| -> crate::Result<usize> {
+            let query = TermQuery::new(Term::from_facet(category, facet), IndexRecordOption::Basic);
+            reader.searcher().search(&query, &Count)
+        };
+        assert_eq!(count(&Facet::root())?, 4);
+        assert_eq!(count(&Facet::from("/a"))?, 2);
+        assert_eq!(count(&Facet::from("/a/b"))?, 2);
+        assert_eq!(count(&escaped_facet)?, 1);
+        // deleting a facet deletes its descendants, but not the facets
+        // with an escaped separator.
+        index_writer.delete_term(Term::from_facet(category, &Facet::from("/a/b")));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 3);
+        assert_eq!(count(&Facet::from("/a/b"))?, 0);
+        assert_eq!(count(&escaped_facet)?, 1);
+        // deleting the root facet deletes all of the documents having a facet.
+        index_writer.delete_term(Term::from_facet(category, &Facet::root()));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        Ok(())
+    }
 }
@@ -1,9 +1,16 @@
 use crate::schema::Facet;
-use crate::tokenizer::PreTokenizedString;
+use crate::tokenizer::{PreTokenizedString, Token};
 use crate::DateTime;
-use serde::de::Visitor;
+use chrono::Timelike;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{cmp::Ordering, fmt};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
+use std::{cmp::Ordering, fmt, mem};
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
@@ -20,11 +27,214 @@ pub enum Value {
     /// 64-bits Float `f64`
     F64(f64),
     /// Signed 64-bits Date time stamp `date`
+    ///
+    /// Stored dates keep their precision down to the nanosecond. Indexed terms
+    /// and fast fields on the other hand only keep the precision of the second.
     Date(DateTime),
     /// Hierarchical Facet
     Facet(Facet),
     /// Arbitrarily sized byte array
     Bytes(Vec<u8>),
+    /// Boolean value
+    Bool(bool),
+    /// Explicitly absent value
+    Null,
+    /// Unsigned 128-bits Integer `u128`
+    U128(u128),
+    /// IP address, in its IPv6 form.
+    ///
+    /// IPv4 addresses are converted to their IPv4-mapped IPv6 form
+    /// when building the value with `From<IpAddr>`, so that all
+    /// of the addresses share the same ordering.
+    IpAddr(Ipv6Addr),
+    /// Json object, made of nested values.
+    JsonObject(BTreeMap<String, Value>),
+    /// Ordered group of values, possibly nested.
+    Array(Vec<Value>),
+    /// 32-bits Float `f32`
+    ///
+    /// `F32` values are ordered and hashed like `F64` values: all of the `NaN`
+    /// are equal to each other and less than `-∞`, and `0.0` is equal to `-0.0`.
+    F32(f32),
+    /// Time span.
+    ///
+    /// Durations are stored as a number of nanoseconds over 16 bytes, so that
+    /// any `Duration`, up to `Duration::MAX` (more than 584 billion years),
+    /// can be represented.
+    Duration(Duration),
+    /// Geographic point, made of a latitude and a longitude, in degrees.
+    ///
+    /// Use [`Value::geo_point`](#method.geo_point) to build a `GeoPoint` value:
+    /// it checks that the coordinates are within their range.
+    GeoPoint {
+        /// Latitude, within `[-90, 90]`.
+        lat: f64,
+        /// Longitude, within `[-180, 180]`.
+        lng: f64,
+    },
+}
+
+/// Type of a `Value`, with one variant per variant of `Value`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// Type of `Value::Str`
+    Str,
+    /// Type of `Value::PreTokStr`
+    PreTokStr,
+    /// Type of `Value::U64`
+    U64,
+    /// Type of `Value::I64`
+    I64,
+    /// Type of `Value::F64`
+    F64,
+    /// Type of `Value::Date`
+    Date,
+    /// Type of `Value::Facet`
+    Facet,
+    /// Type of `Value::Bytes`
+    Bytes,
+    /// Type of `Value::Bool`
+    Bool,
+    /// Type of `Value::Null`
+    Null,
+    /// Type of `Value::U128`
+    U128,
+    /// Type of `Value::IpAddr`
+    IpAddr,
+    /// Type of `Value::JsonObject`
+    JsonObject,
+    /// Type of `Value::Array`
+    Array,
+    /// Type of `Value::F32`
+    F32,
+    /// Type of `Value::Duration`
+    Duration,
+    /// Type of `Value::GeoPoint`
+    GeoPoint,
+}
+
+impl ValueType {
+    /// Returns the name of the type, e.g. `"u64"`.
+    ///
+    /// Names are stable: they can be relied upon in error messages and logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::Str => "str",
+            ValueType::PreTokStr => "pre-tokenized str",
+            ValueType::U64 => "u64",
+            ValueType::I64 => "i64",
+            ValueType::F64 => "f64",
+            ValueType::F32 => "f32",
+            ValueType::Duration => "duration",
+            ValueType::GeoPoint => "geo point",
+            ValueType::Date => "date",
+            ValueType::Facet => "facet",
+            ValueType::Bytes => "bytes",
+            ValueType::Bool => "bool",
+            ValueType::Null => "null",
+            ValueType::U128 => "u128",
+            ValueType::IpAddr => "ip address",
+            ValueType::JsonObject => "json object",
+            ValueType::Array => "array",
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Precision a date is truncated to before being stored.
+///
+/// Dates are serialized with the most compact encoding able to represent
+/// them exactly: 8 bytes for the dates truncated to the second, the
+/// millisecond or the microsecond, and 12 bytes for the other dates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DatePrecision {
+    /// The date is truncated to the second.
+    Seconds,
+    /// The date is truncated to the millisecond.
+    Milliseconds,
+    /// The date is truncated to the microsecond.
+    Microseconds,
+    /// The date is kept as is, down to the nanosecond.
+    Nanoseconds,
+}
+
+impl DatePrecision {
+    /// Truncates the date to the precision.
+    ///
+    /// Dates are truncated towards the past, including the dates before
+    /// the unix epoch: `1969-12-31T23:59:59.9Z` truncated to the second
+    /// is `1969-12-31T23:59:59Z`.
+    pub fn truncate(self, date: &DateTime) -> DateTime {
+        let nanos = date.nanosecond();
+        let truncated_nanos = match self {
+            DatePrecision::Seconds => nanos - nanos % 1_000_000_000,
+            DatePrecision::Milliseconds => nanos - nanos % 1_000_000,
+            DatePrecision::Microseconds => nanos - nanos % 1_000,
+            DatePrecision::Nanoseconds => nanos,
+        };
+        date.with_nanosecond(truncated_nanos).unwrap_or(*date)
+    }
+}
+
+/// Returns the IPv6 form of the address, mapping IPv4 addresses to IPv6.
+fn ip_addr_to_ipv6(ip_addr: IpAddr) -> Ipv6Addr {
+    match ip_addr {
+        IpAddr::V4(ipv4_addr) => ipv4_addr.to_ipv6_mapped(),
+        IpAddr::V6(ipv6_addr) => ipv6_addr,
+    }
+}
+
+/// Compares two `f64` with a total order:
+/// all of the `NaN` are equal to each other and less than `-∞`.
+fn cmp_f64(left: f64, right: f64) -> Ordering {
+    match (left.is_nan(), right.is_nan()) {
+        (false, false) => left.partial_cmp(&right).unwrap(), // only fail on NaN
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less, // we define NaN as less than -∞
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Returns the bits of a `f64`, to be hashed consistently with `cmp_f64`.
+///
+/// `0.0` and `-0.0` are both hashed as `0.0`, and all `NaN` as the canonical `NaN`.
+fn f64_hash_bits(val: f64) -> u64 {
+    if val == 0.0 {
+        0.0f64.to_bits()
+    } else if val.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        val.to_bits()
+    }
+}
+
+/// Compares two `f32` with the same total order as `Value::F64`:
+/// all of the `NaN` are equal to each other and less than `-∞`.
+pub(crate) fn cmp_f32(left: f32, right: f32) -> Ordering {
+    match (left.is_nan(), right.is_nan()) {
+        (false, false) => left.partial_cmp(&right).unwrap(),
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Returns the bits of a `f32`, to be hashed consistently with `cmp_f32`.
+///
+/// `0.0` and `-0.0` are both hashed as `0.0`, and all `NaN` as the canonical `NaN`.
+pub(crate) fn f32_hash_bits(val: f32) -> u32 {
+    if val == 0.0 {
+        0.0f32.to_bits()
+    } else if val.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        val.to_bits()
+    }
 }
 
 impl Eq for Value {}
@@ -43,14 +253,25 @@ impl Ord for Value {
             (Value::Date(l), Value::Date(r)) => l.cmp(r),
             (Value::Facet(l), Value::Facet(r)) => l.cmp(r),
             (Value::Bytes(l), Value::Bytes(r)) => l.cmp(r),
-            (Value::F64(l), Value::F64(r)) => {
-                match (l.is_nan(), r.is_nan()) {
-                    (false, false) => l.partial_cmp(r).unwrap(), // only fail on NaN
-                    (true, true) => Ordering::Equal,
-                    (true, false) => Ordering::Less, // we define NaN as less than -∞
-                    (false, true) => Ordering::Greater,
-                }
-            }
+            (Value::Bool(l), Value::Bool(r)) => l.cmp(r),
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::U128(l), Value::U128(r)) => l.cmp(r),
+            (Value::IpAddr(l), Value::IpAddr(r)) => l.cmp(r),
+            (Value::JsonObject(l), Value::JsonObject(r)) => l.cmp(r),
+            (Value::Array(l), Value::Array(r)) => l.cmp(r),
+            (Value::F64(l), Value::F64(r)) => cmp_f64(*l, *r),
+            (Value::F32(l), Value::F32(r)) => cmp_f32(*l, *r),
+            (Value::Duration(l), Value::Duration(r)) => l.cmp(r),
+            (
+                Value::GeoPoint {
+                    lat: l_lat,
+                    lng: l_lng,
+                },
+                Value::GeoPoint {
+                    lat: r_lat,
+                    lng: r_lng,
+                },
+            ) => cmp_f64(*l_lat, *r_lat).then_with(|| cmp_f64(*l_lng, *r_lng)),
             (Value::Str(_), _) => Ordering::Less,
             (_, Value::Str(_)) => Ordering::Greater,
             (Value::PreTokStr(_), _) => Ordering::Less,
@@ -61,10 +282,112 @@ impl Ord for Value {
             (_, Value::I64(_)) => Ordering::Greater,
             (Value::F64(_), _) => Ordering::Less,
             (_, Value::F64(_)) => Ordering::Greater,
+            (Value::F32(_), _) => Ordering::Less,
+            (_, Value::F32(_)) => Ordering::Greater,
+            (Value::Duration(_), _) => Ordering::Less,
+            (_, Value::Duration(_)) => Ordering::Greater,
+            (Value::GeoPoint { .. }, _) => Ordering::Less,
+            (_, Value::GeoPoint { .. }) => Ordering::Greater,
             (Value::Date(_), _) => Ordering::Less,
             (_, Value::Date(_)) => Ordering::Greater,
             (Value::Facet(_), _) => Ordering::Less,
             (_, Value::Facet(_)) => Ordering::Greater,
+            (Value::Bytes(_), _) => Ordering::Less,
+            (_, Value::Bytes(_)) => Ordering::Greater,
+            (Value::Bool(_), _) => Ordering::Less,
+            (_, Value::Bool(_)) => Ordering::Greater,
+            (Value::Null, _) => Ordering::Less,
+            (_, Value::Null) => Ordering::Greater,
+            (Value::U128(_), _) => Ordering::Less,
+            (_, Value::U128(_)) => Ordering::Greater,
+            (Value::IpAddr(_), _) => Ordering::Less,
+            (_, Value::IpAddr(_)) => Ordering::Greater,
+            (Value::JsonObject(_), _) => Ordering::Less,
+            (_, Value::JsonObject(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Value::Str(text) => text.hash(state),
+            Value::PreTokStr(tok_str) => tok_str.hash(state),
+            Value::U64(val) => val.hash(state),
+            Value::I64(val) => val.hash(state),
+            // `f64` is not `Hash`, so we hash its bit pattern.
+            Value::F64(val) => f64_hash_bits(*val).hash(state),
+            Value::F32(val) => f32_hash_bits(*val).hash(state),
+            Value::Duration(duration) => duration.hash(state),
+            Value::GeoPoint { lat, lng } => {
+                f64_hash_bits(*lat).hash(state);
+                f64_hash_bits(*lng).hash(state);
+            }
+            Value::Date(date) => date.hash(state),
+            Value::Facet(facet) => facet.hash(state),
+            Value::Bytes(bytes) => bytes.hash(state),
+            Value::Bool(val) => val.hash(state),
+            Value::Null => {}
+            Value::U128(val) => val.hash(state),
+            Value::IpAddr(ip_addr) => ip_addr.hash(state),
+            Value::JsonObject(object) => object.hash(state),
+            Value::Array(values) => values.hash(state),
+        }
+    }
+}
+
+/// Maximum number of bytes rendered when displaying a `Bytes` value.
+const DISPLAY_MAX_NUM_BYTES: usize = 32;
+
+/// Human-readable rendering of a value.
+///
+/// Texts are rendered raw, dates as RFC3339 and bytes as base64.
+/// Only the first 32 bytes of a `Bytes` value are rendered, followed by `…`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(text) => write!(f, "{}", text),
+            Value::PreTokStr(tok_str) => write!(f, "{}", tok_str.text),
+            Value::U64(val) => write!(f, "{}", val),
+            Value::I64(val) => write!(f, "{}", val),
+            Value::F64(val) => write!(f, "{}", val),
+            Value::F32(val) => write!(f, "{}", val),
+            Value::Duration(duration) => write!(f, "{:?}", duration),
+            Value::GeoPoint { lat, lng } => write!(f, "({}, {})", lat, lng),
+            Value::Date(date) => write!(f, "{}", date.to_rfc3339()),
+            Value::Facet(facet) => write!(f, "{}", facet),
+            Value::Bytes(bytes) => {
+                if bytes.len() > DISPLAY_MAX_NUM_BYTES {
+                    write!(f, "{}…", base64::encode(&bytes[..DISPLAY_MAX_NUM_BYTES]))
+                } else {
+                    write!(f, "{}", base64::encode(bytes))
+                }
+            }
+            Value::Bool(val) => write!(f, "{}", val),
+            Value::Null => write!(f, "null"),
+            Value::U128(val) => write!(f, "{}", val),
+            Value::IpAddr(ip_addr) => write!(f, "{}", ip_addr),
+            Value::JsonObject(object) => {
+                write!(f, "{{")?;
+                for (ord, (key, value)) in object.iter().enumerate() {
+                    if ord > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (ord, value) in values.iter().enumerate() {
+                    if ord > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
         }
     }
 }
@@ -80,9 +403,23 @@ impl Serialize for Value {
             Value::U64(u) => serializer.serialize_u64(u),
             Value::I64(u) => serializer.serialize_i64(u),
             Value::F64(u) => serializer.serialize_f64(u),
+            Value::F32(u) => serializer.serialize_f32(u),
+            Value::Duration(ref duration) => duration.serialize(serializer),
+            Value::GeoPoint { lat, lng } => {
+                let mut geo_point = serializer.serialize_struct("GeoPoint", 2)?;
+                geo_point.serialize_field("lat", &lat)?;
+                geo_point.serialize_field("lng", &lng)?;
+                geo_point.end()
+            }
             Value::Date(ref date) => serializer.serialize_str(&date.to_rfc3339()),
             Value::Facet(ref facet) => facet.serialize(serializer),
             Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            Value::Bool(b) => serializer.serialize_bool(b),
+            Value::Null => serializer.serialize_unit(),
+            Value::U128(u) => serializer.serialize_u128(u),
+            Value::IpAddr(ip_addr) => serializer.collect_str(&ip_addr),
+            Value::JsonObject(ref object) => object.serialize(serializer),
+            Value::Array(ref values) => values.serialize(serializer),
         }
     }
 }
@@ -101,6 +438,14 @@ impl<'de> Deserialize<'de> for Value {
                 formatter.write_str("a string or u32")
             }
 
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(Value::Bool(v))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(Value::Null)
+            }
+
             fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
                 Ok(Value::I64(v))
             }
@@ -109,6 +454,10 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::U64(v))
             }
 
+            fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+                Ok(Value::U128(v))
+            }
+
             fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
                 Ok(Value::F64(v))
             }
@@ -120,6 +469,28 @@ impl<'de> Deserialize<'de> for Value {
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
                 Ok(Value::Str(v))
             }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut object = BTreeMap::new();
+                while let Some((key, value)) = map.next_entry()? {
+                    object.insert(key, value);
+                }
+                Ok(Value::JsonObject(object))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Value::Array(values))
+            }
         }
 
         deserializer.deserialize_any(ValueVisitor)
@@ -127,9 +498,89 @@ impl<'de> Deserialize<'de> for Value {
 }
 
 impl Value {
+    /// Creates a `GeoPoint` value.
+    ///
+    /// Returns an error if `lat` is not within `[-90, 90]` or `lng` is not within
+    /// `[-180, 180]`, including if either of them is `NaN`. Out of range coordinates
+    /// are rejected rather than clamped, as clamping would silently move the point.
+    ///
+    /// Coordinates of `-0.0` are normalized to `0.0`.
+    pub fn geo_point(lat: f64, lng: f64) -> Result<Value, GeoPointError> {
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+            return Err(GeoPointError { lat, lng });
+        }
+        let normalize = |coord: f64| if coord == 0.0 { 0.0 } else { coord };
+        Ok(Value::GeoPoint {
+            lat: normalize(lat),
+            lng: normalize(lng),
+        })
+    }
+
+    /// Returns the type of the value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::Str(_) => ValueType::Str,
+            Value::PreTokStr(_) => ValueType::PreTokStr,
+            Value::U64(_) => ValueType::U64,
+            Value::I64(_) => ValueType::I64,
+            Value::F64(_) => ValueType::F64,
+            Value::F32(_) => ValueType::F32,
+            Value::Duration(_) => ValueType::Duration,
+            Value::GeoPoint { .. } => ValueType::GeoPoint,
+            Value::Date(_) => ValueType::Date,
+            Value::Facet(_) => ValueType::Facet,
+            Value::Bytes(_) => ValueType::Bytes,
+            Value::Bool(_) => ValueType::Bool,
+            Value::Null => ValueType::Null,
+            Value::U128(_) => ValueType::U128,
+            Value::IpAddr(_) => ValueType::IpAddr,
+            Value::JsonObject(_) => ValueType::JsonObject,
+            Value::Array(_) => ValueType::Array,
+        }
+    }
+
+    /// Returns the name of the type of the value, e.g. `"u64"`.
+    ///
+    /// See [`ValueType::name`](./enum.ValueType.html#method.name).
+    pub fn type_name(&self) -> &'static str {
+        self.value_type().name()
+    }
+
+    /// Returns an estimate of the number of bytes held by the value.
+    ///
+    /// Texts, bytes and facets count for their length, and numeric
+    /// values for their fixed size. See `Document::byte_size`.
+    pub(crate) fn byte_size(&self) -> usize {
+        match self {
+            Value::Str(text) => text.len(),
+            Value::PreTokStr(tok_str) => {
+                tok_str.text.len()
+                    + tok_str
+                        .tokens
+                        .iter()
+                        .map(|token| mem::size_of::<Token>() + token.text.len())
+                        .sum::<usize>()
+            }
+            Value::U64(_) | Value::I64(_) | Value::F64(_) | Value::Date(_) => 8,
+            Value::F32(_) => 4,
+            Value::Duration(_) | Value::GeoPoint { .. } => 16,
+            Value::Facet(facet) => facet.encoded_str().len(),
+            Value::Bytes(bytes) => bytes.len(),
+            Value::Bool(_) => 1,
+            Value::Null => 0,
+            Value::U128(_) | Value::IpAddr(_) => 16,
+            Value::JsonObject(object) => object
+                .iter()
+                .map(|(key, value)| key.len() + value.byte_size())
+                .sum(),
+            Value::Array(values) => values.iter().map(Value::byte_size).sum(),
+        }
+    }
+
     /// Returns the text value, provided the value is of the `Str` type.
-    /// (Returns None if the value is not of the `Str` type).
-    pub fn text(&self) -> Option<&str> {
+    ///
+    /// Returns None if the value is not of the `Str` type.
+    pub fn as_str(&self) -> Option<&str> {
         if let Value::Str(text) = self {
             Some(text)
         } else {
@@ -140,7 +591,7 @@ impl Value {
     /// Returns the tokenized text, provided the value is of the `PreTokStr` type.
     ///
     /// Returns None if the value is not of the `PreTokStr` type.
-    pub fn tokenized_text(&self) -> Option<&PreTokenizedString> {
+    pub fn as_pre_tokenized(&self) -> Option<&PreTokenizedString> {
         if let Value::PreTokStr(tokenized_text) = self {
             Some(tokenized_text)
         } else {
@@ -151,7 +602,7 @@ impl Value {
     /// Returns the u64-value, provided the value is of the `U64` type.
     ///
     /// Returns None if the value is not of the `U64` type.
-    pub fn u64_value(&self) -> Option<u64> {
+    pub fn as_u64(&self) -> Option<u64> {
         if let Value::U64(val) = self {
             Some(*val)
         } else {
@@ -161,8 +612,8 @@ impl Value {
 
     /// Returns the i64-value, provided the value is of the `I64` type.
     ///
-    /// Return None if the value is not of type `I64`.
-    pub fn i64_value(&self) -> Option<i64> {
+    /// Returns None if the value is not of type `I64`.
+    pub fn as_i64(&self) -> Option<i64> {
         if let Value::I64(val) = self {
             Some(*val)
         } else {
@@ -172,8 +623,8 @@ impl Value {
 
     /// Returns the f64-value, provided the value is of the `F64` type.
     ///
-    /// Return None if the value is not of type `F64`.
-    pub fn f64_value(&self) -> Option<f64> {
+    /// Returns None if the value is not of type `F64`.
+    pub fn as_f64(&self) -> Option<f64> {
         if let Value::F64(value) = self {
             Some(*value)
         } else {
@@ -181,10 +632,44 @@ impl Value {
         }
     }
 
+    /// Returns the f32-value, provided the value is of the `F32` type.
+    ///
+    /// Returns None if the value is not of type `F32`.
+    pub fn as_f32(&self) -> Option<f32> {
+        if let Value::F32(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the duration, provided the value is of the `Duration` type.
+    ///
+    /// Returns None if the value is not of type `Duration`.
+    pub fn as_duration(&self) -> Option<Duration> {
+        if let Value::Duration(duration) = self {
+            Some(*duration)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `(latitude, longitude)` pair, provided the value is
+    /// of the `GeoPoint` type.
+    ///
+    /// Returns None if the value is not of type `GeoPoint`.
+    pub fn as_geo_point(&self) -> Option<(f64, f64)> {
+        if let Value::GeoPoint { lat, lng } = self {
+            Some((*lat, *lng))
+        } else {
+            None
+        }
+    }
+
     /// Returns the Date-value, provided the value is of the `Date` type.
     ///
     /// Returns None if the value is not of type `Date`.
-    pub fn date_value(&self) -> Option<&DateTime> {
+    pub fn as_date(&self) -> Option<&DateTime> {
         if let Value::Date(date) = self {
             Some(date)
         } else {
@@ -192,16 +677,132 @@ impl Value {
         }
     }
 
+    /// Returns the Facet-value, provided the value is of the `Facet` type.
+    ///
+    /// Returns None if the value is not of type `Facet`.
+    pub fn as_facet(&self) -> Option<&Facet> {
+        if let Value::Facet(facet) = self {
+            Some(facet)
+        } else {
+            None
+        }
+    }
+
     /// Returns the Bytes-value, provided the value is of the `Bytes` type.
     ///
     /// Returns None if the value is not of type `Bytes`.
-    pub fn bytes_value(&self) -> Option<&[u8]> {
+    pub fn as_bytes(&self) -> Option<&[u8]> {
         if let Value::Bytes(bytes) = self {
             Some(bytes)
         } else {
             None
         }
     }
+
+    /// Returns the bool-value, provided the value is of the `Bool` type.
+    ///
+    /// Returns None if the value is not of type `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        if let Value::Bool(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the u128-value, provided the value is of the `U128` type.
+    ///
+    /// Returns None if the value is not of type `U128`.
+    pub fn as_u128(&self) -> Option<u128> {
+        if let Value::U128(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the IP address, in its IPv6 form, provided the value is of the `IpAddr` type.
+    ///
+    /// Returns None if the value is not of type `IpAddr`.
+    pub fn as_ip_addr(&self) -> Option<Ipv6Addr> {
+        if let Value::IpAddr(ip_addr) = self {
+            Some(*ip_addr)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the json object, provided the value is of the `JsonObject` type.
+    ///
+    /// Returns None if the value is not of type `JsonObject`.
+    pub fn as_json_object(&self) -> Option<&BTreeMap<String, Value>> {
+        if let Value::JsonObject(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the values of the array, provided the value is of the `Array` type.
+    ///
+    /// Returns None if the value is not of type `Array`.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        if let Value::Array(values) = self {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true iff the value is of the `Null` type.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Same as [`as_str`](#method.as_str).
+    pub fn text(&self) -> Option<&str> {
+        self.as_str()
+    }
+
+    /// Same as [`as_pre_tokenized`](#method.as_pre_tokenized).
+    pub fn tokenized_text(&self) -> Option<&PreTokenizedString> {
+        self.as_pre_tokenized()
+    }
+
+    /// Same as [`as_u64`](#method.as_u64).
+    pub fn u64_value(&self) -> Option<u64> {
+        self.as_u64()
+    }
+
+    /// Same as [`as_i64`](#method.as_i64).
+    pub fn i64_value(&self) -> Option<i64> {
+        self.as_i64()
+    }
+
+    /// Same as [`as_f64`](#method.as_f64).
+    pub fn f64_value(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    /// Same as [`as_date`](#method.as_date).
+    pub fn date_value(&self) -> Option<&DateTime> {
+        self.as_date()
+    }
+
+    /// Same as [`as_bytes`](#method.as_bytes).
+    pub fn bytes_value(&self) -> Option<&[u8]> {
+        self.as_bytes()
+    }
+
+    /// Same as [`as_bool`](#method.as_bool).
+    pub fn bool_value(&self) -> Option<bool> {
+        self.as_bool()
+    }
+
+    /// Same as [`as_u128`](#method.as_u128).
+    pub fn u128_value(&self) -> Option<u128> {
+        self.as_u128()
+    }
 }
 
 impl From<String> for Value {
@@ -216,6 +817,12 @@ impl From<u64> for Value {
     }
 }
 
+impl From<u128> for Value {
+    fn from(v: u128) -> Value {
+        Value::U128(v)
+    }
+}
+
 impl From<i64> for Value {
     fn from(v: i64) -> Value {
         Value::I64(v)
@@ -228,6 +835,36 @@ impl From<f64> for Value {
     }
 }
 
+impl From<f32> for Value {
+    fn from(v: f32) -> Value {
+        Value::F32(v)
+    }
+}
+
+impl From<Duration> for Value {
+    fn from(duration: Duration) -> Value {
+        Value::Duration(duration)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Bool(v)
+    }
+}
+
+impl From<IpAddr> for Value {
+    fn from(ip_addr: IpAddr) -> Value {
+        Value::IpAddr(ip_addr_to_ipv6(ip_addr))
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(object: BTreeMap<String, Value>) -> Value {
+        Value::JsonObject(object)
+    }
+}
+
 impl From<crate::DateTime> for Value {
     fn from(date_time: crate::DateTime) -> Value {
         Value::Date(date_time)
@@ -264,13 +901,80 @@ impl From<PreTokenizedString> for Value {
     }
 }
 
+/// Error returned by `Value::geo_point` when the coordinates are
+/// not a valid latitude and longitude.
+#[derive(Debug, Error, PartialEq)]
+#[error("Invalid geo point ({lat}, {lng}): the latitude must be within [-90, 90] and the longitude within [-180, 180]")]
+pub struct GeoPointError {
+    /// Rejected latitude
+    pub lat: f64,
+    /// Rejected longitude
+    pub lng: f64,
+}
+
+/// Error returned when converting a `Value` into a value
+/// of a type that does not match its variant.
+#[derive(Debug, Error, PartialEq)]
+#[error("Expected a value of type {expected}, got {actual}")]
+pub struct ValueTypeError {
+    /// Type of the conversion target
+    pub expected: ValueType,
+    /// Type of the converted value
+    pub actual: &'static str,
+}
+
+macro_rules! impl_try_from_value {
+    ($typ:ty, $variant:ident, $expected:expr) => {
+        impl TryFrom<Value> for $typ {
+            type Error = ValueTypeError;
+
+            fn try_from(value: Value) -> Result<$typ, ValueTypeError> {
+                match value {
+                    Value::$variant(val) => Ok(val),
+                    _ => Err(ValueTypeError {
+                        expected: $expected,
+                        actual: value.type_name(),
+                    }),
+                }
+            }
+        }
+
+        impl<'a> TryFrom<&'a Value> for $typ {
+            type Error = ValueTypeError;
+
+            fn try_from(value: &'a Value) -> Result<$typ, ValueTypeError> {
+                match value {
+                    Value::$variant(val) => Ok(val.clone()),
+                    _ => Err(ValueTypeError {
+                        expected: $expected,
+                        actual: value.type_name(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_value!(u64, U64, ValueType::U64);
+impl_try_from_value!(i64, I64, ValueType::I64);
+impl_try_from_value!(f64, F64, ValueType::F64);
+impl_try_from_value!(String, Str, ValueType::Str);
+impl_try_from_value!(Vec<u8>, Bytes, ValueType::Bytes);
+impl_try_from_value!(DateTime, Date, ValueType::Date);
+
 mod binary_serialize {
     use super::Value;
-    use crate::common::{f64_to_u64, u64_to_f64, BinarySerializable};
+    use crate::common::{f64_to_u64, u64_to_f64, BinarySerializable, VInt};
     use crate::schema::Facet;
+    use crate::store::{compress, decompress};
     use crate::tokenizer::PreTokenizedString;
+    use crate::DateTime;
     use chrono::{TimeZone, Utc};
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
     use std::io::{self, Read, Write};
+    use std::net::Ipv6Addr;
+    use std::time::Duration;
 
     const TEXT_CODE: u8 = 0;
     const U64_CODE: u8 = 1;
@@ -280,6 +984,67 @@ mod binary_serialize {
     const DATE_CODE: u8 = 5;
     const F64_CODE: u8 = 6;
     const EXT_CODE: u8 = 7;
+    const BOOL_CODE: u8 = 8;
+    const NULL_CODE: u8 = 9;
+    // u128 values are serialized over 16 bytes, in little endian.
+    const U128_CODE: u8 = 10;
+    // ip addresses are serialized as the 16 bytes of their IPv6 form.
+    const IP_ADDR_CODE: u8 = 11;
+    // json objects are serialized as their number of entries,
+    // followed by each of their (key, value) pairs.
+    const JSON_OBJECT_CODE: u8 = 12;
+    // arrays are serialized as their number of values, followed by each of the values.
+    const ARRAY_CODE: u8 = 13;
+    // bytes compressed with the compression of the doc store, serialized
+    // as the length of the compressed bytes, followed by the compressed bytes.
+    const COMPRESSED_BYTES_CODE: u8 = 14;
+    // f32 values are serialized over 4 bytes.
+    const F32_CODE: u8 = 15;
+    // durations are serialized as their number of nanoseconds, over 16 bytes.
+    const DURATION_CODE: u8 = 16;
+    // geo points are serialized as their latitude and their longitude,
+    // each over 8 bytes.
+    const GEO_POINT_CODE: u8 = 17;
+    // dates with a sub-second part are serialized as their timestamp in seconds,
+    // followed by their nanoseconds over 4 bytes. Other dates are serialized
+    // with `DATE_CODE`, as their timestamp in seconds only.
+    const PRECISE_DATE_CODE: u8 = 18;
+    // dates with a whole number of milliseconds (resp. microseconds) are
+    // serialized as their timestamp in milliseconds (resp. microseconds), over 8 bytes.
+    const MILLIS_DATE_CODE: u8 = 20;
+    const MICROS_DATE_CODE: u8 = 21;
+
+    const NANOS_PER_MILLI: u32 = 1_000_000;
+    const NANOS_PER_MICRO: u32 = 1_000;
+
+    /// Returns the timestamp of the date in the given unit, provided the
+    /// date holds a whole number of units and the timestamp fits in an `i64`.
+    fn timestamp_in_units(date: &DateTime, nanos_per_unit: u32) -> Option<i64> {
+        let nanos = date.timestamp_subsec_nanos();
+        // leap seconds have more than 1_000_000_000 nanoseconds.
+        if nanos % nanos_per_unit != 0 || nanos >= 1_000_000_000 {
+            return None;
+        }
+        let units_per_sec = i64::from(1_000_000_000 / nanos_per_unit);
+        date.timestamp()
+            .checked_mul(units_per_sec)?
+            .checked_add(i64::from(nanos / nanos_per_unit))
+    }
+
+    fn date_from_units(timestamp: i64, nanos_per_unit: u32) -> io::Result<Value> {
+        let units_per_sec = i64::from(1_000_000_000 / nanos_per_unit);
+        let secs = timestamp.div_euclid(units_per_sec);
+        let nanos = timestamp.rem_euclid(units_per_sec) as u32 * nanos_per_unit;
+        Utc.timestamp_opt(secs, nanos)
+            .single()
+            .map(Value::Date)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid date {}s and {}ns", secs, nanos),
+                )
+            })
+    }
 
     // extended types
 
@@ -316,9 +1081,40 @@ mod binary_serialize {
                     F64_CODE.serialize(writer)?;
                     f64_to_u64(*val).serialize(writer)
                 }
+                Value::F32(val) => {
+                    F32_CODE.serialize(writer)?;
+                    val.serialize(writer)
+                }
+                Value::Duration(duration) => {
+                    DURATION_CODE.serialize(writer)?;
+                    duration.as_nanos().serialize(writer)
+                }
+                Value::GeoPoint { lat, lng } => {
+                    // The variant can be built with out of range coordinates:
+                    // they are rejected here, as they could not be read back.
+                    Value::geo_point(lat, lng).map_err(|err| {
+                        io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+                    })?;
+                    GEO_POINT_CODE.serialize(writer)?;
+                    lat.serialize(writer)?;
+                    lng.serialize(writer)
+                }
                 Value::Date(ref val) => {
-                    DATE_CODE.serialize(writer)?;
-                    val.timestamp().serialize(writer)
+                    let nanos = val.timestamp_subsec_nanos();
+                    if nanos == 0 {
+                        DATE_CODE.serialize(writer)?;
+                        val.timestamp().serialize(writer)
+                    } else if let Some(millis) = timestamp_in_units(val, NANOS_PER_MILLI) {
+                        MILLIS_DATE_CODE.serialize(writer)?;
+                        millis.serialize(writer)
+                    } else if let Some(micros) = timestamp_in_units(val, NANOS_PER_MICRO) {
+                        MICROS_DATE_CODE.serialize(writer)?;
+                        micros.serialize(writer)
+                    } else {
+                        PRECISE_DATE_CODE.serialize(writer)?;
+                        val.timestamp().serialize(writer)?;
+                        nanos.serialize(writer)
+                    }
                 }
                 Value::Facet(ref facet) => {
                     HIERARCHICAL_FACET_CODE.serialize(writer)?;
@@ -328,6 +1124,36 @@ mod binary_serialize {
                     BYTES_CODE.serialize(writer)?;
                     bytes.serialize(writer)
                 }
+                Value::Bool(val) => {
+                    BOOL_CODE.serialize(writer)?;
+                    (val as u8).serialize(writer)
+                }
+                Value::Null => NULL_CODE.serialize(writer),
+                Value::U128(ref val) => {
+                    U128_CODE.serialize(writer)?;
+                    val.serialize(writer)
+                }
+                Value::IpAddr(ip_addr) => {
+                    IP_ADDR_CODE.serialize(writer)?;
+                    writer.write_all(&ip_addr.octets())
+                }
+                Value::JsonObject(ref object) => {
+                    JSON_OBJECT_CODE.serialize(writer)?;
+                    VInt(object.len() as u64).serialize(writer)?;
+                    for (key, value) in object {
+                        key.serialize(writer)?;
+                        value.serialize(writer)?;
+                    }
+                    Ok(())
+                }
+                Value::Array(ref values) => {
+                    ARRAY_CODE.serialize(writer)?;
+                    VInt(values.len() as u64).serialize(writer)?;
+                    for value in values {
+                        value.serialize(writer)?;
+                    }
+                    Ok(())
+                }
             }
         }
         fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
@@ -349,12 +1175,89 @@ mod binary_serialize {
                     let value = u64_to_f64(u64::deserialize(reader)?);
                     Ok(Value::F64(value))
                 }
+                F32_CODE => Ok(Value::F32(f32::deserialize(reader)?)),
+                DURATION_CODE => {
+                    let nanos = u128::deserialize(reader)?;
+                    duration_from_nanos(nanos)
+                        .map(Value::Duration)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("Invalid duration of {} nanoseconds", nanos),
+                            )
+                        })
+                }
+                GEO_POINT_CODE => {
+                    let lat = f64::deserialize(reader)?;
+                    let lng = f64::deserialize(reader)?;
+                    Value::geo_point(lat, lng)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+                }
                 DATE_CODE => {
                     let timestamp = i64::deserialize(reader)?;
                     Ok(Value::Date(Utc.timestamp(timestamp, 0)))
                 }
+                MILLIS_DATE_CODE => {
+                    let millis = i64::deserialize(reader)?;
+                    date_from_units(millis, NANOS_PER_MILLI)
+                }
+                MICROS_DATE_CODE => {
+                    let micros = i64::deserialize(reader)?;
+                    date_from_units(micros, NANOS_PER_MICRO)
+                }
+                PRECISE_DATE_CODE => {
+                    let timestamp = i64::deserialize(reader)?;
+                    let nanos = u32::deserialize(reader)?;
+                    Utc.timestamp_opt(timestamp, nanos)
+                        .single()
+                        .map(Value::Date)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("Invalid date {}s and {}ns", timestamp, nanos),
+                            )
+                        })
+                }
                 HIERARCHICAL_FACET_CODE => Ok(Value::Facet(Facet::deserialize(reader)?)),
                 BYTES_CODE => Ok(Value::Bytes(Vec::<u8>::deserialize(reader)?)),
+                COMPRESSED_BYTES_CODE => {
+                    let compressed = Vec::<u8>::deserialize(reader)?;
+                    let mut bytes = Vec::new();
+                    decompress(&compressed, &mut bytes)?;
+                    Ok(Value::Bytes(bytes))
+                }
+                BOOL_CODE => match u8::deserialize(reader)? {
+                    0 => Ok(Value::Bool(false)),
+                    1 => Ok(Value::Bool(true)),
+                    byte => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid bool value {:?}", byte),
+                    )),
+                },
+                NULL_CODE => Ok(Value::Null),
+                U128_CODE => Ok(Value::U128(u128::deserialize(reader)?)),
+                IP_ADDR_CODE => {
+                    let mut octets = [0u8; 16];
+                    reader.read_exact(&mut octets)?;
+                    Ok(Value::IpAddr(Ipv6Addr::from(octets)))
+                }
+                JSON_OBJECT_CODE => {
+                    let num_entries = VInt::deserialize(reader)?.val();
+                    let mut object = BTreeMap::new();
+                    for _ in 0..num_entries {
+                        let key = String::deserialize(reader)?;
+                        let value = Value::deserialize(reader)?;
+                        object.insert(key, value);
+                    }
+                    Ok(Value::JsonObject(object))
+                }
+                ARRAY_CODE => {
+                    let num_values = VInt::deserialize(reader)?.val();
+                    let values = (0..num_values)
+                        .map(|_| Value::deserialize(reader))
+                        .collect::<io::Result<Vec<Value>>>()?;
+                    Ok(Value::Array(values))
+                }
                 EXT_CODE => {
                     let ext_type_code = u8::deserialize(reader)?;
                     match ext_type_code {
@@ -386,13 +1289,135 @@ mod binary_serialize {
             }
         }
     }
+
+    impl Value {
+        /// Serializes a `&str` exactly as a `Value::Str` would be serialized.
+        pub(crate) fn serialize_str<W: Write>(text: &str, writer: &mut W) -> io::Result<()> {
+            TEXT_CODE.serialize(writer)?;
+            VInt(text.len() as u64).serialize(writer)?;
+            writer.write_all(text.as_bytes())
+        }
+
+        /// Serializes a `&[u8]` exactly as a `Value::Bytes` would be serialized.
+        pub(crate) fn serialize_bytes<W: Write>(bytes: &[u8], writer: &mut W) -> io::Result<()> {
+            BYTES_CODE.serialize(writer)?;
+            VInt(bytes.len() as u64).serialize(writer)?;
+            writer.write_all(bytes)
+        }
+
+        /// Serializes the value, compressing it if it is a `Value::Bytes`
+        /// longer than `bytes_compression_threshold` bytes.
+        ///
+        /// Other values, including the bytes nested in json objects or arrays,
+        /// are serialized exactly as `serialize` would.
+        pub(crate) fn serialize_compressing_bytes<W: Write>(
+            &self,
+            bytes_compression_threshold: usize,
+            writer: &mut W,
+        ) -> io::Result<()> {
+            match *self {
+                Value::Bytes(ref bytes) if bytes.len() > bytes_compression_threshold => {
+                    let mut compressed = Vec::new();
+                    compress(bytes, &mut compressed)?;
+                    COMPRESSED_BYTES_CODE.serialize(writer)?;
+                    compressed.serialize(writer)
+                }
+                _ => self.serialize(writer),
+            }
+        }
+
+        /// Advances the reader past a serialized `Value`, without allocating.
+        pub(crate) fn skip_deserialize<R: Read>(reader: &mut R) -> io::Result<()> {
+            let type_code = u8::deserialize(reader)?;
+            let num_bytes = match type_code {
+                TEXT_CODE | HIERARCHICAL_FACET_CODE | BYTES_CODE | COMPRESSED_BYTES_CODE => {
+                    VInt::deserialize(reader)?.val()
+                }
+                U64_CODE | I64_CODE | F64_CODE | DATE_CODE | MILLIS_DATE_CODE
+                | MICROS_DATE_CODE => 8,
+                F32_CODE => 4,
+                PRECISE_DATE_CODE => 12,
+                BOOL_CODE => 1,
+                NULL_CODE => 0,
+                U128_CODE | IP_ADDR_CODE | DURATION_CODE | GEO_POINT_CODE => 16,
+                JSON_OBJECT_CODE => {
+                    let num_entries = VInt::deserialize(reader)?.val();
+                    for _ in 0..num_entries {
+                        let key_len = VInt::deserialize(reader)?.val();
+                        skip_bytes(reader, key_len)?;
+                        Value::skip_deserialize(reader)?;
+                    }
+                    return Ok(());
+                }
+                ARRAY_CODE => {
+                    let num_values = VInt::deserialize(reader)?.val();
+                    for _ in 0..num_values {
+                        Value::skip_deserialize(reader)?;
+                    }
+                    return Ok(());
+                }
+                EXT_CODE => {
+                    let ext_type_code = u8::deserialize(reader)?;
+                    match ext_type_code {
+                        TOK_STR_CODE => VInt::deserialize(reader)?.val(),
+                        _ => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "No extened field type is associated with code {:?}",
+                                    ext_type_code
+                                ),
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("No field type is associated with code {:?}", type_code),
+                    ))
+                }
+            };
+            skip_bytes(reader, num_bytes)
+        }
+    }
+
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    // Returns the duration of `nanos` nanoseconds, if it does not exceed `Duration::MAX`.
+    fn duration_from_nanos(nanos: u128) -> Option<Duration> {
+        let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+        Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+    }
+
+    fn skip_bytes<R: Read>(reader: &mut R, num_bytes: u64) -> io::Result<()> {
+        let num_skipped_bytes = io::copy(&mut reader.take(num_bytes), &mut io::sink())?;
+        if num_skipped_bytes != num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Reached end of stream while skipping a value.",
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Value;
+    use super::{DatePrecision, Value, ValueType, ValueTypeError};
+    use crate::common::BinarySerializable;
+    use crate::schema::Facet;
+    use crate::tokenizer::PreTokenizedString;
     use crate::DateTime;
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
+    use std::hash::{Hash, Hasher};
+    use std::io;
+    use std::net::{IpAddr, Ipv6Addr};
     use std::str::FromStr;
+    use std::time::Duration;
 
     #[test]
     fn test_serialize_date() {
@@ -400,4 +1425,484 @@ mod tests {
         let serialized_value_json = serde_json::to_string_pretty(&value).unwrap();
         assert_eq!(serialized_value_json, r#""1996-12-20T00:39:57+00:00""#);
     }
+
+    #[test]
+    fn test_date_precision() {
+        for (date_str, num_bytes) in &[
+            ("1996-12-20T00:39:57+00:00", 9),
+            ("1996-12-20T00:39:57.123+00:00", 9),
+            ("1996-12-20T00:39:57.123456+00:00", 9),
+            ("1996-12-20T00:39:57.123456789+00:00", 13),
+            ("1996-12-20T00:39:57.000001+00:00", 9),
+            ("1969-12-31T23:59:59.999+00:00", 9),
+            ("1969-12-31T23:59:59.999999+00:00", 9),
+            ("1969-12-31T23:59:59.999999999+00:00", 13),
+        ] {
+            let value = Value::Date(DateTime::from_str(date_str).unwrap());
+            let mut buffer = Vec::new();
+            value.serialize(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), *num_bytes);
+            assert_eq!(Value::deserialize(&mut &buffer[..]).unwrap(), value);
+            let mut cursor = &buffer[..];
+            Value::skip_deserialize(&mut cursor).unwrap();
+            assert!(cursor.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_date_precision_truncate() {
+        let date = DateTime::from_str("1996-12-20T00:39:57.123456789+00:00").unwrap();
+        for (precision, truncated_date_str) in &[
+            (DatePrecision::Seconds, "1996-12-20T00:39:57+00:00"),
+            (DatePrecision::Milliseconds, "1996-12-20T00:39:57.123+00:00"),
+            (
+                DatePrecision::Microseconds,
+                "1996-12-20T00:39:57.123456+00:00",
+            ),
+            (
+                DatePrecision::Nanoseconds,
+                "1996-12-20T00:39:57.123456789+00:00",
+            ),
+        ] {
+            assert_eq!(
+                precision.truncate(&date),
+                DateTime::from_str(truncated_date_str).unwrap()
+            );
+        }
+        let date = DateTime::from_str("1969-12-31T23:59:59.9+00:00").unwrap();
+        assert_eq!(
+            DatePrecision::Seconds.truncate(&date),
+            DateTime::from_str("1969-12-31T23:59:59+00:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_value_type() {
+        let pre_tokenized_text = PreTokenizedString {
+            text: String::from("hello"),
+            tokens: Vec::new(),
+        };
+        let values_types_and_names = vec![
+            (Value::from("hello"), ValueType::Str, "str"),
+            (
+                Value::PreTokStr(pre_tokenized_text),
+                ValueType::PreTokStr,
+                "pre-tokenized str",
+            ),
+            (Value::U64(1), ValueType::U64, "u64"),
+            (Value::I64(-1), ValueType::I64, "i64"),
+            (Value::F64(1.5), ValueType::F64, "f64"),
+            (
+                Value::Date(DateTime::from_str("1996-12-20T00:39:57+00:00").unwrap()),
+                ValueType::Date,
+                "date",
+            ),
+            (Value::Facet(Facet::from("/a/b")), ValueType::Facet, "facet"),
+            (Value::Bytes(vec![1u8]), ValueType::Bytes, "bytes"),
+            (Value::Bool(true), ValueType::Bool, "bool"),
+            (Value::Null, ValueType::Null, "null"),
+            (Value::U128(1), ValueType::U128, "u128"),
+            (
+                Value::from(IpAddr::from_str("127.0.0.1").unwrap()),
+                ValueType::IpAddr,
+                "ip address",
+            ),
+            (
+                Value::JsonObject(BTreeMap::new()),
+                ValueType::JsonObject,
+                "json object",
+            ),
+            (Value::Array(Vec::new()), ValueType::Array, "array"),
+            (Value::F32(1.5), ValueType::F32, "f32"),
+            (
+                Value::Duration(Duration::from_secs(1)),
+                ValueType::Duration,
+                "duration",
+            ),
+            (
+                Value::geo_point(1.0, 2.0).unwrap(),
+                ValueType::GeoPoint,
+                "geo point",
+            ),
+        ];
+        for (value, value_type, name) in values_types_and_names {
+            assert_eq!(value.value_type(), value_type);
+            assert_eq!(value.type_name(), name);
+            assert_eq!(value_type.name(), name);
+            assert_eq!(value_type.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn test_value_accessors() {
+        let text = Value::Str("hello".to_string());
+        let u64_val = Value::U64(3u64);
+        assert_eq!(text.as_str(), Some("hello"));
+        assert_eq!(u64_val.as_str(), None);
+        let pre_tokenized = PreTokenizedString {
+            text: "hello".to_string(),
+            tokens: Vec::new(),
+        };
+        assert_eq!(
+            Value::PreTokStr(pre_tokenized.clone()).as_pre_tokenized(),
+            Some(&pre_tokenized)
+        );
+        assert_eq!(text.as_pre_tokenized(), None);
+        assert_eq!(u64_val.as_u64(), Some(3u64));
+        assert_eq!(Value::I64(3i64).as_u64(), None);
+        assert_eq!(Value::I64(-3i64).as_i64(), Some(-3i64));
+        assert_eq!(u64_val.as_i64(), None);
+        assert_eq!(Value::F64(3.5f64).as_f64(), Some(3.5f64));
+        assert_eq!(u64_val.as_f64(), None);
+        assert_eq!(Value::F32(3.5f32).as_f32(), Some(3.5f32));
+        assert_eq!(Value::F64(3.5f64).as_f32(), None);
+        let duration = Duration::from_millis(1_500);
+        assert_eq!(Value::Duration(duration).as_duration(), Some(duration));
+        assert_eq!(u64_val.as_duration(), None);
+        assert_eq!(
+            Value::geo_point(48.85, 2.35).unwrap().as_geo_point(),
+            Some((48.85, 2.35))
+        );
+        assert_eq!(Value::F64(48.85).as_geo_point(), None);
+        let date = DateTime::from_str("1996-12-20T00:39:57+00:00").unwrap();
+        assert_eq!(Value::Date(date).as_date(), Some(&date));
+        assert_eq!(Value::I64(date.timestamp()).as_date(), None);
+        let facet = Facet::from("/a/b");
+        assert_eq!(Value::Facet(facet.clone()).as_facet(), Some(&facet));
+        assert_eq!(Value::Str("/a/b".to_string()).as_facet(), None);
+        assert_eq!(
+            Value::Bytes(vec![1u8, 2u8]).as_bytes(),
+            Some(&[1u8, 2u8][..])
+        );
+        assert_eq!(text.as_bytes(), None);
+        assert_eq!(Value::Bool(true).as_bool(), Some(true));
+        assert_eq!(u64_val.as_bool(), None);
+        assert_eq!(Value::U128(3u128).as_u128(), Some(3u128));
+        assert_eq!(u64_val.as_u128(), None);
+        let mut object = BTreeMap::new();
+        object.insert("a".to_string(), Value::U64(1u64));
+        assert_eq!(
+            Value::JsonObject(object.clone()).as_json_object(),
+            Some(&object)
+        );
+        assert_eq!(u64_val.as_json_object(), None);
+        assert_eq!(
+            Value::Array(vec![u64_val.clone()]).as_array(),
+            Some(&[u64_val.clone()][..])
+        );
+        assert_eq!(u64_val.as_array(), None);
+        let ip_addr = Ipv6Addr::from_str("::1").unwrap();
+        assert_eq!(Value::IpAddr(ip_addr).as_ip_addr(), Some(ip_addr));
+        assert_eq!(u64_val.as_ip_addr(), None);
+        assert!(Value::Null.is_null());
+        assert!(!u64_val.is_null());
+    }
+
+    #[test]
+    fn test_ip_addr_value() {
+        let ipv4 = Value::from(IpAddr::from_str("192.168.0.1").unwrap());
+        assert_eq!(
+            ipv4.as_ip_addr(),
+            Some(Ipv6Addr::from_str("::ffff:192.168.0.1").unwrap())
+        );
+        assert_eq!(
+            ipv4,
+            Value::IpAddr(Ipv6Addr::from_str("::ffff:192.168.0.1").unwrap())
+        );
+        let ipv6 = Value::from(IpAddr::from_str("2001:db8::1").unwrap());
+        assert_eq!(ipv6.as_ip_addr(), Ipv6Addr::from_str("2001:db8::1").ok());
+        assert!(Value::from(IpAddr::from_str("10.0.0.1").unwrap()) < ipv4);
+        assert!(ipv4 < ipv6);
+        for value in &[ipv4, ipv6] {
+            let mut buffer = Vec::new();
+            value.serialize(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), 17);
+            assert_eq!(&Value::deserialize(&mut &buffer[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_f32_value() {
+        let values = vec![
+            Value::F32(1.5f32),
+            Value::F32(-0.0f32),
+            Value::F32(f32::MIN_POSITIVE / 2.0),
+            Value::F32(f32::from_bits(1)),
+            Value::F32(f32::INFINITY),
+            Value::F32(f32::NEG_INFINITY),
+            Value::F32(f32::NAN),
+        ];
+        for value in &values {
+            let mut buffer = Vec::new();
+            value.serialize(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), 5);
+            // `NaN` is not equal to itself, so values are compared with `cmp`.
+            let deserialized = Value::deserialize(&mut &buffer[..]).unwrap();
+            assert_eq!(deserialized.cmp(value), Ordering::Equal);
+            let mut cursor = &buffer[..];
+            Value::skip_deserialize(&mut cursor).unwrap();
+            assert!(cursor.is_empty());
+        }
+        assert_eq!(Value::F32(0.0f32), Value::F32(-0.0f32));
+        assert_eq!(hash(&Value::F32(0.0f32)), hash(&Value::F32(-0.0f32)));
+        let other_nan = f32::from_bits(f32::NAN.to_bits() + 1);
+        assert_eq!(
+            Value::F32(f32::NAN).cmp(&Value::F32(other_nan)),
+            Ordering::Equal
+        );
+        assert_eq!(hash(&Value::F32(f32::NAN)), hash(&Value::F32(other_nan)));
+        assert!(Value::F32(f32::NAN) < Value::F32(f32::NEG_INFINITY));
+        assert!(Value::F32(f32::MAX) < Value::F32(f32::INFINITY));
+        assert_ne!(Value::F32(1.5f32), Value::F64(1.5f64));
+        assert_eq!(Value::from(1.5f32).to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_duration_value() {
+        let values = vec![
+            Value::Duration(Duration::from_nanos(1)),
+            Value::Duration(Duration::new(3_600, 999_999_999)),
+            Value::Duration(Duration::MAX),
+        ];
+        for value in &values {
+            let mut buffer = Vec::new();
+            value.serialize(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), 17);
+            assert_eq!(&Value::deserialize(&mut &buffer[..]).unwrap(), value);
+            let mut cursor = &buffer[..];
+            Value::skip_deserialize(&mut cursor).unwrap();
+            assert!(cursor.is_empty());
+        }
+        assert!(Value::Duration(Duration::from_nanos(1)) < Value::Duration(Duration::from_secs(1)));
+        let mut buffer = Vec::new();
+        Value::Duration(Duration::MAX)
+            .serialize(&mut buffer)
+            .unwrap();
+        buffer[1..].copy_from_slice(&u128::MAX.to_le_bytes());
+        assert!(Value::deserialize(&mut &buffer[..]).is_err());
+    }
+
+    #[test]
+    fn test_geo_point_value() {
+        for &(lat, lng) in &[
+            (90.0, 180.0),
+            (-90.0, -180.0),
+            (90.0, -180.0),
+            (0.0, 0.0),
+            (48.8566, 2.3522),
+        ] {
+            let value = Value::geo_point(lat, lng).unwrap();
+            assert_eq!(value, Value::GeoPoint { lat, lng });
+            let mut buffer = Vec::new();
+            value.serialize(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), 17);
+            assert_eq!(Value::deserialize(&mut &buffer[..]).unwrap(), value);
+            let mut cursor = &buffer[..];
+            Value::skip_deserialize(&mut cursor).unwrap();
+            assert!(cursor.is_empty());
+        }
+        let zero = Value::geo_point(-0.0, -0.0).unwrap();
+        assert!(zero.as_geo_point().unwrap().0.is_sign_positive());
+        assert!(zero.as_geo_point().unwrap().1.is_sign_positive());
+        for &(lat, lng) in &[
+            (90.5, 0.0),
+            (-90.5, 0.0),
+            (0.0, 180.5),
+            (0.0, -180.5),
+            (f64::NAN, 0.0),
+            (0.0, f64::NAN),
+            (f64::INFINITY, 0.0),
+        ] {
+            assert!(Value::geo_point(lat, lng).is_err());
+        }
+        assert_eq!(
+            Value::geo_point(91.0, 0.0).unwrap_err().to_string(),
+            "Invalid geo point (91, 0): the latitude must be within [-90, 90] and the longitude \
+             within [-180, 180]"
+        );
+        // Out of range coordinates are rejected when serializing...
+        let mut buffer = Vec::new();
+        let err = Value::GeoPoint {
+            lat: 91.0,
+            lng: 0.0,
+        }
+        .serialize(&mut buffer)
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // ... and when deserializing.
+        let mut buffer = Vec::new();
+        // 17 is the code of geo points.
+        17u8.serialize(&mut buffer).unwrap();
+        91.0f64.serialize(&mut buffer).unwrap();
+        0.0f64.serialize(&mut buffer).unwrap();
+        assert!(Value::deserialize(&mut &buffer[..]).is_err());
+        assert!(Value::geo_point(-10.0, 50.0).unwrap() < Value::geo_point(10.0, -50.0).unwrap());
+        assert_eq!(
+            Value::geo_point(1.5, -2.0).unwrap().to_string(),
+            "(1.5, -2)"
+        );
+    }
+
+    fn hash(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_json_object_value() {
+        let mut inner = BTreeMap::new();
+        inner.insert("port".to_string(), Value::U64(8080u64));
+        inner.insert("secure".to_string(), Value::Bool(false));
+        let mut object = BTreeMap::new();
+        object.insert("host".to_string(), Value::from("localhost"));
+        object.insert("server".to_string(), Value::JsonObject(inner));
+        let value = Value::JsonObject(object);
+        let mut buffer = Vec::new();
+        value.serialize(&mut buffer).unwrap();
+        assert_eq!(Value::deserialize(&mut &buffer[..]).unwrap(), value);
+        let mut cursor = &buffer[..];
+        Value::skip_deserialize(&mut cursor).unwrap();
+        assert!(cursor.is_empty());
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"host":"localhost","server":{"port":8080,"secure":false}}"#
+        );
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::from("hello world").to_string(), "hello world");
+        let pre_tokenized = PreTokenizedString {
+            text: "hello".to_string(),
+            tokens: Vec::new(),
+        };
+        assert_eq!(Value::PreTokStr(pre_tokenized).to_string(), "hello");
+        assert_eq!(Value::U64(3u64).to_string(), "3");
+        assert_eq!(Value::I64(-3i64).to_string(), "-3");
+        assert_eq!(Value::F64(1.5f64).to_string(), "1.5");
+        let date = DateTime::from_str("1996-12-20T00:39:57+00:00").unwrap();
+        assert_eq!(Value::Date(date).to_string(), "1996-12-20T00:39:57+00:00");
+        assert_eq!(Value::Facet(Facet::from("/a/b")).to_string(), "/a/b");
+        assert_eq!(Value::Bytes(b"hello".to_vec()).to_string(), "aGVsbG8=");
+        assert_eq!(
+            Value::Bytes(vec![0u8; 100]).to_string(),
+            format!("{}=…", "A".repeat(43))
+        );
+        assert_eq!(Value::Bool(true).to_string(), "true");
+        assert_eq!(Value::Null.to_string(), "null");
+        assert_eq!(Value::U128(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(
+            Value::IpAddr(Ipv6Addr::from_str("::1").unwrap()).to_string(),
+            "::1"
+        );
+        let mut object = BTreeMap::new();
+        object.insert("a".to_string(), Value::U64(1u64));
+        object.insert("b".to_string(), Value::from("c"));
+        assert_eq!(Value::JsonObject(object).to_string(), "{a: 1, b: c}");
+        let array = Value::Array(vec![
+            Value::U64(1u64),
+            Value::Array(vec![Value::from("a"), Value::from("b")]),
+        ]);
+        assert_eq!(array.to_string(), "[1, [a, b]]");
+    }
+
+    #[test]
+    fn test_array_value() {
+        let value = Value::Array(vec![
+            Value::F64(48.85f64),
+            Value::Array(vec![Value::U64(1u64), Value::Null]),
+            Value::from("c"),
+        ]);
+        let mut buffer = Vec::new();
+        value.serialize(&mut buffer).unwrap();
+        assert_eq!(Value::deserialize(&mut &buffer[..]).unwrap(), value);
+        let mut cursor = &buffer[..];
+        Value::skip_deserialize(&mut cursor).unwrap();
+        assert!(cursor.is_empty());
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"[48.85,[1,null],"c"]"#);
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_value_try_from() {
+        let date = DateTime::from_str("1996-12-20T00:39:57+00:00").unwrap();
+        assert_eq!(u64::try_from(Value::U64(3u64)), Ok(3u64));
+        assert_eq!(u64::try_from(&Value::U64(3u64)), Ok(3u64));
+        assert_eq!(i64::try_from(Value::I64(-3i64)), Ok(-3i64));
+        assert_eq!(i64::try_from(&Value::I64(-3i64)), Ok(-3i64));
+        assert_eq!(f64::try_from(Value::F64(1.5f64)), Ok(1.5f64));
+        assert_eq!(f64::try_from(&Value::F64(1.5f64)), Ok(1.5f64));
+        assert_eq!(String::try_from(Value::from("a")), Ok("a".to_string()));
+        assert_eq!(String::try_from(&Value::from("a")), Ok("a".to_string()));
+        assert_eq!(Vec::<u8>::try_from(Value::from(vec![1u8])), Ok(vec![1u8]));
+        assert_eq!(Vec::<u8>::try_from(&Value::from(vec![1u8])), Ok(vec![1u8]));
+        assert_eq!(DateTime::try_from(Value::Date(date)), Ok(date));
+        assert_eq!(DateTime::try_from(&Value::Date(date)), Ok(date));
+    }
+
+    #[test]
+    fn test_value_try_from_mismatch() {
+        let type_error =
+            |expected: ValueType, actual: &'static str| ValueTypeError { expected, actual };
+        assert_eq!(
+            u64::try_from(Value::I64(3i64)).unwrap_err(),
+            type_error(ValueType::U64, "i64")
+        );
+        assert_eq!(
+            u64::try_from(&Value::Null).unwrap_err(),
+            type_error(ValueType::U64, "null")
+        );
+        assert_eq!(
+            i64::try_from(Value::U64(3u64)).unwrap_err(),
+            type_error(ValueType::I64, "u64")
+        );
+        assert_eq!(
+            i64::try_from(&Value::F64(3.0f64)).unwrap_err(),
+            type_error(ValueType::I64, "f64")
+        );
+        assert_eq!(
+            f64::try_from(Value::I64(3i64)).unwrap_err(),
+            type_error(ValueType::F64, "i64")
+        );
+        assert_eq!(
+            f64::try_from(&Value::from("3")).unwrap_err(),
+            type_error(ValueType::F64, "str")
+        );
+        assert_eq!(
+            String::try_from(Value::from(vec![1u8])).unwrap_err(),
+            type_error(ValueType::Str, "bytes")
+        );
+        assert_eq!(
+            String::try_from(&Value::Facet(Facet::from("/a"))).unwrap_err(),
+            type_error(ValueType::Str, "facet")
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(Value::from("a")).unwrap_err(),
+            type_error(ValueType::Bytes, "str")
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(&Value::Bool(true)).unwrap_err(),
+            type_error(ValueType::Bytes, "bool")
+        );
+        assert_eq!(
+            DateTime::try_from(Value::I64(0i64)).unwrap_err(),
+            type_error(ValueType::Date, "i64")
+        );
+        assert_eq!(
+            DateTime::try_from(&Value::U128(0u128)).unwrap_err(),
+            type_error(ValueType::Date, "u128")
+        );
+        assert_eq!(
+            ValueTypeError {
+                expected: ValueType::U64,
+                actual: "i64"
+            }
+            .to_string(),
+            "Expected a value of type u64, got i64"
+        );
+    }
 }
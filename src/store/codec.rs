@@ -0,0 +1,228 @@
+use crate::common::BinarySerializable;
+use crate::schema::Document;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+/// Encoding of the documents within the doc store.
+///
+/// The store only deals with the bytes produced by the codec:
+/// it takes care of framing, compressing and indexing them.
+///
+/// A store must be read with the codec it was written with.
+pub trait DocumentCodec: Send + Sync + 'static {
+    /// Name of the codec.
+    ///
+    /// It is recorded in the meta of the index, so that opening an index with
+    /// a codec of another name fails instead of misreading the doc store.
+    /// Codecs encoding documents differently must hence have different names.
+    fn name(&self) -> &str;
+
+    /// Serializes a document.
+    fn serialize(&self, doc: &Document, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Deserializes a document serialized by `serialize`.
+    ///
+    /// The reader contains exactly the bytes written by `serialize`.
+    fn deserialize(&self, reader: &mut dyn Read) -> io::Result<Document>;
+
+    /// Returns a copy of this codec compressing individually the `Value::Bytes`
+    /// longer than `bytes_compression_threshold` bytes, if any.
+    ///
+    /// Returns None if the codec does not support compressing bytes values,
+    /// which is the default.
+    fn with_bytes_compression(
+        &self,
+        _bytes_compression_threshold: Option<usize>,
+    ) -> Option<Arc<dyn DocumentCodec>> {
+        None
+    }
+
+    /// Serializes a document followed by `num_raw_field_values` field values
+    /// already serialized with `BinarySerializable` in `raw_field_values`,
+    /// see [`StoreWriter::store_raw`](./struct.StoreWriter.html#method.store_raw).
+    ///
+    /// Returns an error if the codec does not support raw field values,
+    /// which is the default.
+    fn serialize_with_raw_field_values(
+        &self,
+        _doc: &Document,
+        _raw_field_values: &[u8],
+        _num_raw_field_values: usize,
+        _writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The codec of the doc store does not support raw field values",
+        ))
+    }
+}
+
+/// Name of the `DefaultCodec`.
+pub(crate) const DEFAULT_CODEC_NAME: &str = "default";
+
+/// The codec used by the store by default.
+///
+/// Documents are encoded in their `BinarySerializable` form.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultCodec {
+    bytes_compression_threshold: Option<usize>,
+}
+
+impl DefaultCodec {
+    /// Creates a codec compressing individually the `Value::Bytes`
+    /// longer than `bytes_compression_threshold` bytes, if any.
+    ///
+    /// Compressed bytes values are decompressed transparently when
+    /// reading documents back.
+    pub fn with_bytes_compression_threshold(
+        bytes_compression_threshold: Option<usize>,
+    ) -> DefaultCodec {
+        DefaultCodec {
+            bytes_compression_threshold,
+        }
+    }
+
+    /// Returns the size above which `Value::Bytes` are individually compressed.
+    pub fn bytes_compression_threshold(&self) -> Option<usize> {
+        self.bytes_compression_threshold
+    }
+}
+
+impl DocumentCodec for DefaultCodec {
+    fn name(&self) -> &str {
+        DEFAULT_CODEC_NAME
+    }
+
+    fn serialize(&self, doc: &Document, mut writer: &mut dyn Write) -> io::Result<()> {
+        doc.serialize_with_bytes_compression(&mut writer, self.bytes_compression_threshold)
+    }
+
+    fn deserialize(&self, mut reader: &mut dyn Read) -> io::Result<Document> {
+        Document::deserialize(&mut reader)
+    }
+
+    fn with_bytes_compression(
+        &self,
+        bytes_compression_threshold: Option<usize>,
+    ) -> Option<Arc<dyn DocumentCodec>> {
+        Some(Arc::new(DefaultCodec::with_bytes_compression_threshold(
+            bytes_compression_threshold,
+        )))
+    }
+
+    fn serialize_with_raw_field_values(
+        &self,
+        doc: &Document,
+        raw_field_values: &[u8],
+        num_raw_field_values: usize,
+        mut writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        doc.serialize_with_raw_field_values(
+            &mut writer,
+            self.bytes_compression_threshold,
+            raw_field_values,
+            num_raw_field_values,
+        )
+    }
+}
+
+/// Codec encoding the documents in the MessagePack format.
+///
+/// See [`Document::serialize_msgpack`](../schema/struct.Document.html#method.serialize_msgpack)
+/// for the description of the format.
+#[cfg(feature = "msgpack")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MsgpackCodec;
+
+#[cfg(feature = "msgpack")]
+impl DocumentCodec for MsgpackCodec {
+    fn name(&self) -> &str {
+        "msgpack"
+    }
+
+    fn serialize(&self, doc: &Document, mut writer: &mut dyn Write) -> io::Result<()> {
+        doc.serialize_msgpack(&mut writer)
+    }
+
+    fn deserialize(&self, reader: &mut dyn Read) -> io::Result<Document> {
+        Document::deserialize_msgpack(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{DefaultCodec, DocumentCodec};
+    use crate::directory::{Directory, RAMDirectory};
+    use crate::schema::{Document, Schema, STORED, TEXT};
+    use crate::store::{StoreReader, StoreWriter};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn test_documents() -> (Schema, Vec<Document>) {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let docs = (0..1_000u64)
+            .map(|i| {
+                let mut doc = Document::default();
+                doc.add_text(title, format!("Doc {}", i));
+                doc.add_u64(count, i);
+                doc.add_u64(count, i * 2);
+                doc.add_bytes(payload, vec![(i % 7) as u8; (i % 3_000) as usize]);
+                doc
+            })
+            .collect();
+        (schema_builder.build(), docs)
+    }
+
+    fn test_codec_round_trip(codec: Arc<dyn DocumentCodec>) -> crate::Result<()> {
+        let (_, docs) = test_documents();
+        for doc in &docs {
+            let mut buffer = Vec::new();
+            codec.serialize(doc, &mut buffer)?;
+            assert_eq!(&codec.deserialize(&mut &buffer[..])?, doc);
+        }
+        let path = Path::new("store");
+        let directory = RAMDirectory::create();
+        let mut store_writer = StoreWriter::with_codec(directory.open_write(path)?, codec.clone());
+        for doc in &docs {
+            store_writer.store(doc)?;
+        }
+        store_writer.close()?;
+        let store = StoreReader::open_with_codec(directory.open_read(path)?, codec)?;
+        for (doc_id, doc) in docs.iter().enumerate() {
+            assert_eq!(&store.get(doc_id as u32)?, doc);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_codec() -> crate::Result<()> {
+        test_codec_round_trip(Arc::new(DefaultCodec::default()))?;
+        test_codec_round_trip(Arc::new(DefaultCodec::with_bytes_compression_threshold(
+            Some(1_000),
+        )))
+    }
+
+    #[test]
+    fn test_default_codec_matches_binary_serializable() -> crate::Result<()> {
+        use crate::common::BinarySerializable;
+        let (_, docs) = test_documents();
+        for doc in &docs {
+            let mut buffer = Vec::new();
+            DefaultCodec::default().serialize(doc, &mut buffer)?;
+            let mut expected = Vec::new();
+            doc.serialize(&mut expected)?;
+            assert_eq!(buffer, expected);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_codec() -> crate::Result<()> {
+        test_codec_round_trip(Arc::new(super::MsgpackCodec))
+    }
+}
@@ -0,0 +1,144 @@
+use crate::common::{BinarySerializable, VInt};
+use crate::schema::{Document, Field, FieldValue, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+/// Flag set on the footer of the stores written with interned fields.
+///
+/// Stores without interned fields have the same layout as before
+/// string interning was introduced:
+///
+/// `[blocks][skip index][skip index offset: u64]`
+///
+/// while stores with interned fields are laid out as:
+///
+/// `[blocks][skip index][dictionary][dictionary offset: u64][skip index offset | DICTIONARY_FLAG: u64]`
+///
+/// In the latter, each document is prefixed with the field values whose string
+/// was interned, as `[num interned: VInt]([position: VInt][field value header][string id: VInt])*`,
+/// followed by the rest of the document, encoded by the codec of the store.
+/// The position of an interned field value is its index within the document.
+pub(crate) const DICTIONARY_FLAG: u64 = 1u64 << 63;
+
+/// Dictionary of the strings interned by a `StoreWriter`.
+///
+/// Strings are given consecutive ids, in the order of their first occurrence.
+#[derive(Default)]
+pub(crate) struct StringDictionaryBuilder {
+    ids: HashMap<String, u32>,
+}
+
+impl StringDictionaryBuilder {
+    fn intern(&mut self, text: &str) -> Option<u32> {
+        if let Some(&id) = self.ids.get(text) {
+            return Some(id);
+        }
+        let id = u32::try_from(self.ids.len()).ok()?;
+        self.ids.insert(text.to_string(), id);
+        Some(id)
+    }
+
+    /// Writes the prefix of a document, made of the field values of the
+    /// given fields whose string is interned, and returns the rest of the document.
+    pub fn intern_document<W: Write>(
+        &mut self,
+        doc: &Document,
+        fields: &[Field],
+        writer: &mut W,
+    ) -> io::Result<Document> {
+        let mut interned_field_values = Vec::new();
+        let mut field_values = Vec::with_capacity(doc.len());
+        for (position, field_value) in doc.field_values().iter().enumerate() {
+            if fields.contains(&field_value.field()) {
+                if let Value::Str(text) = field_value.value() {
+                    // Past `u32::MAX` distinct strings, strings are stored as is.
+                    if let Some(id) = self.intern(text) {
+                        interned_field_values.push((position, field_value, id));
+                        continue;
+                    }
+                }
+            }
+            field_values.push(field_value.clone());
+        }
+        VInt(interned_field_values.len() as u64).serialize(writer)?;
+        for (position, field_value, id) in interned_field_values {
+            VInt(position as u64).serialize(writer)?;
+            field_value.serialize_header(writer)?;
+            VInt(u64::from(id)).serialize(writer)?;
+        }
+        Ok(Document::from(field_values))
+    }
+
+    /// Serializes the strings, in the order of their id.
+    pub fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut strings: Vec<&str> = vec![""; self.ids.len()];
+        for (text, &id) in &self.ids {
+            strings[id as usize] = text;
+        }
+        VInt(strings.len() as u64).serialize(writer)?;
+        for text in strings {
+            VInt(text.len() as u64).serialize(writer)?;
+            writer.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Dictionary of the strings interned in a store, as read by a `StoreReader`.
+pub(crate) struct StringDictionary {
+    strings: Vec<String>,
+}
+
+impl StringDictionary {
+    pub fn open(mut data: &[u8]) -> io::Result<StringDictionary> {
+        let strings = Vec::<String>::deserialize(&mut data)?;
+        Ok(StringDictionary { strings })
+    }
+
+    /// Reads the interned field values prefixing a document,
+    /// along with their position within the document.
+    pub fn read_interned_field_values<R: Read>(
+        &self,
+        reader: &mut R,
+    ) -> io::Result<Vec<(usize, FieldValue)>> {
+        let num_interned_field_values = VInt::deserialize(reader)?.val();
+        (0..num_interned_field_values)
+            .map(|_| {
+                let position = VInt::deserialize(reader)?.val() as usize;
+                let (field, boost) = FieldValue::deserialize_header(reader)?;
+                let id = VInt::deserialize(reader)?.val();
+                let text = self.strings.get(id as usize).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Unknown interned string id {}", id),
+                    )
+                })?;
+                let field_value = FieldValue::with_boost(field, Value::Str(text.clone()), boost);
+                Ok((position, field_value))
+            })
+            .collect()
+    }
+}
+
+/// Inserts the interned field values read by `read_interned_field_values`
+/// back at their position within the document.
+pub(crate) fn insert_interned_field_values(
+    doc: Document,
+    interned_field_values: Vec<(usize, FieldValue)>,
+) -> io::Result<Document> {
+    if interned_field_values.is_empty() {
+        return Ok(doc);
+    }
+    let mut field_values = doc.into_field_values();
+    for (position, field_value) in interned_field_values {
+        if position > field_values.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid interned field value position {}", position),
+            ));
+        }
+        field_values.insert(position, field_value);
+    }
+    Ok(Document::from(field_values))
+}
@@ -17,6 +17,16 @@ the reader is smart enough to avoid decompressing
 the block a second time, but their is no real
 *uncompressed block* cache.
 
+Documents are encoded by a [`DocumentCodec`](./trait.DocumentCodec.html).
+By default, the [`DefaultCodec`](./struct.DefaultCodec.html) stores them in their
+native binary form, but the store can be given another codec, provided the
+store is read with the same codec it was written with.
+
+The strings of low-cardinality fields can be interned: each distinct string
+is then written once, in a dictionary at the end of the store, and the documents
+only hold its id. The store resolves these ids back to the actual strings when
+reading documents.
+
 A typical use case for the store is, once
 the search result page has been computed, returning
 the actual content of the 10 best document.
@@ -33,9 +43,15 @@ and should rely on either
 
 !*/
 
+mod codec;
+mod dictionary;
 mod index;
 mod reader;
 mod writer;
+#[cfg(feature = "msgpack")]
+pub use self::codec::MsgpackCodec;
+pub(crate) use self::codec::DEFAULT_CODEC_NAME;
+pub use self::codec::{DefaultCodec, DocumentCodec};
 pub use self::reader::StoreReader;
 pub use self::writer::StoreWriter;
 
@@ -47,31 +63,34 @@ mod compression_lz4;
 #[cfg(feature = "lz4")]
 pub use self::compression_lz4::COMPRESSION;
 #[cfg(feature = "lz4")]
-use self::compression_lz4::{compress, decompress};
+pub(crate) use self::compression_lz4::{compress, decompress};
 
 #[cfg(feature = "brotli")]
 mod compression_brotli;
 #[cfg(feature = "brotli")]
 pub use self::compression_brotli::COMPRESSION;
 #[cfg(feature = "brotli")]
-use self::compression_brotli::{compress, decompress};
+pub(crate) use self::compression_brotli::{compress, decompress};
 
 #[cfg(not(any(feature = "lz4", feature = "brotli")))]
 mod compression_snap;
 #[cfg(not(any(feature = "lz4", feature = "brotli")))]
 pub use self::compression_snap::COMPRESSION;
 #[cfg(not(any(feature = "lz4", feature = "brotli")))]
-use self::compression_snap::{compress, decompress};
+pub(crate) use self::compression_snap::{compress, decompress};
 
 #[cfg(test)]
 pub mod tests {
 
     use super::*;
+    use crate::common::HasLen;
     use crate::directory::{Directory, RAMDirectory, WritePtr};
     use crate::schema::Document;
     use crate::schema::FieldValue;
     use crate::schema::Schema;
     use crate::schema::TextOptions;
+    use crate::schema::{DatePrecision, Value, STORED};
+    use chrono::{TimeZone, Utc};
     use std::path::Path;
 
     pub fn write_lorem_ipsum_store(writer: WritePtr, num_docs: usize) -> Schema {
@@ -134,6 +153,163 @@ pub mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_store_bytes_compression() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let small_payload: Vec<u8> = (0..100u32).map(|i| (i % 7) as u8).collect();
+        let large_payload: Vec<u8> = (0..100_000u32).map(|i| (i % 7) as u8).collect();
+        let path = Path::new("store");
+        let directory = RAMDirectory::create();
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(path)?);
+            store_writer.set_bytes_compression_threshold(Some(1_000))?;
+            for _ in 0..10 {
+                let mut doc = Document::default();
+                doc.add_bytes(payload, small_payload.clone());
+                doc.add_bytes(payload, large_payload.clone());
+                store_writer.store(&doc)?;
+            }
+            store_writer.close()?;
+        }
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        for doc_id in 0..10 {
+            let doc = store.get(doc_id)?;
+            assert_eq!(
+                doc.get_all(payload).collect::<Vec<_>>(),
+                vec![
+                    &Value::Bytes(small_payload.clone()),
+                    &Value::Bytes(large_payload.clone())
+                ]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_raw() -> crate::Result<()> {
+        use crate::common::BinarySerializable;
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut raw_field_values = Vec::new();
+        FieldValue::new(count, Value::U64(7u64)).serialize(&mut raw_field_values)?;
+        FieldValue::new(title, Value::from("cached")).serialize(&mut raw_field_values)?;
+        let mut doc = Document::default();
+        doc.add_u64(count, 3u64).add_text(title, "hello");
+        let path = Path::new("store");
+        let directory = RAMDirectory::create();
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(path)?);
+            store_writer.set_bytes_compression_threshold(Some(1_000))?;
+            store_writer.store_raw(&doc, &raw_field_values, 2)?;
+            store_writer.store_raw(&doc, &[], 0)?;
+            store_writer.close()?;
+        }
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        let mut expected_doc = doc.clone();
+        expected_doc.add_u64(count, 7u64).add_text(title, "cached");
+        assert_eq!(store.get(0)?, expected_doc);
+        assert_eq!(store.get(1)?, doc);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_interned_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let country = schema_builder.add_text_field("country", STORED);
+        let title = schema_builder.add_text_field("title", STORED);
+        let countries = ["FR", "DE", "JP", "US"];
+        let docs: Vec<Document> = (0..1_000)
+            .map(|i| {
+                let mut doc = Document::default();
+                doc.add_text(country, countries[i % countries.len()]);
+                doc.add_text(title, format!("Doc {}", i));
+                if i % 10 == 0 {
+                    doc.add_text(country, "BR");
+                    doc.add_u64(country, i as u64);
+                }
+                doc
+            })
+            .collect();
+        let directory = RAMDirectory::create();
+        let path = Path::new("store");
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(path)?);
+            store_writer.set_interned_fields(vec![country]);
+            for doc in &docs {
+                store_writer.store(doc)?;
+            }
+            store_writer.close()?;
+        }
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        assert!(store.has_string_dictionary());
+        for (doc_id, doc) in docs.iter().enumerate() {
+            assert_eq!(&store.get(doc_id as u32)?, doc);
+        }
+        // stacking a store with interned strings re-interns its documents.
+        let stacked_path = Path::new("stacked_store");
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(stacked_path)?);
+            store_writer.set_interned_fields(vec![country]);
+            store_writer.stack(&store)?;
+            store_writer.stack(&store)?;
+            store_writer.close()?;
+        }
+        let stacked_store = StoreReader::open(directory.open_read(stacked_path)?)?;
+        for (doc_id, doc) in docs.iter().chain(docs.iter()).enumerate() {
+            assert_eq!(&stacked_store.get(doc_id as u32)?, doc);
+        }
+        // stacking it into a store without interned fields resolves the strings.
+        let plain_path = Path::new("plain_store");
+        {
+            let mut store_writer = StoreWriter::new(directory.open_write(plain_path)?);
+            store_writer.stack(&store)?;
+            store_writer.close()?;
+        }
+        let plain_store = StoreReader::open(directory.open_read(plain_path)?)?;
+        assert!(!plain_store.has_string_dictionary());
+        for (doc_id, doc) in docs.iter().enumerate() {
+            assert_eq!(&plain_store.get(doc_id as u32)?, doc);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_date_precisions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let day = schema_builder.add_date_field("day", STORED);
+        let timestamp = schema_builder.add_date_field("timestamp", STORED);
+        let dates: Vec<_> = (0..1_000i64)
+            .map(|i| Utc.timestamp(i * 86_400 + i * 7_919 % 86_400, (i * 104_729) as u32))
+            .collect();
+        let directory = RAMDirectory::create();
+        let write_store = |path: &Path, date_precisions: Vec<_>| -> crate::Result<usize> {
+            let mut store_writer = StoreWriter::new(directory.open_write(path)?);
+            store_writer.set_date_precisions(date_precisions);
+            for date in &dates {
+                let mut doc = Document::default();
+                doc.add_date(day, date).add_date(timestamp, date);
+                store_writer.store(&doc)?;
+            }
+            store_writer.close()?;
+            Ok(directory.open_read(path)?.len())
+        };
+        let precise_num_bytes = write_store(Path::new("precise_store"), Vec::new())?;
+        let path = Path::new("store");
+        let num_bytes = write_store(path, vec![(day, DatePrecision::Seconds)])?;
+        assert!(num_bytes < precise_num_bytes);
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        for (doc_id, date) in dates.iter().enumerate() {
+            let doc = store.get(doc_id as u32)?;
+            let stored_day = doc.get_first(day).and_then(Value::as_date).unwrap();
+            assert_eq!(stored_day, &DatePrecision::Seconds.truncate(date));
+            assert_eq!(stored_day.timestamp() / 86_400, date.timestamp() / 86_400);
+            assert_eq!(doc.get_first(timestamp), Some(&Value::Date(*date)));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
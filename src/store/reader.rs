@@ -1,5 +1,7 @@
 use super::decompress;
+use super::dictionary::{insert_interned_field_values, StringDictionary, DICTIONARY_FLAG};
 use super::index::SkipIndex;
+use super::{DefaultCodec, DocumentCodec};
 use crate::common::VInt;
 use crate::common::{BinarySerializable, HasLen};
 use crate::directory::{FileSlice, OwnedBytes};
@@ -27,15 +29,36 @@ pub struct StoreReader {
     cache_misses: Arc<AtomicUsize>,
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
+    codec: Arc<dyn DocumentCodec>,
+    string_dictionary: Option<StringDictionary>,
 }
 
 impl StoreReader {
     /// Opens a store reader
     pub fn open(store_file: FileSlice) -> io::Result<StoreReader> {
-        let (data_file, offset_index_file) = split_file(store_file)?;
+        StoreReader::open_with_codec(store_file, Arc::new(DefaultCodec::default()))
+    }
+
+    /// Opens a store reader decoding the documents with the given codec.
+    ///
+    /// The codec must be the one the store was written with.
+    pub fn open_with_codec(
+        store_file: FileSlice,
+        codec: Arc<dyn DocumentCodec>,
+    ) -> io::Result<StoreReader> {
+        let (data_file, offset_index_file, dictionary_file) = split_file(store_file)?;
         let index_data = offset_index_file.read_bytes()?;
-        let space_usage = StoreSpaceUsage::new(data_file.len(), offset_index_file.len());
+        let dictionary_len = dictionary_file.as_ref().map(FileSlice::len).unwrap_or(0);
+        let space_usage =
+            StoreSpaceUsage::new(data_file.len() + dictionary_len, offset_index_file.len());
         let skip_index = SkipIndex::open(index_data);
+        let string_dictionary = if let Some(dictionary_file) = dictionary_file {
+            Some(StringDictionary::open(
+                dictionary_file.read_bytes()?.as_slice(),
+            )?)
+        } else {
+            None
+        };
         Ok(StoreReader {
             data: data_file,
             cache: Arc::new(Mutex::new(LruCache::new(LRU_CACHE_CAPACITY))),
@@ -43,9 +66,15 @@ impl StoreReader {
             cache_misses: Default::default(),
             skip_index: Arc::new(skip_index),
             space_usage,
+            codec,
+            string_dictionary,
         })
     }
 
+    pub(crate) fn has_string_dictionary(&self) -> bool {
+        self.string_dictionary.is_some()
+    }
+
     pub(crate) fn block_checkpoints<'a>(&'a self) -> impl Iterator<Item = Checkpoint> + 'a {
         self.skip_index.checkpoints()
     }
@@ -107,7 +136,13 @@ impl StoreReader {
 
         let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
         cursor = &cursor[..doc_length];
-        Ok(Document::deserialize(&mut cursor)?)
+        if let Some(string_dictionary) = &self.string_dictionary {
+            let interned_field_values =
+                string_dictionary.read_interned_field_values(&mut cursor)?;
+            let doc = self.codec.deserialize(&mut cursor)?;
+            return Ok(insert_interned_field_values(doc, interned_field_values)?);
+        }
+        Ok(self.codec.deserialize(&mut cursor)?)
     }
 
     /// Summarize total space usage of this store reader.
@@ -116,12 +151,33 @@ impl StoreReader {
     }
 }
 
-fn split_file(data: FileSlice) -> io::Result<(FileSlice, FileSlice)> {
+/// Splits the store file into its data, its skip index
+/// and its dictionary of interned strings, if any.
+fn split_file(data: FileSlice) -> io::Result<(FileSlice, FileSlice, Option<FileSlice>)> {
     let (data, footer_len_bytes) = data.split_from_end(size_of::<u64>());
     let serialized_offset: OwnedBytes = footer_len_bytes.read_bytes()?;
     let mut serialized_offset_buf = serialized_offset.as_slice();
-    let offset = u64::deserialize(&mut serialized_offset_buf)? as usize;
-    Ok(data.split(offset))
+    let footer = u64::deserialize(&mut serialized_offset_buf)?;
+    let offset = (footer & !DICTIONARY_FLAG) as usize;
+    let (data, index) = data.split(offset);
+    if footer & DICTIONARY_FLAG == 0 {
+        return Ok((data, index, None));
+    }
+    let (index, dictionary_offset_bytes) = index.split_from_end(size_of::<u64>());
+    let serialized_dictionary_offset: OwnedBytes = dictionary_offset_bytes.read_bytes()?;
+    let mut serialized_dictionary_offset_buf = serialized_dictionary_offset.as_slice();
+    let dictionary_offset = u64::deserialize(&mut serialized_dictionary_offset_buf)? as usize;
+    let index_len = dictionary_offset
+        .checked_sub(offset)
+        .filter(|&index_len| index_len <= index.len())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid store dictionary offset {}", dictionary_offset),
+            )
+        })?;
+    let (index, dictionary) = index.split(index_len);
+    Ok((data, index, Some(dictionary)))
 }
 
 #[cfg(test)]
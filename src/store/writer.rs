@@ -1,14 +1,17 @@
 use super::compress;
+use super::dictionary::{StringDictionaryBuilder, DICTIONARY_FLAG};
 use super::index::SkipIndexBuilder;
 use super::StoreReader;
+use super::{DefaultCodec, DocumentCodec};
 use crate::common::CountingWriter;
 use crate::common::{BinarySerializable, VInt};
 use crate::directory::TerminatingWrite;
 use crate::directory::WritePtr;
-use crate::schema::Document;
+use crate::schema::{DatePrecision, Document, Field};
 use crate::store::index::Checkpoint;
 use crate::DocId;
 use std::io::{self, Write};
+use std::sync::Arc;
 
 const BLOCK_SIZE: usize = 16_384;
 
@@ -27,6 +30,10 @@ pub struct StoreWriter {
     writer: CountingWriter<WritePtr>,
     intermediary_buffer: Vec<u8>,
     current_block: Vec<u8>,
+    codec: Arc<dyn DocumentCodec>,
+    interned_fields: Vec<Field>,
+    string_dictionary: StringDictionaryBuilder,
+    date_precisions: Vec<(Field, DatePrecision)>,
 }
 
 impl StoreWriter {
@@ -35,6 +42,15 @@ impl StoreWriter {
     /// The store writer will writes blocks on disc as
     /// document are added.
     pub fn new(writer: WritePtr) -> StoreWriter {
+        StoreWriter::with_codec(writer, Arc::new(DefaultCodec::default()))
+    }
+
+    /// Create a store writer encoding the documents with the given codec.
+    ///
+    /// The resulting store must be opened with
+    /// [`StoreReader::open_with_codec`](./struct.StoreReader.html#method.open_with_codec)
+    /// and the same codec.
+    pub fn with_codec(writer: WritePtr, codec: Arc<dyn DocumentCodec>) -> StoreWriter {
         StoreWriter {
             doc: 0,
             first_doc_in_block: 0,
@@ -42,17 +58,150 @@ impl StoreWriter {
             writer: CountingWriter::wrap(writer),
             intermediary_buffer: Vec::new(),
             current_block: Vec::new(),
+            codec,
+            interned_fields: Vec::new(),
+            string_dictionary: StringDictionaryBuilder::default(),
+            date_precisions: Vec::new(),
         }
     }
 
+    /// Sets the size above which `Value::Bytes` are individually
+    /// compressed before being stored.
+    ///
+    /// Smaller bytes values are stored as is. By default (`None`), bytes
+    /// values are never compressed individually.
+    /// Compressed bytes values are decompressed transparently when
+    /// reading documents back.
+    ///
+    /// The threshold is a property of the codec, see
+    /// [`DocumentCodec::with_bytes_compression`](./trait.DocumentCodec.html#method.with_bytes_compression).
+    /// An error is returned if the codec of the store writer does not support
+    /// compressing bytes values, unless `bytes_compression_threshold` is `None`.
+    pub fn set_bytes_compression_threshold(
+        &mut self,
+        bytes_compression_threshold: Option<usize>,
+    ) -> io::Result<()> {
+        match self
+            .codec
+            .with_bytes_compression(bytes_compression_threshold)
+        {
+            Some(codec) => {
+                self.codec = codec;
+                Ok(())
+            }
+            None if bytes_compression_threshold.is_none() => Ok(()),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The codec of the doc store does not support compressing bytes values",
+            )),
+        }
+    }
+
+    /// Sets the fields whose strings are interned.
+    ///
+    /// Each distinct string of these fields is written once, in a
+    /// dictionary at the end of the store, and the documents only
+    /// refer to it by its id. This reduces the size of the store
+    /// dramatically for low-cardinality fields, such as a country
+    /// code or a status, but inflates it for the fields whose
+    /// strings are mostly distinct, as the dictionary is never compressed.
+    ///
+    /// Interned strings are resolved transparently when reading documents back.
+    ///
+    /// # Panics
+    /// If documents were stored already: interning changes the layout of the store.
+    pub fn set_interned_fields(&mut self, interned_fields: Vec<Field>) {
+        assert_eq!(
+            self.doc, 0,
+            "The interned fields must be set before storing any document."
+        );
+        self.interned_fields = interned_fields;
+    }
+
+    /// Sets the precision the dates of each of the given fields
+    /// are truncated to before being stored.
+    ///
+    /// Truncated dates are serialized more compactly: a date truncated to
+    /// the second, the millisecond or the microsecond takes 4 bytes less
+    /// than a date with a nanosecond part. The truncated part of the dates
+    /// is lost, it is not restored when reading documents back.
+    /// Dates nested in arrays or json objects are never truncated.
+    pub fn set_date_precisions(&mut self, date_precisions: Vec<(Field, DatePrecision)>) {
+        self.date_precisions = date_precisions;
+    }
+
     /// Store a new document.
     ///
     /// The document id is implicitely the number of times
     /// this method has been called.
     ///
     pub fn store(&mut self, stored_document: &Document) -> io::Result<()> {
+        self.store_with_raw_field_values(stored_document, &[], 0)
+    }
+
+    /// Stores a document made of the field values of `stored_document`, followed
+    /// by `num_raw_field_values` field values already serialized with
+    /// `BinarySerializable` in `raw_field_values`.
+    ///
+    /// The raw field values are spliced as is into the store, without being parsed
+    /// and encoded again. This avoids decoding field values that are only going to
+    /// be stored, e.g. when they come from a cache. As a consequence, they are not
+    /// interned, truncated or compressed, whatever the settings of the store writer.
+    ///
+    /// The caller is responsible for `raw_field_values` being the encoding of
+    /// exactly `num_raw_field_values` field values: the document is otherwise
+    /// stored as bytes that cannot be read back.
+    /// An error is returned if the codec of the store writer does not support
+    /// raw field values. The [`DefaultCodec`](./struct.DefaultCodec.html) does.
+    pub fn store_raw(
+        &mut self,
+        stored_document: &Document,
+        raw_field_values: &[u8],
+        num_raw_field_values: usize,
+    ) -> io::Result<()> {
+        self.store_with_raw_field_values(stored_document, raw_field_values, num_raw_field_values)
+    }
+
+    fn store_with_raw_field_values(
+        &mut self,
+        stored_document: &Document,
+        raw_field_values: &[u8],
+        num_raw_field_values: usize,
+    ) -> io::Result<()> {
         self.intermediary_buffer.clear();
-        stored_document.serialize(&mut self.intermediary_buffer)?;
+        let truncated_document;
+        let stored_document = if self.date_precisions.is_empty() {
+            stored_document
+        } else {
+            let mut document = stored_document.clone();
+            for &(field, precision) in &self.date_precisions {
+                document.truncate_dates(field, precision);
+            }
+            truncated_document = document;
+            &truncated_document
+        };
+        let interned_document;
+        let stored_document = if self.interned_fields.is_empty() {
+            stored_document
+        } else {
+            interned_document = self.string_dictionary.intern_document(
+                stored_document,
+                &self.interned_fields,
+                &mut self.intermediary_buffer,
+            )?;
+            &interned_document
+        };
+        if num_raw_field_values == 0 {
+            self.codec
+                .serialize(stored_document, &mut self.intermediary_buffer)?;
+        } else {
+            self.codec.serialize_with_raw_field_values(
+                stored_document,
+                raw_field_values,
+                num_raw_field_values,
+                &mut self.intermediary_buffer,
+            )?;
+        }
         let doc_num_bytes = self.intermediary_buffer.len();
         VInt(doc_num_bytes as u64).serialize(&mut self.current_block)?;
         self.current_block
@@ -68,7 +217,25 @@ impl StoreWriter {
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
+    ///
+    /// The ids of interned strings are specific to each store, so the
+    /// documents are decoded and stored one by one instead if either
+    /// store has interned fields.
     pub fn stack(&mut self, store_reader: &StoreReader) -> io::Result<()> {
+        if store_reader.has_string_dictionary() || !self.interned_fields.is_empty() {
+            let num_docs = store_reader
+                .block_checkpoints()
+                .last()
+                .map(|checkpoint| checkpoint.end_doc)
+                .unwrap_or(0);
+            for doc_id in 0..num_docs {
+                let doc = store_reader
+                    .get(doc_id)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+                self.store(&doc)?;
+            }
+            return Ok(());
+        }
         if !self.current_block.is_empty() {
             self.write_and_compress_block()?;
         }
@@ -119,14 +286,22 @@ impl StoreWriter {
     /// Finalized the store writer.
     ///
     /// Compress the last unfinished block if any,
-    /// and serializes the skip list index on disc.
+    /// and serializes the skip list index on disc,
+    /// followed by the dictionary of the interned strings if there are interned fields.
     pub fn close(mut self) -> io::Result<()> {
         if !self.current_block.is_empty() {
             self.write_and_compress_block()?;
         }
         let header_offset: u64 = self.writer.written_bytes() as u64;
         self.offset_index_writer.write(&mut self.writer)?;
-        header_offset.serialize(&mut self.writer)?;
+        if self.interned_fields.is_empty() {
+            header_offset.serialize(&mut self.writer)?;
+        } else {
+            let dictionary_offset: u64 = self.writer.written_bytes() as u64;
+            self.string_dictionary.serialize(&mut self.writer)?;
+            dictionary_offset.serialize(&mut self.writer)?;
+            (header_offset | DICTIONARY_FLAG).serialize(&mut self.writer)?;
+        }
         self.writer.terminate()
     }
 }
@@ -1,9 +1,10 @@
 use crate::tokenizer::{BoxTokenStream, Token, TokenStream, TokenStreamChain};
+use crate::TantivyError;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
 /// Struct representing pre-tokenized text
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct PreTokenizedString {
     /// Original text
     pub text: String,
@@ -11,6 +12,56 @@ pub struct PreTokenizedString {
     pub tokens: Vec<Token>,
 }
 
+impl PreTokenizedString {
+    /// Creates a `PreTokenizedString`, checking that the tokens are consistent
+    /// with the text.
+    ///
+    /// The offsets of each token must be within the text, with
+    /// `offset_from <= offset_to`, and fall on char boundaries.
+    /// The positions of the tokens must be non-decreasing.
+    ///
+    /// Returns an `InvalidArgument` error describing the first faulty token otherwise.
+    pub fn from_tokens(text: String, tokens: Vec<Token>) -> crate::Result<PreTokenizedString> {
+        let mut previous_position = 0;
+        for (ord, token) in tokens.iter().enumerate() {
+            if token.offset_from > token.offset_to || token.offset_to > text.len() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Token #{} {:?} has offsets {}..{} out of the bounds of a text of {} bytes",
+                    ord,
+                    token.text,
+                    token.offset_from,
+                    token.offset_to,
+                    text.len()
+                )));
+            }
+            if !text.is_char_boundary(token.offset_from) || !text.is_char_boundary(token.offset_to)
+            {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Token #{} {:?} has offsets {}..{} not on char boundaries",
+                    ord, token.text, token.offset_from, token.offset_to
+                )));
+            }
+            if token.position < previous_position {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Token #{} {:?} has position {}, lower than the position {} of the previous token",
+                    ord, token.text, token.position, previous_position
+                )));
+            }
+            previous_position = token.position;
+        }
+        Ok(PreTokenizedString { text, tokens })
+    }
+
+    /// Returns a `TokenStream` yielding the tokens, in order.
+    ///
+    /// The stream can be fed to `TokenFilter`s, so that the tokens
+    /// go through the same analysis as the tokens of a regular text.
+    /// The tokens are copied: the stream does not borrow the string.
+    pub fn token_stream(&self) -> PreTokenizedStream {
+        PreTokenizedStream::from(self.clone())
+    }
+}
+
 impl Ord for PreTokenizedString {
     fn cmp(&self, other: &Self) -> Ordering {
         self.text.cmp(&other.text)
@@ -92,7 +143,7 @@ mod tests {
 
     use super::*;
 
-    use crate::tokenizer::Token;
+    use crate::tokenizer::{LowerCaser, Token, TokenFilter};
 
     #[test]
     fn test_tokenized_stream() {
@@ -125,6 +176,25 @@ mod tests {
         assert!(!token_stream.advance());
     }
 
+    #[test]
+    fn test_pre_tokenized_string_token_stream() {
+        let tok_text = PreTokenizedString {
+            text: String::from("Hello World"),
+            tokens: vec![token(0, 5, 0, "Hello"), token(6, 11, 1, "World")],
+        };
+        let mut token_stream = tok_text.token_stream();
+        for expected_token in &tok_text.tokens {
+            assert!(token_stream.advance());
+            assert_eq!(token_stream.token(), expected_token);
+        }
+        assert!(!token_stream.advance());
+        let mut lowercased_texts = Vec::new();
+        LowerCaser
+            .transform(tok_text.token_stream().into())
+            .process(&mut |token: &Token| lowercased_texts.push(token.text.clone()));
+        assert_eq!(lowercased_texts, vec!["hello", "world"]);
+    }
+
     #[test]
     fn test_chain_tokenized_strings() {
         let tok_text = PreTokenizedString {
@@ -188,4 +258,58 @@ mod tests {
         }
         assert!(!token_stream.advance());
     }
+
+    fn token(offset_from: usize, offset_to: usize, position: usize, text: &str) -> Token {
+        Token {
+            offset_from,
+            offset_to,
+            position,
+            text: String::from(text),
+            position_length: 1,
+        }
+    }
+
+    #[test]
+    fn test_from_tokens() {
+        let tokens = vec![
+            token(0, 1, 0, "A"),
+            token(2, 3, 1, "a"),
+            token(2, 3, 1, "b"),
+        ];
+        let tok_text =
+            PreTokenizedString::from_tokens(String::from("A a"), tokens.clone()).unwrap();
+        assert_eq!(tok_text.text, "A a");
+        assert_eq!(tok_text.tokens, tokens);
+        assert!(PreTokenizedString::from_tokens(String::new(), vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_from_tokens_out_of_bounds() {
+        let err = PreTokenizedString::from_tokens(String::from("A a"), vec![token(2, 4, 0, "a")])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Token #0 \"a\" has offsets 2..4 out of the bounds of a text of 3 bytes'"
+        );
+        assert!(
+            PreTokenizedString::from_tokens(String::from("A a"), vec![token(2, 1, 0, "a")])
+                .is_err()
+        );
+        assert!(
+            PreTokenizedString::from_tokens(String::from("é"), vec![token(0, 1, 0, "e")]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_tokens_out_of_order() {
+        let err = PreTokenizedString::from_tokens(
+            String::from("A a"),
+            vec![token(0, 1, 1, "A"), token(2, 3, 0, "a")],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Token #1 \"a\" has position 0, lower than the position 1 of the previous token'"
+        );
+    }
 }